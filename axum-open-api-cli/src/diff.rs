@@ -0,0 +1,218 @@
+use crate::spec::operations;
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use serde_json::Value;
+use std::fmt::Write;
+use syn::{Item, Path};
+
+const METHODS: [&str; 8] = [
+    "GET", "PUT", "POST", "DELETE", "OPTIONS", "HEAD", "PATCH", "TRACE",
+];
+
+/// The `validate_routes!` invocations of a source file.
+pub struct Invocation {
+    /// The `path = "..."` of the spec
+    pub spec_path: Option<String>,
+    /// The declared operations, like `("GET", "/users/{user_id}")`
+    pub operations: Vec<(String, String)>,
+    /// The structs of the declared operations, with the modules of the invocation, like
+    /// `users::GetUser`
+    pub structs: Vec<String>,
+}
+
+/// Finds the `validate_routes!` invocations in the source, also in inline modules.
+pub fn find_invocations(source: &str) -> Result<Vec<Invocation>, String> {
+    let file = syn::parse_file(source).map_err(|e| format!("Could not parse source: {e}"))?;
+    let mut invocations = Vec::new();
+    find_in_items(&file.items, &mut invocations);
+    Ok(invocations)
+}
+
+fn find_in_items(items: &[Item], invocations: &mut Vec<Invocation>) {
+    for item in items {
+        match item {
+            Item::Macro(item) if is_validate_routes(&item.mac.path) => {
+                invocations.push(parse_invocation(item.mac.tokens.clone()));
+            }
+            Item::Mod(item) => {
+                if let Some((_, items)) = &item.content {
+                    find_in_items(items, invocations);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether the path is `validate_routes` or `axum_open_api::validate_routes`.
+fn is_validate_routes(path: &Path) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == "validate_routes")
+}
+
+/// Scans the tokens of the invocation for the spec path and the operations, like
+/// `GET /users/{user_id} as pub GetUser;`, also in the modules of the invocation.
+fn parse_invocation(tokens: TokenStream) -> Invocation {
+    let mut invocation = Invocation {
+        spec_path: None,
+        operations: Vec::new(),
+        structs: Vec::new(),
+    };
+    scan(tokens, &[], &mut invocation);
+    invocation
+}
+
+fn scan(tokens: TokenStream, modules: &[String], invocation: &mut Invocation) {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            // `path = "../my-api.yaml";`
+            TokenTree::Ident(ident) if ident == "path" && invocation.spec_path.is_none() => {
+                if let Some(TokenTree::Literal(literal)) = tokens.get(i + 2) {
+                    let literal = literal.to_string();
+                    invocation.spec_path = Some(literal.trim_matches('"').to_owned());
+                }
+            }
+            // `GET /users/{user_id} as`, but not `POST webhook newPet as`
+            TokenTree::Ident(ident) if METHODS.contains(&ident.to_string().as_str()) => {
+                let mut path = String::new();
+                let mut j = i + 1;
+                loop {
+                    match tokens.get(j) {
+                        Some(TokenTree::Punct(punct)) if punct.as_char() == '/' => path.push('/'),
+                        Some(TokenTree::Ident(ident)) if ident == "as" => break,
+                        Some(TokenTree::Ident(ident)) => path.push_str(&ident.to_string()),
+                        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                            write!(path, "{{{}}}", group.stream()).unwrap();
+                        }
+                        _ => break,
+                    }
+                    j += 1;
+                }
+                if path.starts_with('/') {
+                    invocation.operations.push((ident.to_string(), path));
+                    if let Some(name) = struct_name(&tokens[j + 1..]) {
+                        let path = modules.iter().chain([&name]).cloned().collect::<Vec<_>>();
+                        invocation.structs.push(path.join("::"));
+                    }
+                    i = j;
+                }
+            }
+            // `mod users { ... }`
+            TokenTree::Ident(ident) if ident == "mod" => {
+                if let (Some(TokenTree::Ident(name)), Some(TokenTree::Group(group))) =
+                    (tokens.get(i + 1), tokens.get(i + 2))
+                {
+                    let modules = [modules, &[name.to_string()]].concat();
+                    scan(group.stream(), &modules, invocation);
+                    i += 2;
+                }
+            }
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                scan(group.stream(), modules, invocation);
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// The name of the struct after `as`, like `pub(crate) GetUser;`.
+fn struct_name(tokens: &[TokenTree]) -> Option<String> {
+    let mut tokens = tokens.iter().skip_while(|token| match token {
+        TokenTree::Ident(ident) => ident == "pub",
+        TokenTree::Group(group) => group.delimiter() == Delimiter::Parenthesis,
+        _ => false,
+    });
+    match tokens.next()? {
+        TokenTree::Ident(ident) => Some(ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Reports the operations of the spec that are not declared, and the declared operations that
+/// are not in the spec, or `None` if they are the same.
+pub fn diff(spec: &Value, declared: &[(String, String)]) -> Option<String> {
+    let in_spec: Vec<(String, String)> = operations(spec)
+        .into_iter()
+        .map(|operation| (operation.method, operation.path))
+        .collect();
+    let undeclared: Vec<_> = in_spec.iter().filter(|op| !declared.contains(op)).collect();
+    let unknown: Vec<_> = declared.iter().filter(|op| !in_spec.contains(op)).collect();
+    if undeclared.is_empty() && unknown.is_empty() {
+        return None;
+    }
+
+    let mut out = String::new();
+    if !undeclared.is_empty() {
+        writeln!(out, "Operations of the spec that are not declared:").unwrap();
+        for (method, path) in undeclared {
+            writeln!(out, "    {method} {path}").unwrap();
+        }
+    }
+    if !unknown.is_empty() {
+        writeln!(out, "Declared operations that are not in the spec:").unwrap();
+        for (method, path) in unknown {
+            writeln!(out, "    {method} {path}").unwrap();
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+mod api {
+    axum_open_api::validate_routes!(
+        path = "api.yaml";
+
+        GET /users/{user_id} as pub GetUser;
+        mod admin {
+            DELETE /users as pub(crate) DeleteUsers;
+        }
+        POST webhook newUser as pub NewUser;
+    );
+}
+"#;
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: Users
+  version: 0.0.1
+paths:
+  /users/{user_id}:
+    get: {}
+    put: {}
+"#;
+
+    #[test]
+    fn invocations() {
+        let invocations = find_invocations(SOURCE).unwrap();
+        assert_eq!(invocations.len(), 1);
+        assert_eq!(invocations[0].spec_path.as_deref(), Some("api.yaml"));
+        let operations = [("GET", "/users/{user_id}"), ("DELETE", "/users")]
+            .map(|(method, path)| (method.to_owned(), path.to_owned()));
+        assert_eq!(invocations[0].operations, operations);
+        assert_eq!(invocations[0].structs, ["GetUser", "admin::DeleteUsers"]);
+    }
+
+    #[test]
+    fn differences() {
+        let spec = serde_yaml::from_str(SPEC).unwrap();
+        let invocations = find_invocations(SOURCE).unwrap();
+        let expected = "\
+Operations of the spec that are not declared:
+    PUT /users/{user_id}
+Declared operations that are not in the spec:
+    DELETE /users
+";
+        assert_eq!(diff(&spec, &invocations[0].operations).unwrap(), expected);
+        let declared = [("GET", "/users/{user_id}"), ("PUT", "/users/{user_id}")]
+            .map(|(method, path)| (method.to_owned(), path.to_owned()));
+        assert_eq!(diff(&spec, &declared), None);
+    }
+}
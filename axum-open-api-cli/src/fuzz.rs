@@ -0,0 +1,53 @@
+use convert_case::{Case, Casing};
+use std::fmt::Write;
+
+/// A `cargo-fuzz` target of an operation.
+pub struct FuzzTarget {
+    /// The name of the target, like `get_user`
+    pub name: String,
+    pub source: String,
+}
+
+/// Generates a `cargo-fuzz` target for every struct, which feeds the fuzzer input through its
+/// extractor with `axum_open_api::fuzz::extract`. The module is the path of the module with the
+/// `validate_routes!` invocation, like `my_service::api`.
+pub fn fuzz_targets(module: &str, structs: &[String]) -> Vec<FuzzTarget> {
+    structs
+        .iter()
+        .map(|path| {
+            let struct_name = path.rsplit("::").next().unwrap_or(path);
+            let mut source = String::new();
+            writeln!(source, "#![no_main]").unwrap();
+            writeln!(source).unwrap();
+            writeln!(source, "use libfuzzer_sys::fuzz_target;").unwrap();
+            writeln!(source, "use {module}::{path};").unwrap();
+            writeln!(source).unwrap();
+            writeln!(source, "fuzz_target!(|data: &[u8]| {{").unwrap();
+            writeln!(
+                source,
+                "    axum_open_api::fuzz::extract::<{struct_name}, _>(data);"
+            )
+            .unwrap();
+            writeln!(source, "}});").unwrap();
+            FuzzTarget {
+                name: path.replace("::", "_").to_case(Case::Snake),
+                source,
+            }
+        })
+        .collect()
+}
+
+/// The `[[bin]]` entries of the targets, for the `Cargo.toml` of the fuzz crate.
+pub fn bin_entries(targets: &[FuzzTarget]) -> String {
+    let mut out = String::new();
+    for target in targets {
+        writeln!(out, "[[bin]]").unwrap();
+        writeln!(out, "name = {:?}", target.name).unwrap();
+        writeln!(out, "path = \"fuzz_targets/{}.rs\"", target.name).unwrap();
+        writeln!(out, "test = false").unwrap();
+        writeln!(out, "doc = false").unwrap();
+        writeln!(out, "bench = false").unwrap();
+        writeln!(out).unwrap();
+    }
+    out
+}
@@ -0,0 +1,184 @@
+//! `cargo oapi`, the companion of `validate_routes!`.
+//!
+//! - `cargo oapi scaffold <spec> [<output>]` prints the handler stubs of every operation of the
+//!   spec, or creates the output file with them.
+//! - `cargo oapi diff <source> [<spec>]` reports the operations of the spec that are not
+//!   declared in the `validate_routes!` invocations of the source file, and the other way around.
+//! - `cargo oapi breaking <old> <new>` reports the changes of the new spec that break clients of
+//!   the old one.
+//! - `cargo oapi fuzz <source> <module> [<output>]` creates a `cargo-fuzz` target for every
+//!   operation declared in the source file.
+
+mod diff;
+mod fuzz;
+mod scaffold;
+mod spec;
+use axum_open_api::{breaking::breaking_changes, spec::SpecDocument};
+use diff::{diff, find_invocations};
+use fuzz::{bin_entries, fuzz_targets};
+use scaffold::scaffold;
+use spec::load_spec;
+use std::{env, fs, path::Path, process::ExitCode};
+
+const USAGE: &str = "Usage:
+    cargo oapi scaffold <spec> [<output>]    Scaffolds the handler stubs of the spec
+    cargo oapi diff <source> [<spec>]        Reports the operations missing in the spec or source
+    cargo oapi breaking <old> <new>          Reports the breaking changes of the new spec
+    cargo oapi fuzz <source> <module> [<output>]
+                                             Creates the fuzz targets of the declared operations";
+
+fn main() -> ExitCode {
+    // As a cargo subcommand, cargo passes `oapi` as the first argument
+    let args: Vec<String> = env::args()
+        .skip(1)
+        .skip_while(|arg| arg == "oapi")
+        .collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["scaffold", spec_path] => load_spec(spec_path).map(|spec| {
+            print!("{}", scaffold(spec_path, &spec));
+        }),
+        ["scaffold", spec_path, output] => {
+            load_spec(spec_path).and_then(|spec| create(output, &scaffold(spec_path, &spec)))
+        }
+        ["diff", source_path] => run_diff(source_path, None),
+        ["diff", source_path, spec_path] => run_diff(source_path, Some(spec_path)),
+        ["breaking", old_path, new_path] => run_breaking(old_path, new_path),
+        ["fuzz", source_path, module] => run_fuzz(source_path, module, "fuzz"),
+        ["fuzz", source_path, module, output] => run_fuzz(source_path, module, output),
+        _ => Err(USAGE.to_owned()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Creates the file with the contents, without overwriting an existing file.
+fn create(path: &str, contents: &str) -> Result<(), String> {
+    if Path::new(path).exists() {
+        return Err(format!("{path} already exists"));
+    }
+    fs::write(path, contents).map_err(|e| format!("Could not write {path}: {e}"))
+}
+
+/// Diffs the operations of every `validate_routes!` invocation in the source file with those of
+/// its spec, which fails if any differ.
+fn run_diff(source_path: &str, spec_path: Option<&str>) -> Result<(), String> {
+    let source = fs::read_to_string(source_path)
+        .map_err(|e| format!("Could not read {source_path}: {e}"))?;
+    let invocations = find_invocations(&source)?;
+    if invocations.is_empty() {
+        return Err(format!("No `validate_routes!` found in {source_path}"));
+    }
+
+    let mut report = String::new();
+    for invocation in invocations {
+        let spec_path = spec_path
+            .map(str::to_owned)
+            .or(invocation.spec_path)
+            .ok_or_else(|| format!("No spec path found in {source_path}"))?;
+        // Like the macro, the path is relative to the crate or to the workspace
+        let resolved = [spec_path.clone(), format!("../{spec_path}")]
+            .into_iter()
+            .find(|path| Path::new(path).is_file())
+            .unwrap_or(spec_path);
+        let spec = load_spec(&resolved)?;
+        if let Some(diff) = diff(&spec, &invocation.operations) {
+            report.push_str(&format!("{resolved}:\n{diff}"));
+        }
+    }
+    if report.is_empty() {
+        println!("The declared operations match the spec");
+        Ok(())
+    } else {
+        Err(report.trim_end().to_owned())
+    }
+}
+
+/// Reports the breaking changes between the specs, which fails if there are any.
+fn run_breaking(old_path: &str, new_path: &str) -> Result<(), String> {
+    let parse = |path: &str| {
+        let contents =
+            fs::read_to_string(path).map_err(|e| format!("Could not read {path}: {e}"))?;
+        SpecDocument::parse(&contents).map_err(|e| format!("Could not parse {path}: {e}"))
+    };
+    let changes = breaking_changes(&parse(old_path)?, &parse(new_path)?);
+    if changes.is_empty() {
+        println!("No breaking changes");
+        return Ok(());
+    }
+    let report: Vec<String> = changes.iter().map(ToString::to_string).collect();
+    Err(format!("Breaking changes:\n    {}", report.join("\n    ")))
+}
+
+/// Creates a `cargo-fuzz` target for every operation declared in the `validate_routes!`
+/// invocations of the source file, in `fuzz_targets` of the output directory, and prints the
+/// `[[bin]]` entries of the created targets. Existing targets are not overwritten.
+fn run_fuzz(source_path: &str, module: &str, output: &str) -> Result<(), String> {
+    let source = fs::read_to_string(source_path)
+        .map_err(|e| format!("Could not read {source_path}: {e}"))?;
+    let structs: Vec<String> = find_invocations(&source)?
+        .into_iter()
+        .flat_map(|invocation| invocation.structs)
+        .collect();
+    if structs.is_empty() {
+        return Err(format!("No operations declared in {source_path}"));
+    }
+
+    let dir = Path::new(output).join("fuzz_targets");
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+    let mut created = Vec::new();
+    for target in fuzz_targets(module, &structs) {
+        let path = dir.join(format!("{}.rs", target.name));
+        match create(&path.to_string_lossy(), &target.source) {
+            Ok(()) => created.push(target),
+            Err(e) => eprintln!("Skipped: {e}"),
+        }
+    }
+    print!("{}", bin_entries(&created));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+paths:
+  /users:
+    get:
+      responses:
+        '200':
+          description: The users
+    post:
+      responses:
+        '201':
+          description: Created
+"#;
+
+    /// Writes the spec to a file in the temporary directory, and returns its path.
+    fn write_spec(name: &str, spec: &str) -> String {
+        let path = env::temp_dir().join(format!("cargo-oapi-{}-{name}", std::process::id()));
+        fs::write(&path, spec).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn breaking() {
+        let old = write_spec("old.yaml", SPEC);
+        let new = write_spec("new.yaml", &SPEC.replace("    post:", "    put:"));
+        assert_eq!(run_breaking(&old, &old), Ok(()));
+        assert_eq!(
+            run_breaking(&old, &new),
+            Err("Breaking changes:\n    POST /users: operation removed".to_owned())
+        );
+        fs::remove_file(old).unwrap();
+        fs::remove_file(new).unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+use crate::spec::operations;
+use convert_case::{Case, Casing};
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Scaffolds a `validate_routes!` invocation declaring every operation of the spec, and an
+/// implementation of the generated `Api` trait with a handler stub for every operation.
+pub fn scaffold(spec_path: &str, spec: &Value) -> String {
+    let operations = operations(spec);
+    let mut out = String::new();
+
+    writeln!(out, "use axum_open_api::validate_routes;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "validate_routes!(").unwrap();
+    writeln!(out, "    path = {spec_path:?};").unwrap();
+    writeln!(out, "    api_trait = Api;").unwrap();
+    writeln!(out).unwrap();
+    for operation in &operations {
+        let struct_name = operation.struct_name();
+        writeln!(
+            out,
+            "    {} {} as pub {struct_name};",
+            operation.method, operation.path
+        )
+        .unwrap();
+    }
+    writeln!(out, ");").unwrap();
+    writeln!(out).unwrap();
+
+    writeln!(out, "#[derive(Clone)]").unwrap();
+    writeln!(out, "pub struct Service;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "#[axum::async_trait]").unwrap();
+    writeln!(out, "impl Api for Service {{").unwrap();
+    for (i, operation) in operations.iter().enumerate() {
+        if i > 0 {
+            writeln!(out).unwrap();
+        }
+        let struct_name = operation.struct_name();
+        if let Some(summary) = &operation.summary {
+            writeln!(out, "    /// {summary}").unwrap();
+        }
+        writeln!(
+            out,
+            "    async fn {}(&self, req: {struct_name}) -> {struct_name}Response {{",
+            struct_name.to_case(Case::Snake)
+        )
+        .unwrap();
+        writeln!(out, "        todo!()").unwrap();
+        writeln!(out, "    }}").unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: Users
+  version: 0.0.1
+paths:
+  /users/{user_id}:
+    get:
+      operationId: getUser
+      summary: Gets a user
+    delete: {}
+"#;
+
+    #[test]
+    fn handler_stubs() {
+        let spec = serde_yaml::from_str(SPEC).unwrap();
+        let scaffolded = scaffold("api.yaml", &spec);
+        assert!(scaffolded.contains("    path = \"api.yaml\";\n"));
+        // Operations without an operationId are named after their method and path
+        assert!(scaffolded.contains("    GET /users/{user_id} as pub GetUser;\n"));
+        assert!(scaffolded.contains("    DELETE /users/{user_id} as pub DeleteUsersUserId;\n"));
+        assert!(scaffolded.contains(
+            "    /// Gets a user\n    async fn get_user(&self, req: GetUser) -> GetUserResponse {\n"
+        ));
+        assert!(scaffolded.contains(
+            "    async fn delete_users_user_id(&self, req: DeleteUsersUserId) -> \
+            DeleteUsersUserIdResponse {\n"
+        ));
+    }
+}
@@ -0,0 +1,59 @@
+use convert_case::{Case, Casing};
+use serde_json::Value;
+use std::fs;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// An operation of the spec.
+pub struct Operation {
+    /// The method in upper case, like `GET`
+    pub method: String,
+    /// The path as written in the spec, like `/users/{user_id}`
+    pub path: String,
+    pub operation_id: Option<String>,
+    pub summary: Option<String>,
+}
+
+impl Operation {
+    /// The name of the struct of the operation, from its operationId, or else from its method and
+    /// path, like `GetUsersUserId`.
+    pub fn struct_name(&self) -> String {
+        match &self.operation_id {
+            Some(operation_id) => operation_id.to_case(Case::Pascal),
+            None => format!(
+                "{} {}",
+                self.method,
+                self.path.replace(['/', '{', '}'], " ")
+            )
+            .to_case(Case::Pascal),
+        }
+    }
+}
+
+/// Reads and parses the spec at the path, which can be yaml or json.
+pub fn load_spec(path: &str) -> Result<Value, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Could not read OpenAPI spec {path}: {e}"))?;
+    serde_yaml::from_str(&contents).map_err(|e| format!("Could not parse OpenAPI spec {path}: {e}"))
+}
+
+/// The operations of the spec, in the order of their paths.
+pub fn operations(spec: &Value) -> Vec<Operation> {
+    let mut operations = Vec::new();
+    for (path, path_item) in spec["paths"].as_object().into_iter().flatten() {
+        for method in METHODS {
+            let Some(operation) = path_item.get(method) else {
+                continue;
+            };
+            operations.push(Operation {
+                method: method.to_uppercase(),
+                path: path.clone(),
+                operation_id: operation["operationId"].as_str().map(str::to_owned),
+                summary: operation["summary"].as_str().map(str::to_owned),
+            });
+        }
+    }
+    operations
+}
@@ -0,0 +1,222 @@
+use crate::quirks::normalize;
+use oas3::Spec;
+use serde_json::Value;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    env, fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::{Mutex, OnceLock},
+    time::SystemTime,
+};
+use syn::LitStr;
+
+/// A spec that was read and parsed.
+#[derive(Clone)]
+pub struct LoadedSpec {
+    /// The spec exactly as it was written
+    pub contents: String,
+    pub spec: Spec,
+    /// The spec as plain json, for everything `oas3` does not parse, like extensions.
+    pub raw: Value,
+}
+
+/// The parsed specs by their path, with the modification time they were parsed at. The process
+/// of the compiler outlives a single macro invocation, so crates with several invocations parse
+/// the spec once.
+static CACHE: OnceLock<Mutex<HashMap<PathBuf, (SystemTime, LoadedSpec)>>> = OnceLock::new();
+
+/// Reads and parses the spec at the path, or returns it from the cache if the file has not been
+/// modified since. Parsed specs are also kept in the target directory, for the next builds.
+pub fn load_spec(spec_path: &LitStr) -> syn::Result<LoadedSpec> {
+    // Working directory of cargo and rust-analyzer is different.
+    // This is a hack to get around that, and have it work with both.
+    let spec_path_str = expand_env(&spec_path.value()).map_err(|e| err!(spec_path, e))?;
+    let path = [
+        PathBuf::from(&spec_path_str),
+        PathBuf::from(format!("../{spec_path_str}")),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+    .ok_or_else(|| err!(spec_path, "File does not exist at path: {spec_path_str}"))?;
+    let modified = fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(modified) = modified {
+        let cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_at, spec)) = cache.get(&path) {
+            if *cached_at == modified {
+                return Ok(spec.clone());
+            }
+        }
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| err!(spec_path, "Could not read OpenAPI spec: {e}"))?;
+    let raw = match load_compiled(&contents) {
+        Some(raw) => raw,
+        None => {
+            let mut raw = serde_yaml::from_str(&contents)
+                .map_err(|e| err!(spec_path, "Could not parse OpenAPI spec: {e}"))?;
+            normalize(&mut raw);
+            store_compiled(&contents, &raw);
+            raw
+        }
+    };
+    let spec = serde_json::from_value(raw.clone())
+        .map_err(|e| err!(spec_path, "Could not parse OpenAPI spec: {e}"))?;
+    let loaded = LoadedSpec {
+        contents,
+        spec,
+        raw,
+    };
+
+    if let Some(modified) = modified {
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.insert(path, (modified, loaded.clone()));
+    }
+    Ok(loaded)
+}
+
+/// The directory of the compiled specs, which are kept across builds in the target directory of
+/// the workspace. `None` if it can't be found, or with `AXUM_OPEN_API_NO_CACHE` set.
+fn compiled_dir() -> Option<PathBuf> {
+    if env::var_os("AXUM_OPEN_API_NO_CACHE").is_some() {
+        return None;
+    }
+    let target = match env::var_os("CARGO_TARGET_DIR") {
+        Some(target) => PathBuf::from(target),
+        None => {
+            let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR")?);
+            let root = manifest_dir
+                .ancestors()
+                .find(|dir| dir.join("Cargo.lock").is_file())?;
+            root.join("target")
+        }
+    };
+    Some(target.join("axum-open-api"))
+}
+
+/// The path of the compiled spec of the contents, keyed by their hash and by the version of the
+/// macro, which may normalize specs differently.
+fn compiled_path(contents: &str) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    let version = env!("CARGO_PKG_VERSION");
+    let name = format!("{version}-{:016x}-{}.json", hasher.finish(), contents.len());
+    Some(compiled_dir()?.join(name))
+}
+
+/// Loads the normalized json of a spec that was compiled by an earlier build, which is much
+/// faster than parsing large yaml specs again.
+fn load_compiled(contents: &str) -> Option<Value> {
+    let compiled = fs::read_to_string(compiled_path(contents)?).ok()?;
+    serde_json::from_str(&compiled).ok()
+}
+
+/// Stores the normalized json of the spec for later builds. Failing to store it only costs the
+/// time to parse it again, so errors are ignored.
+fn store_compiled(contents: &str, raw: &Value) {
+    let Some(path) = compiled_path(contents) else {
+        return;
+    };
+    let Some(dir) = path.parent() else {
+        return;
+    };
+    // Written to a temporary file first, so parallel builds never read a partial spec
+    let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+    let stored = fs::create_dir_all(dir)
+        .and_then(|()| fs::write(&temporary, raw.to_string()))
+        .and_then(|()| fs::rename(&temporary, &path));
+    if stored.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+}
+
+/// Expands the environment variables in the path, like `$OUT_DIR/openapi.json` or
+/// `${SPEC_PATH}`, for specs that are generated by a build script.
+fn expand_env(path: &str) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = path;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let (name, after) = match rest.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced.find('}').ok_or("Unclosed `${` in spec path")?;
+                (&braced[..end], &braced[end + 1..])
+            }
+            None => {
+                let end = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+        };
+        let value = env::var(name)
+            .map_err(|_| format!("Environment variable `{name}` of the spec path is not set"))?;
+        expanded.push_str(&value);
+        rest = after;
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proc_macro2::Span;
+    use std::{fs::File, time::Duration};
+
+    fn spec(title: &str) -> String {
+        format!("openapi: 3.0.0\ninfo:\n  title: {title}\n  version: 0.0.1\npaths: {{}}\n")
+    }
+
+    fn set_modified(path: &PathBuf, modified: SystemTime) {
+        let file = File::options().write(true).open(path).unwrap();
+        file.set_modified(modified).unwrap();
+    }
+
+    #[test]
+    fn specs_cached_by_modification_time() {
+        let name = format!("axum-open-api-cache-{}.yaml", std::process::id());
+        let path = env::temp_dir().join(name);
+        fs::write(&path, spec("First")).unwrap();
+        let spec_path = LitStr::new(path.to_str().unwrap(), Span::call_site());
+        assert_eq!(load_spec(&spec_path).unwrap().raw["info"]["title"], "First");
+
+        // A spec with the same modification time is not read again
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, spec("Second")).unwrap();
+        set_modified(&path, modified);
+        assert_eq!(load_spec(&spec_path).unwrap().raw["info"]["title"], "First");
+
+        set_modified(&path, modified + Duration::from_secs(1));
+        let title = &load_spec(&spec_path).unwrap().raw["info"]["title"];
+        assert_eq!(title, "Second");
+        fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    fn compiled_specs_reused() {
+        let name = format!("axum-open-api-compiled-{}.yaml", std::process::id());
+        let path = env::temp_dir().join(name);
+        let contents = spec(&format!("Compiled {}", path.display()));
+        fs::write(&path, &contents).unwrap();
+        let Some(compiled) = compiled_path(&contents) else {
+            return;
+        };
+
+        // The compiled spec of the contents is used instead of parsing them
+        let mut raw: Value = serde_yaml::from_str(&contents).unwrap();
+        raw["info"]["title"] = "Stored".into();
+        store_compiled(&contents, &raw);
+        assert_eq!(load_compiled(&contents), Some(raw));
+        let spec_path = LitStr::new(path.to_str().unwrap(), Span::call_site());
+        let title = &load_spec(&spec_path).unwrap().raw["info"]["title"];
+        assert_eq!(title, "Stored");
+        fs::remove_file(&compiled).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,7 +1,7 @@
-use proc_macro2::Ident;
-use quote::ToTokens;
-use syn::{Type, Visibility, Path};
 use crate::parsing::MethodType;
+use proc_macro2::{Ident, TokenStream};
+use quote::ToTokens;
+use syn::{Attribute, Path, Type, Visibility};
 
 /// The root of the codegen tree.
 pub struct Root {
@@ -32,22 +32,54 @@ pub struct MethodItem {
     pub method_ty: MethodType,
     /// The http path
     pub axum_path: String,
+    /// The oapi path, or the name of the webhook
     pub oapi_path: String,
+    /// Whether this is a webhook, which is mounted at a user-chosen path
+    pub webhook: bool,
 
     /// The name of the generated struct
     pub struct_name: Ident,
     /// The visibility of the generated struct
     pub struct_vis: Visibility,
+    /// The doc comments of the declaration, which come before the generated docs
+    pub docs: Vec<Attribute>,
 
     /// The path parameters
     pub path_param_names: Vec<Ident>,
     pub path_param_types: Vec<Type>,
+    pub path_param_styles: Vec<PathStyle>,
+    /// Whether the undecoded path parameters are exposed as `raw_` fields
+    pub raw_path: bool,
+    /// The checks of the path parameters against their schemas
+    pub path_checks: Option<PathChecks>,
     /// The query parameters
     pub query_param_names: Vec<Ident>,
     pub query_param_types: Vec<Type>,
 
+    /// The oapi operationId
+    pub operation_id: Option<String>,
+    /// The oapi tags
+    pub tags: Vec<String>,
+
     /// The body extractor
     pub extractor: Option<Extractor>,
+    /// The security checks
+    pub security: Option<Security>,
+    /// Whether the headers of the request are exposed as the `headers` field
+    pub headers: bool,
+    /// The `Idempotency-Key` header parameter
+    pub idempotency: Option<Idempotency>,
+    /// Whether the `If-Match` and `If-None-Match` headers are declared as parameters
+    pub if_match: bool,
+    pub if_none_match: bool,
+    /// The `Range` header of a download
+    pub byte_range: Option<ByteRange>,
+    /// The standard header parameters, for `typed_headers = true;`
+    pub typed_headers: Vec<TypedHeader>,
+    /// The shared query struct, if the query parameters are not extracted into a local one
+    pub query_path: Option<Path>,
+    /// The query parameters with `allowEmptyValue` or `allowReserved`
+    pub query_options: Option<QueryOptions>,
 
     /// the oapi summary
     pub summary: Option<String>,
@@ -55,12 +87,190 @@ pub struct MethodItem {
     pub description: Option<String>,
 }
 
+/// The checks of the path parameters against their schemas, for `invalid_path_params`.
+pub struct PathChecks {
+    /// The `StatusCode` constant of a mismatch, like `NOT_FOUND`
+    pub status: Ident,
+    /// The string parameters with a `pattern`
+    pub patterns: Vec<(Ident, String)>,
+}
+
+/// How the query parameters with `allowEmptyValue` or `allowReserved` are read, by their names.
+#[derive(Default)]
+pub struct QueryOptions {
+    pub absent_if_empty: Vec<String>,
+    pub true_if_empty: Vec<String>,
+    pub reserved: Vec<String>,
+}
+
+impl QueryOptions {
+    pub fn is_empty(&self) -> bool {
+        self.absent_if_empty.is_empty() && self.true_if_empty.is_empty() && self.reserved.is_empty()
+    }
+}
+
+/// The `style` of a path parameter, which determines the prefix of its segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathStyle {
+    /// `5`
+    Simple,
+    /// `.5`
+    Label,
+    /// `;post_id=5`
+    Matrix,
+}
+
+impl PathStyle {
+    /// The format of the segment of the parameter, like `;post_id={}`.
+    pub fn segment_format(&self, name: &str) -> String {
+        match self {
+            PathStyle::Simple => "{}".to_owned(),
+            PathStyle::Label => ".{}".to_owned(),
+            PathStyle::Matrix => format!(";{name}={{}}"),
+        }
+    }
+
+    /// The runtime function that strips the prefix of the segment and parses the value.
+    fn parse_fn(&self) -> Option<Ident> {
+        match self {
+            PathStyle::Simple => None,
+            PathStyle::Label => Some(format_ident!("label_param")),
+            PathStyle::Matrix => Some(format_ident!("matrix_param")),
+        }
+    }
+}
+
 /// An extractor, like `let Json(body) = req.extract().await?;`
 pub struct Extractor {
     pub body_ty: Type,
     pub body_ident: Ident,
     pub extractor_ty: Type,
     pub rejection_var: Path,
+    /// The type of the body field when it is not `body_ty`, like `BorrowedJson<User>` for
+    /// `#[borrowed]`, which is extracted as it is
+    pub wrapper: Option<Type>,
+}
+
+/// The `Idempotency-Key` header parameter of an operation, which is checked with the
+/// `IdempotencyStore` of the state.
+pub struct Idempotency {
+    /// The operationId, or `METHOD /path` if it has none
+    pub operation: String,
+    pub required: bool,
+}
+
+/// The `Range` header of a download, which is answered with a `PartialContent` response.
+pub struct ByteRange {
+    /// The media type of the response, unless it is a wildcard
+    pub content_type: Option<String>,
+}
+
+/// A standard header parameter, like `User-Agent`, which is extracted as the `TypedHeader` of
+/// its type in `headers`.
+pub struct TypedHeader {
+    /// The name of the field, like `user_agent`
+    pub field_ident: Ident,
+    /// The type in `headers`, like `UserAgent`
+    pub header_ty: Ident,
+    pub required: bool,
+}
+
+/// The security requirements of an operation. A request must satisfy at least one of the
+/// alternatives, and an alternative is satisfied when all of its checks pass.
+pub struct Security {
+    pub alternatives: Vec<Vec<SchemeCheck>>,
+}
+
+/// A check of a single security scheme, like `let o_auth_2: security::OAuth2 = ...;`
+pub struct SchemeCheck {
+    pub scheme_name: String,
+    pub scheme_ty: Type,
+    /// The name of the field on the generated struct
+    pub field_ident: Ident,
+    /// The required scopes; only checked for oauth2 schemes
+    pub scopes: Vec<String>,
+}
+
+impl Security {
+    /// The fields of the generated struct. These are only optional if there are multiple
+    /// alternatives, since then not every scheme is extracted.
+    fn fields(&self) -> Vec<(Ident, Type)> {
+        let optional = self.alternatives.len() > 1;
+        let mut fields: Vec<(Ident, Type)> = Vec::new();
+        for check in self.alternatives.iter().flatten() {
+            if fields.iter().any(|(ident, _)| *ident == check.field_ident) {
+                continue;
+            }
+            let ty = &check.scheme_ty;
+            let ty = if optional {
+                parse_quote!(Option<#ty>)
+            } else {
+                ty.clone()
+            };
+            fields.push((check.field_ident.clone(), ty));
+        }
+        fields
+    }
+
+    /// The code that checks the requirements, and binds the fields of the generated struct.
+    fn check(&self) -> TokenStream {
+        let extract = |checks: &[SchemeCheck]| {
+            let extractions = checks.iter().map(|check| {
+                let SchemeCheck { scheme_name, scheme_ty, field_ident, scopes } = check;
+                let scope_check = (!scopes.is_empty()).then(|| quote! {
+                    ::axum_open_api::security::check_scopes(#scheme_name, &#field_ident.scopes, &[#(#scopes),*])?;
+                });
+                quote! {
+                    let #field_ident: #scheme_ty = parts.extract_with_state(_state).await?;
+                    #scope_check
+                }
+            });
+            quote!(#(#extractions)*)
+        };
+
+        // With a single alternative, everything can be extracted directly
+        if let [checks] = self.alternatives.as_slice() {
+            let extractions = extract(checks);
+            return quote! {
+                use ::axum::RequestPartsExt;
+                #extractions
+            };
+        }
+
+        // Otherwise, we try the alternatives in order until one of them is satisfied
+        let field_idents = self.fields().into_iter().map(|(ident, _)| ident);
+        let alternatives = self.alternatives.iter().map(|checks| {
+            let extractions = extract(checks);
+            let idents = checks
+                .iter()
+                .map(|check| &check.field_ident)
+                .collect::<Vec<_>>();
+            quote! {
+                let result = async {
+                    #extractions
+                    Ok::<_, ::axum_open_api::Rejection>((#(Some(#idents),)*))
+                }.await;
+                match result {
+                    Ok(fields) => {
+                        (#(#idents,)*) = fields;
+                        break 'security;
+                    }
+                    Err(e) => { rejection.get_or_insert(e); }
+                }
+            }
+        });
+        quote! {
+            use ::axum::RequestPartsExt;
+            #(let mut #field_idents = None;)*
+            let mut rejection = None;
+            'security: {
+                #(#alternatives)*
+                if let Some(e) = rejection {
+                    return Err(e);
+                }
+            }
+        }
+    }
 }
 
 impl ToTokens for Root {
@@ -88,7 +298,7 @@ impl ToTokens for ModuleItem {
             items,
         } = self;
 
-        tokens.extend(quote! {
+        tokens.extend(quote!{
             #vis mod #name {
                 #(#items)*
             }
@@ -102,13 +312,17 @@ impl ToTokens for Extractor {
             body_ty: _,
             body_ident,
             extractor_ty,
-            rejection_var,
+            rejection_var: _,
+            wrapper,
         } = self;
-        tokens.extend(quote!{
-            let #extractor_ty(#body_ident) = match req.extract().await {
-                Ok(body) => body,
-                Err(e) => return Err(::axum_open_api::Rejection::#rejection_var(e)),
-            };
+        if let Some(wrapper) = wrapper {
+            tokens.extend(quote! {
+                let #body_ident: #wrapper = ::axum::RequestExt::extract(req).await?;
+            });
+            return;
+        }
+        tokens.extend(quote! {
+            let #extractor_ty(#body_ident) = ::axum::RequestExt::extract(req).await?;
         });
     }
 }
@@ -119,17 +333,135 @@ impl ToTokens for MethodItem {
             method_ty,
             axum_path,
             oapi_path,
+            webhook,
             struct_name,
             struct_vis,
+            docs,
             summary,
             description,
             path_param_names: path_param_idents,
             path_param_types,
+            path_param_styles,
+            raw_path,
+            path_checks,
             query_param_names: query_param_idents,
             query_param_types,
+            operation_id,
+            tags,
             extractor,
+            security,
+            headers,
+            idempotency,
+            if_match,
+            if_none_match,
+            byte_range,
+            typed_headers,
+            query_path,
+            query_options,
         } = self;
 
+        let method_ident = Ident::new(&method_ty.to_string(), proc_macro2::Span::call_site());
+        let operation_id = match operation_id {
+            Some(operation_id) => quote!(Some(#operation_id)),
+            None => quote!(None),
+        };
+
+        let routing_impl = if *webhook {
+            quote! {
+                impl ::axum_open_api::OapiWebhook for #struct_name {
+                    fn method_filter() -> ::axum::routing::MethodFilter {
+                        ::axum::routing::MethodFilter::#method_ident
+                    }
+                }
+            }
+        } else {
+            quote! {
+                impl ::axum_open_api::OapiPath for #struct_name {
+                    fn path() -> &'static str {
+                        #axum_path
+                    }
+
+                    fn method_filter() -> ::axum::routing::MethodFilter {
+                        ::axum::routing::MethodFilter::#method_ident
+                    }
+                }
+            }
+        };
+
+        // Webhooks have no path, so they can't be routed to
+        let uri_impl = (!*webhook).then(|| {
+            let mut params = path_param_idents.iter().zip(path_param_styles);
+            let path_format = axum_path
+                .split('/')
+                .map(|segment| {
+                    let param = segment.starts_with(':').then(|| params.next()).flatten();
+                    match param {
+                        Some((ident, style)) => style.segment_format(&ident.to_string()),
+                        None => segment.to_owned(),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            // Parameters with `allowReserved` are sent with their reserved characters
+            let (encode_query, reserved_arg) = match query_options {
+                Some(QueryOptions { reserved, .. }) if !reserved.is_empty() => (
+                    quote!(::axum_open_api::uri::encode_query_reserved),
+                    Some(quote!(, &[#(#reserved),*])),
+                ),
+                _ => (quote!(::axum_open_api::uri::encode_query), None),
+            };
+            // The parameters are taken by reference to the types of the fields, like `&String`
+            quote! {
+                #[allow(clippy::ptr_arg)]
+                impl #struct_name {
+                    /// Builds the path of the operation, with the path parameters filled in.
+                    pub fn path_with(#(#path_param_idents: &#path_param_types),*) -> String {
+                        format!(
+                            #path_format,
+                            #(::axum_open_api::uri::encode_path_param(#path_param_idents)),*
+                        )
+                    }
+
+                    /// Builds the uri of the operation, with the path and query parameters
+                    /// filled in.
+                    pub fn uri(
+                        #(#path_param_idents: &#path_param_types,)*
+                        #(#query_param_idents: &#query_param_types,)*
+                    ) -> String {
+                        #[derive(serde::Serialize)]
+                        struct __QueryGenerated__<'a> {
+                            #(#query_param_idents: &'a #query_param_types,)*
+                            #[serde(skip)]
+                            __lifetime__: ::std::marker::PhantomData<&'a ()>,
+                        }
+
+                        // Named to not shadow parameters like `path`
+                        let __path__ = Self::path_with(#(#path_param_idents),*);
+                        let __query__ = #encode_query(&__QueryGenerated__ {
+                            #(#query_param_idents,)*
+                            __lifetime__: ::std::marker::PhantomData,
+                        } #reserved_arg);
+                        if __query__.is_empty() {
+                            __path__
+                        } else {
+                            format!("{__path__}?{__query__}")
+                        }
+                    }
+
+                    /// The location of the operation, for a redirect to it, with the path and
+                    /// query parameters filled in.
+                    pub fn location(
+                        #(#path_param_idents: &#path_param_types,)*
+                        #(#query_param_idents: &#query_param_types,)*
+                    ) -> ::axum_open_api::redirect::Location {
+                        let uri = Self::uri(#(#path_param_idents,)* #(#query_param_idents,)*);
+                        ::axum_open_api::redirect::Location::new(&uri)
+                            .expect("the path and query parameters are encoded")
+                    }
+                }
+            }
+        });
+
         let struct_doc: String = format!(
             "
 Generated from OpenAPI spec.
@@ -142,79 +474,386 @@ Generated from OpenAPI spec.
         let body_field = extractor.as_ref().map(|extractor| {
             let body_ty = &extractor.body_ty;
             let _extractor_ty = &extractor.extractor_ty;
-            quote!(pub body: #body_ty,)
+            match &extractor.wrapper {
+                Some(wrapper) => quote!(pub body: #wrapper,),
+                None => quote!(pub body: #body_ty,),
+            }
+        });
+        let body_ident = extractor.as_ref().map(|_| quote!(body));
+        let headers_field = headers.then(|| quote!(pub headers: ::axum::http::HeaderMap,));
+        let headers_ident = headers.then(|| quote!(headers,));
+        let headers_extraction = headers.then(|| quote!(let headers = parts.headers.clone();));
+        let idempotency_field = idempotency.as_ref().map(|idempotency| {
+            let key_ty = quote!(::axum_open_api::idempotency::IdempotencyKey);
+            match idempotency.required {
+                true => quote!(pub idempotency_key: #key_ty,),
+                false => quote!(pub idempotency_key: Option<#key_ty>,),
+            }
+        });
+        let idempotency_ident = idempotency.as_ref().map(|_| quote!(idempotency_key,));
+        let idempotency_bound = idempotency
+            .as_ref()
+            .map(|_| quote!(S: ::axum_open_api::idempotency::IdempotencyStore,));
+        // With `invalid_path_params`, path parameters that don't match their schemas are rejected
+        // with its status, instead of `400 Bad Request`
+        let path_status = match path_checks {
+            Some(PathChecks { status, .. }) => quote!(::axum::http::StatusCode::#status),
+            None => quote!(::axum::http::StatusCode::BAD_REQUEST),
+        };
+        let path_rejection = path_checks.as_ref().map(|_| {
+            quote!(.map_err(|e| ::axum_open_api::extract::invalid_path_params(e, #path_status)))
+        });
+        // The patterns are checked with the `regex` crate, which is behind feature `pattern`
+        let require_pattern = path_checks
+            .iter()
+            .any(|checks| !checks.patterns.is_empty())
+            .then(|| quote!(::axum_open_api::require_pattern_feature!();));
+        let pattern_checks =
+            path_checks
+                .iter()
+                .flat_map(|checks| &checks.patterns)
+                .map(|(ident, pattern)| {
+                    let name = ident.to_string();
+                    quote! {
+                        static PATTERN: ::axum_open_api::pattern::Pattern =
+                            ::axum_open_api::pattern::Pattern::new(#pattern);
+                        PATTERN.check_path_param(&#ident, #name, #path_status)?;
+                    }
+                });
+
+        // Path parameters with a `label` or `matrix` style are extracted as their segment, and
+        // parsed without its prefix
+        let path_segment_types =
+            path_param_types
+                .iter()
+                .zip(path_param_styles)
+                .map(|(ty, style)| match style {
+                    PathStyle::Simple => quote!(#ty),
+                    _ => quote!(String),
+                });
+        let styled_path_params = path_param_idents
+            .iter()
+            .zip(path_param_types)
+            .zip(path_param_styles)
+            .filter_map(|((ident, ty), style)| {
+                let parse_fn = style.parse_fn()?;
+                let name = ident.to_string();
+                Some(quote! {
+                    let #ident: #ty =
+                        ::axum_open_api::extract::#parse_fn(&#ident, #name, #path_status)?;
+                })
+            });
+
+        let idempotency_check = idempotency.as_ref().map(
+            |Idempotency {
+                 operation,
+                 required,
+             }| {
+                let missing = required.then(|| {
+                    quote! {
+                        .ok_or(::axum_open_api::idempotency::IdempotencyRejection::Missing)?
+                    }
+                });
+                quote! {
+                    let idempotency_key =
+                        ::axum_open_api::idempotency::idempotency_key(parts, _state, #operation)
+                            .await?
+                            #missing;
+                }
+            },
+        );
+        // The conditional headers are extracted as `EntityTagMatch`, checked by the handler
+        let conditional_headers = [(*if_match, "if_match"), (*if_none_match, "if_none_match")]
+            .into_iter()
+            .filter(|(declared, _)| *declared)
+            .map(|(_, name)| Ident::new(name, proc_macro2::Span::call_site()))
+            .collect::<Vec<_>>();
+        let conditional_header_names = conditional_headers
+            .iter()
+            .map(|ident| Ident::new(&ident.to_string().to_uppercase(), ident.span()));
+        let conditional_extraction = quote! {
+            #(
+                let #conditional_headers = ::axum_open_api::conditional::entity_tag_match(
+                    parts,
+                    ::axum::http::header::#conditional_header_names,
+                )?;
+            )*
+        };
+        let preconditions_impl = (!conditional_headers.is_empty()).then(|| {
+            let if_match = match if_match {
+                true => quote!(self.if_match.as_ref()),
+                false => quote!(None),
+            };
+            let if_none_match = match if_none_match {
+                true => quote!(self.if_none_match.as_ref()),
+                false => quote!(None),
+            };
+            quote! {
+                impl #struct_name {
+                    /// Checks the conditional headers of the request against the current entity
+                    /// tag of the resource, which is `None` if it does not exist. The error is
+                    /// the `304 Not Modified` or `412 Precondition Failed` response to return.
+                    pub fn check_preconditions(
+                        &self,
+                        current: Option<&::axum_open_api::conditional::EntityTag>,
+                    ) -> Result<(), ::axum_open_api::conditional::FailedPrecondition> {
+                        ::axum_open_api::conditional::check_preconditions(
+                            ::axum::http::Method::#method_ident,
+                            #if_match,
+                            #if_none_match,
+                            current,
+                        )
+                    }
+                }
+            }
+        });
+        // With `#[raw_path]`, every path parameter also gets its undecoded value
+        let raw_path_idents = path_param_idents
+            .iter()
+            .filter(|_| *raw_path)
+            .map(|ident| format_ident!("raw_{ident}"))
+            .collect::<Vec<_>>();
+        let raw_path_extraction = raw_path.then(|| {
+            let names = path_param_idents.iter().map(|ident| ident.to_string());
+            quote! {
+                let [#(#raw_path_idents),*] =
+                    ::axum_open_api::extract::raw_path_params(parts, [#(#names),*]);
+            }
+        });
+
+        // The range of a download is ignored if it is invalid, as allowed by RFC 9110
+        let range_field = byte_range
+            .as_ref()
+            .map(|_| quote!(pub range: Option<::axum_open_api::range::ByteRange>,));
+        let range_ident = byte_range.as_ref().map(|_| quote!(range,));
+        let range_extraction = byte_range
+            .as_ref()
+            .map(|_| quote!(let range = ::axum_open_api::range::byte_range(parts);));
+        // The standard headers are extracted as their `TypedHeader`, and rejected if invalid
+        let typed_header_idents = typed_headers.iter().map(|header| &header.field_ident);
+        let typed_header_fields = typed_headers.iter().map(|header| {
+            let TypedHeader {
+                field_ident,
+                header_ty,
+                required,
+            } = header;
+            let ty = quote! {
+                ::axum_open_api::typed_header::TypedHeader<
+                    ::axum_open_api::typed_header::headers::#header_ty
+                >
+            };
+            match required {
+                true => quote!(pub #field_ident: #ty,),
+                false => quote!(pub #field_ident: Option<#ty>,),
+            }
         });
-        let body_ident = extractor.as_ref().map(|_| {
-            quote!(body)
+        let require_typed_header = (!typed_headers.is_empty())
+            .then(|| quote!(::axum_open_api::require_typed_header_feature!();));
+        let typed_header_extractions = typed_headers.iter().map(|header| {
+            let field_ident = &header.field_ident;
+            let extract_fn = match header.required {
+                true => quote!(typed_header),
+                false => quote!(optional_typed_header),
+            };
+            quote! {
+                let #field_ident = ::axum_open_api::typed_header::#extract_fn(parts).await?;
+            }
         });
-        let (from_req_trait, from_req_fn_name, req_type, extract_parts) = match &extractor {
-            Some(_) => ( quote!(FromRequest), quote!(from_request), quote!(::axum::extract::Request), quote!(extract_parts) ),
-            None => ( quote!(FromRequestParts), quote!(from_request_parts), quote!(&mut ::axum::http::request::Parts), quote!(extract) ),
+        let range_impl = byte_range.as_ref().map(|ByteRange { content_type }| {
+            let content_type = match content_type {
+                Some(content_type) => quote!(Some(#content_type)),
+                None => quote!(None),
+            };
+            quote! {
+                impl #struct_name {
+                    /// Responds with the requested range of the body, or with the full body if
+                    /// the request has no range.
+                    pub fn partial_content(
+                        &self,
+                        body: impl Into<::axum::body::Bytes>,
+                    ) -> ::axum_open_api::range::PartialContent {
+                        ::axum_open_api::range::PartialContent {
+                            range: self.range,
+                            body: body.into(),
+                            content_type: #content_type,
+                        }
+                    }
+                }
+
+                impl ::axum_open_api::ResponseFor<#struct_name>
+                    for ::axum_open_api::range::PartialContent
+                {
+                }
+            }
+        });
+        let query_params = match query_options {
+            Some(QueryOptions {
+                absent_if_empty,
+                true_if_empty,
+                reserved,
+            }) => quote! {
+                ::axum_open_api::extract::query_params_with(
+                    parts,
+                    &::axum_open_api::extract::QueryOptions {
+                        absent_if_empty: &[#(#absent_if_empty),*],
+                        true_if_empty: &[#(#true_if_empty),*],
+                        reserved: &[#(#reserved),*],
+                    },
+                )?
+            },
+            None => quote!(::axum_open_api::extract::query_params(parts)?),
+        };
+        let query_extraction = match query_path {
+            Some(query_path) => quote! {
+                let #query_path { #(#query_param_idents),* } = #query_params;
+            },
+            None => quote! {
+                #[derive(serde::Deserialize)]
+                struct __QueryGenerated__ {
+                    #(#query_param_idents: #query_param_types,)*
+                }
+                let __QueryGenerated__ { #(#query_param_idents),* } = #query_params;
+            },
+        };
+
+        // Everything but the body is extracted from the parts, so a request is split first
+        let (from_req_trait, from_req_fn_name, req_arg, split_req, join_req) = match &extractor {
+            Some(_) => (
+                quote!(FromRequest),
+                quote!(from_request),
+                quote!(req: ::axum::extract::Request),
+                quote!(let (mut head, body) = req.into_parts(); let parts = &mut head;),
+                quote!(let req = ::axum::extract::Request::from_parts(head, body);),
+            ),
+            None => (
+                quote!(FromRequestParts),
+                quote!(from_request_parts),
+                quote!(parts: &mut ::axum::http::request::Parts),
+                quote!(),
+                quote!(),
+            ),
         };
 
+        // Every security scheme that is checked must be extractable with the state
+        let scheme_tys = security
+            .iter()
+            .flat_map(|security| security.alternatives.iter().flatten())
+            .map(|check| &check.scheme_ty);
+        let security_check = security.as_ref().map(Security::check);
+        let security_fields = security.as_ref().map(Security::fields).unwrap_or_default();
+        let security_field_idents = security_fields.iter().map(|(ident, _)| ident);
+        let security_field_defs = security_fields
+            .iter()
+            .map(|(ident, ty)| quote!(pub #ident: #ty,));
+        let param_names = path_param_idents
+            .iter()
+            .chain(query_param_idents)
+            .map(|ident| ident.to_string());
+        let param_idents = path_param_idents.iter().chain(query_param_idents);
+
         tokens.extend(quote! {
 
             // Generate the struct first
+            #(#docs)*
             #[doc = #struct_doc]
             #[derive(Debug)]
             #struct_vis struct #struct_name {
                 #(pub #path_param_idents: #path_param_types,)*
+                #(pub #raw_path_idents: ::axum_open_api::extract::RawPathParam,)*
                 #(pub #query_param_idents: #query_param_types,)*
+                #(#security_field_defs)*
+                #headers_field
+                #idempotency_field
+                #(pub #conditional_headers: Option<::axum_open_api::conditional::EntityTagMatch>,)*
+                #range_field
+                #(#typed_header_fields)*
                 #body_field // add the body field only if it is extracted
             }
 
-            // Implement the OapiPath trait for it
-            impl ::axum_open_api::OapiPath for #struct_name {
-                fn path() -> &'static str {
-                    #axum_path
+            // Implement the OapiPath or OapiWebhook trait for it
+            #routing_impl
+
+            // Implement the uri builders for it
+            #uri_impl
+
+            // Implement the precondition check of the conditional headers for it
+            #preconditions_impl
+
+            // Implement the partial content response of a download for it
+            #range_impl
+
+            // Implement the OperationMeta trait for it
+            impl ::axum_open_api::OperationMeta for #struct_name {
+                fn method() -> ::axum::http::Method {
+                    ::axum::http::Method::#method_ident
                 }
 
-                fn method_router<H, T, S>(handler: H) -> axum::routing::MethodRouter<S>
-                where
-                    H: axum::handler::Handler<T, S>,
-                    T: 'static,
-                    S: Clone + Send + Sync + 'static,
-                {
-                    axum::routing::MethodRouter::new().#method_ty(handler)
+                fn path_template() -> &'static str {
+                    #oapi_path
+                }
+
+                fn operation_id() -> Option<&'static str> {
+                    #operation_id
+                }
+
+                fn tags() -> &'static [&'static str] {
+                    &[#(#tags),*]
                 }
             }
 
             // Implement FromRequest(Parts)
             #[axum::async_trait]
-            impl<S: Send + Sync> ::axum::extract::#from_req_trait<S> for #struct_name {
+            impl<S: Send + Sync> ::axum::extract::#from_req_trait<S> for #struct_name
+            where
+                #(#scheme_tys: ::axum::extract::FromRequestParts<S, Rejection = ::axum_open_api::Rejection>,)*
+                #idempotency_bound
+            {
                 type Rejection = ::axum_open_api::Rejection;
     
                 async fn #from_req_fn_name(
-                    mut req: #req_type,
+                    #req_arg,
                     _state: &S,
                 ) -> Result<Self, Self::Rejection> {
-                    use axum::{
-                        extract::{Path, Query},
-                        RequestPartsExt, RequestExt
-                    };
-    
-                    let Path((#(#path_param_idents),*)) = match req.#extract_parts().await {
-                        Ok(params) => params,
-                        Err(e) => return Err(::axum_open_api::Rejection::Path(e)),
-                    };
-    
-                    #[derive(serde::Deserialize)]
-                    struct __QueryGenerated__ {
-                        #(#query_param_idents: #query_param_types,)*
-                    }
-    
-                    let Query(__QueryGenerated__ { #(#query_param_idents),* }) = match req.#extract_parts().await {
-                        Ok(query) => query,
-                        Err(e) => return Err(::axum_open_api::Rejection::Query(e)),
-                    };
+                    // Everything is extracted within the span of the operation
+                    let span = ::axum_open_api::trace::ExtractionSpan::new::<Self>();
+                    let extraction = span.clone().instrument(async move {
+                        #split_req
+                        #headers_extraction
+                        #security_check
+                        #conditional_extraction
+                        #range_extraction
+                        #require_typed_header
+                        #(#typed_header_extractions)*
+
+                        let (#(#path_param_idents),*): (#(#path_segment_types),*) =
+                            ::axum_open_api::extract::path_params(parts).await #path_rejection?;
+                        #(#styled_path_params)*
+                        #require_pattern
+                        #({ #pattern_checks })*
+                        #raw_path_extraction
+
+                        #query_extraction
+
+                        span.record_params(&[#((#param_names, &#param_idents as &dyn ::std::fmt::Debug)),*]);
+                        #idempotency_check
 
-                    #extractor
+                        #join_req
+                        #extractor
     
-                    Ok(Self {
-                        #(#path_param_idents,)*
-                        #(#query_param_idents,)*
-                        #body_ident // add the body field only if it is extracted
-                    })
+                        Ok(Self {
+                            #(#path_param_idents,)*
+                            #(#raw_path_idents,)*
+                            #(#query_param_idents,)*
+                            #(#security_field_idents,)*
+                            #headers_ident
+                            #idempotency_ident
+                            #(#conditional_headers,)*
+                            #range_ident
+                            #(#typed_header_idents,)*
+                            #body_ident // add the body field only if it is extracted
+                        })
+                    });
+                    ::axum_open_api::metrics::measure_extraction::<Self, _>(extraction).await
                 }
             }
         
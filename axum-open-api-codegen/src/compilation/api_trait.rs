@@ -0,0 +1,157 @@
+use crate::codegen;
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span};
+use syn::{Item, Path};
+
+/// An operation of the `Api` trait.
+pub struct ApiOperation {
+    /// The path of the generated struct from the root, like `feed::GetPosts`
+    pub struct_path: Path,
+    /// The path of the response enum from the root, like `feed::GetPostsResponse`
+    pub response_path: Path,
+    /// Whether the struct extracts the body, and thus only implements `FromRequest`
+    pub has_body: bool,
+}
+
+impl ApiOperation {
+    pub fn of(method: &codegen::MethodItem, module_path: &[Ident]) -> Self {
+        let struct_name = &method.struct_name;
+        let response_ident = format_ident!("{struct_name}Response");
+        Self {
+            struct_path: parse_quote!(#(#module_path::)* #struct_name),
+            response_path: parse_quote!(#(#module_path::)* #response_ident),
+            has_body: method.extractor.is_some(),
+        }
+    }
+}
+
+/// Compiles the `Api` trait with a method for every operation, and the `into_router` function
+/// that routes every operation to an implementation of it.
+pub fn compile_api_trait(api: &Ident, operations: &[ApiOperation], items: &mut Vec<Item>) {
+    let method_idents = operations
+        .iter()
+        .map(|operation| {
+            let struct_name = &operation.struct_path.segments.last().unwrap().ident;
+            Ident::new(
+                &struct_name.to_string().to_case(Case::Snake),
+                Span::call_site(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let struct_paths = operations
+        .iter()
+        .map(|operation| &operation.struct_path)
+        .collect::<Vec<_>>();
+    let response_paths = operations.iter().map(|operation| &operation.response_path);
+    let docs = operations.iter().map(|operation| {
+        let struct_path = &operation.struct_path;
+        format!(
+            "Handles [`{}`].",
+            quote!(#struct_path).to_string().replace(' ', "")
+        )
+    });
+    let bounds = operations.iter().map(|operation| {
+        let struct_path = &operation.struct_path;
+        if operation.has_body {
+            quote!(#struct_path: ::axum::extract::FromRequest<A>)
+        } else {
+            quote!(#struct_path: ::axum::extract::FromRequestParts<A>)
+        }
+    });
+
+    items.push(parse_quote! {
+        /// The operations of the OpenAPI spec. Implementations are turned into a router with
+        /// [`into_router`], which routes every operation to its method.
+        #[::axum::async_trait]
+        pub trait #api: Clone + Send + Sync + 'static {
+            #(
+                #[doc = #docs]
+                async fn #method_idents(&self, req: #struct_paths) -> #response_paths;
+            )*
+        }
+    });
+    items.push(parse_quote! {
+        /// Routes every operation of the OpenAPI spec to the implementation of the api.
+        pub fn into_router<A: #api>(api: A) -> ::axum::Router
+        where
+            #(#bounds,)*
+        {
+            use ::axum_open_api::OapiRouter;
+            ::axum::Router::new()
+                #(
+                    .oapi_route(
+                        |::axum::extract::State(api): ::axum::extract::State<A>, req: #struct_paths| async move {
+                            api.#method_idents(req).await
+                        },
+                    )
+                )*
+                .with_state(api)
+        }
+    });
+}
+
+/// Compiles the `build_router` macro, like `build_router!(GetPosts => get_posts, ...)`, which
+/// routes a handler for every operation. A missing or unknown operation is a missing or unknown
+/// field of the struct holding the handlers, and a handler must extract the struct of its
+/// operation.
+pub fn compile_build_router(name: &Ident, operations: &[ApiOperation], items: &mut Vec<Item>) {
+    let struct_names = operations
+        .iter()
+        .map(|operation| &operation.struct_path.segments.last().unwrap().ident)
+        .collect::<Vec<_>>();
+    let struct_paths = operations.iter().map(|operation| &operation.struct_path);
+    let handler_tys = (0..operations.len())
+        .map(|i| format_ident!("H{i}"))
+        .collect::<Vec<_>>();
+
+    items.push(parse_quote! {
+        /// Builds a router with a handler for every operation of the OpenAPI spec, like
+        /// `build_router!(GetPosts => get_posts, AddTag => add_tag)`.
+        #[allow(unused_macros)]
+        macro_rules! #name {
+            ($($operation:ident => $handler:expr),* $(,)?) => {{
+                #[allow(non_snake_case)]
+                struct Handlers<#(#handler_tys),*> {
+                    #(#struct_names: #handler_tys,)*
+                }
+                let handlers = Handlers { $($operation: $handler,)* };
+                let router = ::axum::Router::new();
+                #(
+                    let router = ::axum_open_api::route_operation::<#struct_paths, _, _, _>(
+                        router,
+                        handlers.#struct_names,
+                    );
+                )*
+                router
+            }};
+        }
+    });
+}
+
+/// Compiles a macro that adds routes outside of the OpenAPI spec to a router, like
+/// `validated_router!(router, "/health" => get(health))`, which fails to compile if a path
+/// overlaps one of the paths of the spec.
+pub fn compile_validated_router(name: &Ident, paths: &[String], items: &mut Vec<Item>) {
+    items.push(parse_quote! {
+        /// Adds routes that are not in the OpenAPI spec to a router, like
+        /// `validated_router!(router, "/health" => get(health))`. It fails to compile if a path
+        /// conflicts with, or shadows, a path of the spec.
+        #[allow(unused_macros)]
+        macro_rules! #name {
+            ($router:expr, $($path:literal => $method_router:expr),* $(,)?) => {{
+                const SPEC_PATHS: &[&str] = &[#(#paths),*];
+                $(
+                    const _: () = assert!(
+                        !::axum_open_api::route_conflicts($path, SPEC_PATHS),
+                        concat!("The route `", $path, "` overlaps a path of the OpenAPI spec"),
+                    );
+                )*
+                let router = $router;
+                $(
+                    let router = router.route($path, $method_router);
+                )*
+                router
+            }};
+        }
+    });
+}
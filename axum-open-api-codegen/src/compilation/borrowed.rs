@@ -0,0 +1,171 @@
+use super::derives::visit_mut;
+use crate::codegen;
+use proc_macro2::{Ident, TokenStream};
+use syn::{Field, Fields, Item, ItemStruct, Type};
+
+/// Generates the borrowed forms of the json bodies of `#[borrowed]` operations, like
+/// `UserRef<'a>` for `User`, next to their schemas. Their `String` fields are `Cow<'a, str>`,
+/// which borrow from the body of the request, and the other fields are the same.
+///
+/// The bodies are given with the struct of their operation, for the errors.
+pub fn add_borrowed_types(
+    items: &mut [codegen::Item],
+    bodies: &[(Ident, Type)],
+) -> syn::Result<()> {
+    let mut names = Vec::new();
+    for (struct_name, body_ty) in bodies {
+        let name = match body_ty {
+            Type::Path(path) => path.path.segments.last().map(|segment| &segment.ident),
+            _ => None,
+        };
+        match name.filter(|name| !["Vec", "Option", "HashMap"].iter().any(|ty| name == ty)) {
+            Some(name) => names.push((struct_name, name.to_string())),
+            None => {
+                return Err(err!(
+                    struct_name,
+                    "`#[borrowed]` requires a body with an object schema"
+                ))
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    let mut error = None;
+    visit_mut(items, &mut |item| {
+        let Item::Struct(schema) = item else {
+            return;
+        };
+        let name = names.iter().find(|(_, name)| schema.ident == name);
+        let Some((struct_name, name)) = name else {
+            return;
+        };
+        if found.contains(name) {
+            return;
+        }
+        match compile_borrowed(schema) {
+            Some(borrowed) => *item = Item::Verbatim(quote!(#schema #borrowed)),
+            None => {
+                let message = format!("`#[borrowed]` body `{name}` has no string fields to borrow");
+                error.get_or_insert(err!(struct_name, message));
+            }
+        }
+        found.push(name.clone());
+    });
+    if let Some(error) = error {
+        return Err(error);
+    }
+    match names.iter().find(|(_, name)| !found.contains(name)) {
+        Some((struct_name, _)) => Err(err!(
+            struct_name,
+            "`#[borrowed]` requires a body with an object schema that is generated by this \
+            invocation"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Compiles the borrowed form of the struct, with its `into_owned` and its `Borrowed` impl, or
+/// `None` if it has no string fields.
+fn compile_borrowed(schema: &ItemStruct) -> Option<TokenStream> {
+    let Fields::Named(fields) = &schema.fields else {
+        return None;
+    };
+    let ident = &schema.ident;
+    let ref_ident = format_ident!("{ident}Ref");
+
+    let mut borrows = false;
+    let mut ref_fields = Vec::new();
+    let mut conversions = Vec::new();
+    for field in &fields.named {
+        let field_ident = &field.ident;
+        let mut attrs: Vec<_> = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("serde") || attr.path().is_ident("doc"))
+            .cloned()
+            .collect();
+        let (ty, conversion) = if is_string(&field.ty) {
+            borrows = true;
+            attrs.push(parse_quote! {
+                #[serde(borrow, deserialize_with = "::axum_open_api::borrowed::borrow_str")]
+            });
+            let conversion = quote!(self.#field_ident.into_owned());
+            (parse_quote!(::std::borrow::Cow<'a, str>), conversion)
+        } else if option_inner(&field.ty).is_some_and(is_string) {
+            borrows = true;
+            attrs.push(parse_quote! {
+                #[serde(
+                    borrow,
+                    default,
+                    deserialize_with = "::axum_open_api::borrowed::borrow_optional_str"
+                )]
+            });
+            let conversion = quote!(self.#field_ident.map(::std::borrow::Cow::into_owned));
+            (
+                parse_quote!(Option<::std::borrow::Cow<'a, str>>),
+                conversion,
+            )
+        } else {
+            (field.ty.clone(), quote!(self.#field_ident))
+        };
+        ref_fields.push(Field {
+            attrs,
+            ty,
+            ..field.clone()
+        });
+        conversions.push(quote!(#field_ident: #conversion));
+    }
+    if !borrows {
+        return None;
+    }
+
+    let serde_attrs = schema
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"));
+    let doc = format!("The borrowed form of [`{ident}`], whose strings borrow from the request");
+    Some(quote! {
+        #[doc = #doc]
+        #[derive(serde::Deserialize, Debug)]
+        #(#serde_attrs)*
+        pub struct #ref_ident<'a> {
+            #(#ref_fields,)*
+        }
+
+        impl #ref_ident<'_> {
+            /// Converts it into the owned schema, allocating its strings
+            pub fn into_owned(self) -> #ident {
+                #ident {
+                    #(#conversions,)*
+                }
+            }
+        }
+
+        impl ::axum_open_api::borrowed::Borrowed for #ident {
+            type Ref<'a> = #ref_ident<'a>;
+        }
+    })
+}
+
+fn is_string(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.qself.is_none() && path.path.is_ident("String"))
+}
+
+/// The `T` of an `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path
+        .path
+        .segments
+        .last()
+        .filter(|segment| segment.ident == "Option")?;
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,210 @@
+use super::{
+    derives::{visit, visit_mut},
+    schema::compile_conversions,
+};
+use crate::codegen;
+use proc_macro2::{Ident, Span};
+use quote::ToTokens;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+};
+use syn::{Fields, GenericArgument, Item, ItemEnum, PathArguments, Type};
+
+/// How much larger than the second largest variant a variant may be, before it is boxed. This is
+/// the threshold of clippy's `large_enum_variant`.
+const LARGE_VARIANT: usize = 200;
+
+/// The estimated size of a pointer, like a `Box`.
+const POINTER_SIZE: usize = 8;
+
+/// The fields of a generated type, for estimating its size.
+enum Shape {
+    Struct(Vec<Type>),
+    Enum(Vec<Vec<Type>>),
+    Alias(Type),
+}
+
+/// Boxes the variants of the `oneOf` enums that are much larger than the other variants, like a
+/// large struct next to a string. The sizes are estimated from the generated types.
+pub fn box_large_variants(items: &mut [codegen::Item]) {
+    let mut shapes = BTreeMap::new();
+    visit(items, &mut |item| {
+        let (ident, shape) = match item {
+            Item::Struct(item) => (&item.ident, Shape::Struct(field_tys(&item.fields))),
+            Item::Enum(item) => {
+                let variants = item
+                    .variants
+                    .iter()
+                    .map(|variant| field_tys(&variant.fields));
+                (&item.ident, Shape::Enum(variants.collect()))
+            }
+            Item::Type(item) => (&item.ident, Shape::Alias((*item.ty).clone())),
+            _ => return,
+        };
+        shapes.insert(ident.to_string(), shape);
+    });
+
+    // The variants to box, by the name of their enum
+    let mut boxed: BTreeMap<String, Vec<(Ident, Type)>> = BTreeMap::new();
+    visit(items, &mut |item| {
+        let Some(variants) = one_of_variants(item) else {
+            return;
+        };
+        let Item::Enum(item) = item else {
+            return;
+        };
+        let mut sizes = variants
+            .iter()
+            .map(|(_, ty)| size_of(ty, &shapes, &mut BTreeSet::new()))
+            .collect::<Vec<_>>();
+        loop {
+            let mut order = (0..sizes.len()).collect::<Vec<_>>();
+            order.sort_by_key(|index| Reverse(sizes[*index]));
+            let [largest, second, ..] = order[..] else {
+                break;
+            };
+            if sizes[largest] < sizes[second] + LARGE_VARIANT {
+                break;
+            }
+            sizes[largest] = POINTER_SIZE;
+            let variant = variants[largest].clone();
+            boxed
+                .entry(item.ident.to_string())
+                .or_default()
+                .push(variant);
+        }
+    });
+    if boxed.is_empty() {
+        return;
+    }
+
+    // The conversions of the boxed variants are replaced by conversions that box and unbox
+    let mut conversions = BTreeMap::new();
+    for (ident, variants) in &boxed {
+        let ident = Ident::new(ident, Span::call_site());
+        for (variant_ident, ty) in variants {
+            let unboxed = compile_conversions(&ident, variant_ident, ty, false);
+            let boxed = compile_conversions(&ident, variant_ident, ty, true);
+            for (unboxed, boxed) in unboxed.into_iter().zip(boxed) {
+                conversions.insert(unboxed.to_token_stream().to_string(), boxed);
+            }
+        }
+    }
+
+    visit_mut(items, &mut |item| {
+        if let Item::Impl(_) = item {
+            if let Some(conversion) = conversions.get(&item.to_token_stream().to_string()) {
+                *item = conversion.clone();
+            }
+            return;
+        }
+        let Item::Enum(item) = item else {
+            return;
+        };
+        let Some(variants) = boxed.get(&item.ident.to_string()) else {
+            return;
+        };
+        for variant in &mut item.variants {
+            if !variants.iter().any(|(ident, _)| *ident == variant.ident) {
+                continue;
+            }
+            for field in variant.fields.iter_mut() {
+                let ty = &field.ty;
+                field.ty = parse_quote!(Box<#ty>);
+            }
+        }
+    });
+}
+
+/// The variants of an enum generated from a `oneOf`, which derives serde and has a single value in
+/// every variant.
+pub(super) fn one_of_variants(item: &Item) -> Option<Vec<(Ident, Type)>> {
+    let Item::Enum(ItemEnum {
+        attrs, variants, ..
+    }) = item
+    else {
+        return None;
+    };
+    let derives_serde = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .any(|attr| attr.to_token_stream().to_string().contains("serde"));
+    if !derives_serde {
+        return None;
+    }
+    variants
+        .iter()
+        .map(|variant| match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                Some((variant.ident.clone(), fields.unnamed[0].ty.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn field_tys(fields: &Fields) -> Vec<Type> {
+    fields.iter().map(|field| field.ty.clone()).collect()
+}
+
+/// The estimated size of the type in bytes. Recursive types are estimated as a pointer, because
+/// they must be boxed somewhere.
+fn size_of(ty: &Type, shapes: &BTreeMap<String, Shape>, visiting: &mut BTreeSet<String>) -> usize {
+    let Type::Path(path) = ty else {
+        return POINTER_SIZE;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return POINTER_SIZE;
+    };
+    let args: Vec<&Type> = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let name = segment.ident.to_string();
+    match name.as_str() {
+        "bool" => 1,
+        "i64" | "f64" => 8,
+        "String" | "Vec" | "BTreeMap" => 24,
+        "Value" => 32,
+        "HashMap" => 48,
+        "Box" | "Arc" | "Rc" => POINTER_SIZE,
+        "Option" => 8 + args.first().map_or(0, |ty| size_of(ty, shapes, visiting)),
+        _ => {
+            let Some(shape) = shapes.get(&name) else {
+                return POINTER_SIZE;
+            };
+            if !visiting.insert(name.clone()) {
+                return POINTER_SIZE;
+            }
+            let size = match shape {
+                Shape::Struct(fields) => sum_of(fields, shapes, visiting),
+                Shape::Enum(variants) => {
+                    let sizes = variants
+                        .iter()
+                        .map(|fields| sum_of(fields, shapes, visiting));
+                    8 + sizes.max().unwrap_or(0)
+                }
+                Shape::Alias(ty) => size_of(ty, shapes, visiting),
+            };
+            visiting.remove(&name);
+            size
+        }
+    }
+}
+
+fn sum_of(
+    tys: &[Type],
+    shapes: &BTreeMap<String, Shape>,
+    visiting: &mut BTreeSet<String>,
+) -> usize {
+    tys.iter().map(|ty| size_of(ty, shapes, visiting)).sum()
+}
@@ -0,0 +1,143 @@
+use convert_case::{Case, Casing};
+use proc_macro2::Ident;
+use syn::{ext::IdentExt, Item, Type};
+
+/// A field of a generated struct.
+pub struct Field {
+    pub ident: Ident,
+    /// The type of the field, without the `Option` of optional fields
+    pub ty: Type,
+    pub required: bool,
+    /// Whether the field is not an `Option`, but defaults to the default of its type
+    pub default: bool,
+}
+
+/// Compiles the builder of a generated struct, like `User::builder().id(1).name("x").build()`.
+///
+/// Every required field is a type parameter of the builder, which is `()` until the field is set,
+/// so `build` only exists once all required fields are set. Optional fields can be set in any
+/// order, and are `None` or their default otherwise.
+pub fn compile_builder(ident: &Ident, fields: &[Field], items: &mut Vec<Item>) {
+    let builder = format_ident!("{ident}Builder");
+    let required = fields
+        .iter()
+        .filter(|field| field.required)
+        .collect::<Vec<_>>();
+    let params = required
+        .iter()
+        .map(|field| {
+            format_ident!(
+                "__{}",
+                field.ident.unraw().to_string().to_case(Case::Pascal)
+            )
+        })
+        .collect::<Vec<_>>();
+    let required_idents = required
+        .iter()
+        .map(|field| &field.ident)
+        .collect::<Vec<_>>();
+    let required_tys = required.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let optional = fields
+        .iter()
+        .filter(|field| !field.required)
+        .collect::<Vec<_>>();
+    let optional_idents = optional
+        .iter()
+        .map(|field| &field.ident)
+        .collect::<Vec<_>>();
+    let optional_tys = optional.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let unwrap = optional
+        .iter()
+        .map(|field| field.default.then(|| quote!(.unwrap_or_default())))
+        .collect::<Vec<_>>();
+    let all_idents = fields.iter().map(|field| &field.ident).collect::<Vec<_>>();
+
+    let doc = format!("The builder of [`{ident}`], created by [`{ident}::builder`].");
+    items.push(parse_quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone)]
+        pub struct #builder<#(#params),*> {
+            #(#required_idents: #params,)*
+            #(#optional_idents: Option<#optional_tys>,)*
+        }
+    });
+    let unset = required.iter().map(|_| quote!(()));
+    items.push(parse_quote! {
+        impl #ident {
+            /// Builds the struct field by field. It can only be built once every required field
+            /// is set.
+            pub fn builder() -> #builder<#(#unset),*> {
+                #builder {
+                    #(#required_idents: (),)*
+                    #(#optional_idents: None,)*
+                }
+            }
+        }
+    });
+
+    // Setting a required field changes its type parameter from `()` to the type of the field
+    for (index, field) in required.iter().enumerate() {
+        let field_ident = &field.ident;
+        let field_ty = &field.ty;
+        let other_params = params
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .map(|(_, param)| param);
+        let before = params.iter().enumerate().map(|(other, param)| {
+            if other == index {
+                quote!(())
+            } else {
+                quote!(#param)
+            }
+        });
+        let after = params.iter().enumerate().map(|(other, param)| {
+            if other == index {
+                quote!(#field_ty)
+            } else {
+                quote!(#param)
+            }
+        });
+        let moved = all_idents
+            .iter()
+            .filter(|ident| **ident != field_ident)
+            .collect::<Vec<_>>();
+        items.push(parse_quote! {
+            impl<#(#other_params),*> #builder<#(#before),*> {
+                pub fn #field_ident(
+                    self,
+                    #field_ident: impl Into<#field_ty>,
+                ) -> #builder<#(#after),*> {
+                    #builder {
+                        #field_ident: #field_ident.into(),
+                        #(#moved: self.#moved,)*
+                    }
+                }
+            }
+        });
+    }
+
+    items.push(parse_quote! {
+        impl<#(#params),*> #builder<#(#params),*> {
+            #(
+                pub fn #optional_idents(
+                    mut self,
+                    #optional_idents: impl Into<#optional_tys>,
+                ) -> Self {
+                    self.#optional_idents = Some(#optional_idents.into());
+                    self
+                }
+            )*
+        }
+    });
+    items.push(parse_quote! {
+        impl #builder<#(#required_tys),*> {
+            pub fn build(self) -> #ident {
+                #ident {
+                    #(#required_idents: self.#required_idents,)*
+                    #(#optional_idents: self.#optional_idents #unwrap,)*
+                }
+            }
+        }
+    });
+}
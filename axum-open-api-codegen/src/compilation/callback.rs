@@ -0,0 +1,130 @@
+use super::{
+    raw::resolve,
+    schema::{compile_schema, Depth},
+};
+use crate::parsing;
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span};
+use serde_json::Value;
+use syn::Item;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Compiles the `callbacks` of the operation into senders on its struct, like
+/// `impl PostSubscription { pub async fn send_on_event_post(...) }`.
+///
+/// The runtime expressions in the url, like `{$request.body#/callbackUrl}`, become arguments of
+/// the sender.
+pub fn compile_callbacks(
+    method: &parsing::MethodItem,
+    raw_operation: &Value,
+    root: &Value,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<()> {
+    let struct_name = &method.struct_name;
+    let mut senders = Vec::new();
+    for (callback_name, callback) in raw_operation["callbacks"].as_object().into_iter().flatten() {
+        let callback = resolve(root, callback);
+        for (expression, path_item) in callback.as_object().into_iter().flatten() {
+            let path_item = resolve(root, path_item);
+            for http_method in METHODS {
+                let Some(operation) = path_item.get(http_method) else {
+                    continue;
+                };
+                senders.push(compile_sender(
+                    struct_name,
+                    callback_name,
+                    expression,
+                    http_method,
+                    resolve(root, operation),
+                    root,
+                    depth,
+                    items,
+                )?);
+            }
+        }
+    }
+
+    if !senders.is_empty() {
+        items.push(parse_quote! {
+            impl #struct_name {
+                #(#senders)*
+            }
+        });
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn compile_sender(
+    struct_name: &Ident,
+    callback_name: &str,
+    expression: &str,
+    http_method: &str,
+    operation: &Value,
+    root: &Value,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<syn::ImplItemFn> {
+    // Every runtime expression, like `{$request.body#/callbackUrl}`, becomes an argument
+    let mut url_format = String::new();
+    let mut url_args = Vec::new();
+    let mut rest = expression;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| err_call_site!("Unclosed expression in callback url {expression}"))?;
+        url_format.push_str(&rest[..start]);
+        url_format.push_str("{}");
+        url_args.push(expression_ident(&rest[start + 1..start + end]));
+        rest = &rest[start + end + 1..];
+    }
+    url_format.push_str(rest);
+
+    // The body is the json body of the callback operation
+    let request_body = resolve(root, &operation["requestBody"]);
+    let schema = request_body["content"]["application/json"].get("schema");
+    let (body_arg, body_call) = match schema {
+        Some(schema) => {
+            let title = format!("{struct_name}{}Body", callback_name.to_case(Case::Pascal));
+            let body_ty = compile_schema(schema, Some(&title), depth, items)?;
+            (Some(quote!(body: &#body_ty,)), Some(quote!(.json(body))))
+        }
+        None => (None, None),
+    };
+
+    let sender_ident = Ident::new(
+        &format!("send_{}_{http_method}", callback_name.to_case(Case::Snake)),
+        Span::call_site(),
+    );
+    let http_method = Ident::new(&http_method.to_uppercase(), Span::call_site());
+    let doc = format!("Sends the `{callback_name}` callback to `{http_method} {expression}`.");
+    Ok(parse_quote! {
+        #[doc = #doc]
+        pub async fn #sender_ident(
+            client: &::axum_open_api::client::reqwest::Client,
+            #(#url_args: &str,)*
+            #body_arg
+        ) -> Result<::axum_open_api::client::reqwest::Response, ::axum_open_api::client::reqwest::Error> {
+            client
+                .request(::axum::http::Method::#http_method, format!(#url_format, #(#url_args),*))
+                #body_call
+                .send()
+                .await
+        }
+    })
+}
+
+/// The argument for a runtime expression, named after the value it refers to:
+/// `$request.body#/callbackUrl` becomes `callback_url`, and `$request.query.url` becomes `url`.
+fn expression_ident(expression: &str) -> Ident {
+    let name = expression
+        .rsplit(['/', '.', '#'])
+        .map(|part| part.trim_start_matches('$'))
+        .find(|part| !part.is_empty())
+        .unwrap_or("url");
+    Ident::new(&name.to_case(Case::Snake), Span::call_site())
+}
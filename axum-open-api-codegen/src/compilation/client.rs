@@ -0,0 +1,211 @@
+use super::{
+    responses::{Response, ResponseBody},
+    schema::Depth,
+};
+use crate::{codegen, parsing};
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span};
+use syn::{Item, Type};
+
+/// The parts of an operation, as they were compiled for its extractor.
+#[derive(Clone, Copy)]
+pub struct ClientParams<'a> {
+    pub path_param_types: &'a [Type],
+    pub path_param_styles: &'a [codegen::PathStyle],
+    pub query_param_names: &'a [Ident],
+    pub query_param_types: &'a [Type],
+    /// The query parameters with `allowReserved`
+    pub query_reserved: &'a [String],
+    pub extractor: Option<&'a codegen::Extractor>,
+    pub responses: &'a [Response],
+}
+
+/// Compiles the client method of the operation, like
+/// `impl ApiClient { pub async fn get_posts(&self, ...) -> Result<GetPostsResponse, Error> }`.
+pub fn compile_client_method(
+    client: &Ident,
+    method: &parsing::MethodItem,
+    params: ClientParams,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) {
+    let ClientParams {
+        path_param_types,
+        path_param_styles,
+        query_param_names,
+        query_param_types,
+        query_reserved,
+        extractor,
+        responses,
+    } = params;
+    let struct_name = &method.struct_name;
+    let response_ident = format_ident!("{struct_name}Response");
+
+    // Decode the response into the variant of its status code
+    let arms = responses.iter().filter_map(|response| {
+        let code = response.status.parse::<u16>().ok()?;
+        let variant = format_ident!("Status{code}");
+        Some(match &response.body {
+            ResponseBody::Json(_) => {
+                quote!(#code => #response_ident::#variant(response.json().await?))
+            }
+            ResponseBody::Text => {
+                quote!(#code => #response_ident::#variant(response.text().await?))
+            }
+            ResponseBody::Binary => {
+                quote!(#code => #response_ident::#variant(response.bytes().await?))
+            }
+            ResponseBody::Empty => quote!(#code => #response_ident::#variant),
+            // A redirect without a valid `Location` is not the documented response
+            ResponseBody::Redirect => quote! {
+                #code => match ::axum_open_api::redirect::Location::from_headers(
+                    response.headers(),
+                ) {
+                    Some(location) => #response_ident::#variant(location),
+                    None => #response_ident::Other(status, response.bytes().await?),
+                }
+            },
+        })
+    });
+
+    // The arguments of the method
+    let path_param_idents = method.path.path_param_idents().collect::<Vec<_>>();
+    let path_format = method.path.to_oapi_path();
+    let path_params = path_param_idents.iter().zip(path_param_styles);
+    let path_format = path_params.fold(path_format, |path, (ident, style)| {
+        let ident = ident.to_string();
+        path.replace(&format!("{{{ident}}}"), &style.segment_format(&ident))
+    });
+    let (body_arg, body_call) = match extractor {
+        None => (None, None),
+        Some(extractor) => {
+            let body_ty = &extractor.body_ty;
+            let kind = extractor
+                .rejection_var
+                .segments
+                .last()
+                .unwrap()
+                .ident
+                .to_string();
+            match kind.as_str() {
+                "Json" => (Some(quote!(body: #body_ty,)), Some(quote!(.json(&body)))),
+                "Form" => (Some(quote!(body: #body_ty,)), Some(quote!(.form(&body)))),
+                "Multipart" => (
+                    Some(quote!(body: ::axum_open_api::client::reqwest::multipart::Form,)),
+                    Some(quote!(.multipart(body))),
+                ),
+                "Text" => (Some(quote!(body: String,)), Some(quote!(.body(body)))),
+                _ => (Some(quote!(body: Vec<u8>,)), Some(quote!(.body(body)))),
+            }
+        }
+    };
+
+    // Parameters with `allowReserved` are sent with their reserved characters, which `reqwest`
+    // would encode
+    let query = quote! {
+        __QueryGenerated__ {
+            #(#query_param_names: &#query_param_names,)*
+            __lifetime__: ::std::marker::PhantomData,
+        }
+    };
+    let (reserved_query, query_call) = match query_reserved.is_empty() {
+        true => (None, Some(quote!(.query(&#query)))),
+        false => (
+            Some(quote! {
+                let __query__ =
+                    ::axum_open_api::uri::encode_query_reserved(&#query, &[#(#query_reserved),*]);
+                let __path__ = if __query__.is_empty() {
+                    __path__
+                } else {
+                    format!("{__path__}?{__query__}")
+                };
+            }),
+            None,
+        ),
+    };
+
+    let depth_prefix = depth.prefix();
+    let method_ident = Ident::new(
+        &struct_name.to_string().to_case(Case::Snake),
+        Span::call_site(),
+    );
+    let http_method = Ident::new(&method.method_ty.to_string(), Span::call_site());
+    let method_doc = format!(
+        "Sends a `{} {}` request, like [`{struct_name}`] extracts it.",
+        method.method_ty,
+        method.path.to_oapi_path()
+    );
+    items.push(parse_quote! {
+        impl #depth_prefix #client {
+            #[doc = #method_doc]
+            pub async fn #method_ident(
+                &self,
+                #(#path_param_idents: #path_param_types,)*
+                #(#query_param_names: #query_param_types,)*
+                #body_arg
+            ) -> Result<#response_ident, ::axum_open_api::client::reqwest::Error> {
+                #[derive(serde::Serialize)]
+                struct __QueryGenerated__<'a> {
+                    #(#query_param_names: &'a #query_param_types,)*
+                    #[serde(skip)]
+                    __lifetime__: ::std::marker::PhantomData<&'a ()>,
+                }
+
+                // Named to not shadow parameters like `path`
+                let __path__ = format!(
+                    #path_format,
+                    #(::axum_open_api::uri::encode_path_param(&#path_param_idents)),*
+                );
+                #reserved_query
+                let response = self
+                    .client
+                    .request(::axum::http::Method::#http_method, self.url(&__path__))
+                    #query_call
+                    #body_call
+                    .send()
+                    .await?;
+
+                let status = response.status();
+                Ok(match status.as_u16() {
+                    #(#arms,)*
+                    _ => #response_ident::Other(status, response.bytes().await?),
+                })
+            }
+        }
+    });
+}
+
+/// Compiles the client struct, like `pub struct ApiClient { ... }`.
+pub fn compile_client(client: &Ident, items: &mut Vec<Item>) {
+    items.push(parse_quote! {
+        /// A client for the operations of the OpenAPI spec, sharing the generated schemas.
+        #[derive(Debug, Clone)]
+        pub struct #client {
+            pub base_url: String,
+            pub client: ::axum_open_api::client::reqwest::Client,
+        }
+    });
+    items.push(parse_quote! {
+        impl #client {
+            pub fn new(base_url: impl Into<String>) -> Self {
+                Self::with_client(base_url, ::axum_open_api::client::reqwest::Client::new())
+            }
+
+            /// Uses the given client, which can be configured with default headers for
+            /// authentication.
+            pub fn with_client(
+                base_url: impl Into<String>,
+                client: ::axum_open_api::client::reqwest::Client,
+            ) -> Self {
+                Self {
+                    base_url: base_url.into(),
+                    client,
+                }
+            }
+
+            fn url(&self, path: &str) -> String {
+                format!("{}{path}", self.base_url.trim_end_matches('/'))
+            }
+        }
+    });
+}
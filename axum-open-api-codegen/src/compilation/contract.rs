@@ -0,0 +1,177 @@
+use super::raw::{example, example_to_param, percent_encode, resolve};
+use crate::parsing;
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span};
+use serde_json::Value;
+use syn::{Item, Path};
+
+/// Compiles a contract test for the operation, that sends a request built from the examples
+/// in the spec to the router, and checks that the status code is documented.
+///
+/// Returns `None` if a required parameter has no example. The parameters of the path item must
+/// be merged into the operation. Operations with security requirements are ignored, as the
+/// examples have no credentials.
+pub fn compile_contract_test(
+    router: &Path,
+    method: &parsing::MethodItem,
+    raw_operation: &Value,
+    root: &Value,
+    path_prefix: &str,
+) -> syn::Result<Option<Item>> {
+    // Build the uri from the examples of the parameters
+    let mut path = format!("{path_prefix}{}", method.path.to_oapi_path());
+    let mut query = Vec::new();
+    for param in raw_operation["parameters"].as_array().into_iter().flatten() {
+        let param = resolve(root, param);
+        let name = param["name"].as_str().unwrap_or_default();
+        let example = example(root, param);
+        match (param["in"].as_str(), example) {
+            (Some("path"), Some(example)) => {
+                let value = percent_encode(&example_to_param(&example));
+                path = path.replace(&format!("{{{name}}}"), &value);
+            }
+            (Some("query"), Some(example)) => {
+                let value = percent_encode(&example_to_param(&example));
+                query.push(format!("{}={value}", percent_encode(name)));
+            }
+            (Some("path"), None) => return Ok(None),
+            (Some("query"), None) if param["required"] == true => return Ok(None),
+            _ => {}
+        }
+    }
+    let uri = if query.is_empty() {
+        path
+    } else {
+        format!("{path}?{}", query.join("&"))
+    };
+
+    // And the body from the example of the first media type
+    let request_body = resolve(root, &raw_operation["requestBody"]);
+    let media_type = request_body["content"]
+        .as_object()
+        .and_then(|content| content.iter().next());
+    let (content_type, body) =
+        match media_type.map(|(name, media_type)| (name, example(root, media_type))) {
+            Some((name, Some(example))) => {
+                let body = match example {
+                    Value::String(string) => string,
+                    other => other.to_string(),
+                };
+                (quote!(Some(#name)), quote!(Some(#body)))
+            }
+            Some((_, None)) if request_body["required"] == true => return Ok(None),
+            _ => (quote!(None), quote!(None)),
+        };
+
+    let statuses = raw_operation["responses"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(status, _)| status.as_str());
+
+    // The requirements of the operation replace the global ones, and are satisfied without
+    // credentials only if one of the alternatives is empty
+    let security = match raw_operation.get("security") {
+        Some(security) => security,
+        None => &root["security"],
+    };
+    let secured = security.as_array().is_some_and(|alternatives| {
+        !alternatives.is_empty()
+            && alternatives
+                .iter()
+                .all(|alternative| alternative.as_object().is_some_and(|a| !a.is_empty()))
+    });
+    let ignore = secured.then(
+        || quote!(#[ignore = "the security requirements can't be satisfied from the examples"]),
+    );
+
+    let method_ident = Ident::new(&method.method_ty.to_string(), Span::call_site());
+    let test_ident = Ident::new(
+        &method.struct_name.to_string().to_case(Case::Snake),
+        Span::call_site(),
+    );
+    Ok(Some(parse_quote! {
+        #[tokio::test]
+        #ignore
+        async fn #test_ident() {
+            ::axum_open_api::testing::check_contract_case(
+                #router(),
+                ::axum_open_api::testing::ContractCase {
+                    method: ::axum::http::Method::#method_ident,
+                    uri: #uri,
+                    content_type: #content_type,
+                    body: #body,
+                    statuses: &[#(#statuses),*],
+                },
+            )
+            .await;
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::expand;
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: Pets
+  version: 0.0.1
+security:
+  - ApiKey: []
+components:
+  securitySchemes:
+    ApiKey:
+      type: apiKey
+      in: header
+      name: X-API-Key
+paths:
+  /pets/{pet_name}:
+    parameters:
+      - in: path
+        name: pet_name
+        required: true
+        schema:
+          type: string
+        example: Mr. Whiskers/2
+    get:
+      security: []
+      parameters:
+        - in: query
+          name: tags
+          schema:
+            type: array
+            items:
+              type: string
+          example: [a&b, c]
+      responses:
+        '200':
+          description: The pet
+    delete:
+      responses:
+        '204':
+          description: Deleted
+"#;
+
+    #[test]
+    fn examples_are_encoded() {
+        let expanded = expand(
+            SPEC,
+            "contract_tests = crate::router; GET /pets/{pet_name} as pub GetPet;",
+        );
+        assert!(expanded.contains(r#"uri: "/pets/Mr.%20Whiskers%2F2?tags=a%26b,c","#));
+        assert!(!expanded.contains("#[ignore"));
+    }
+
+    #[test]
+    fn secured_operations_are_ignored() {
+        let expanded = expand(
+            SPEC,
+            "contract_tests = crate::router; DELETE /pets/{pet_name} as pub DeletePet;",
+        );
+        assert!(expanded.contains(
+            r#"#[ignore = "the security requirements can't be satisfied from the examples"]"#
+        ));
+    }
+}
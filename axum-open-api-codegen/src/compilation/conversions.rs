@@ -0,0 +1,105 @@
+use super::{
+    boxing::one_of_variants,
+    derives::{visit, visit_mut},
+    schema::compile_conversions,
+};
+use crate::codegen;
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use std::collections::{BTreeMap, BTreeSet};
+use syn::{GenericArgument, Item, PathArguments, Type};
+
+/// Removes the `From` and `TryFrom` conversions of the `oneOf` variants that are the same type as
+/// another variant of the enum, like two aliases of `String`, as a type can only be converted
+/// into the enum once. The types are compared after resolving the generated aliases.
+pub fn remove_conflicting_conversions(items: &mut [codegen::Item]) {
+    let mut aliases = BTreeMap::new();
+    visit(items, &mut |item| {
+        if let Item::Type(item) = item {
+            aliases.insert(item.ident.to_string(), (*item.ty).clone());
+        }
+    });
+
+    let mut conflicting = BTreeSet::new();
+    visit(items, &mut |item| {
+        let (Some(variants), Item::Enum(item)) = (one_of_variants(item), item) else {
+            return;
+        };
+        let variants = variants
+            .into_iter()
+            .map(|(variant_ident, ty)| (variant_ident, unboxed(ty)))
+            .collect::<Vec<_>>();
+        let resolved = variants
+            .iter()
+            .map(|(_, ty)| resolve(ty, &aliases, &mut BTreeSet::new()))
+            .collect::<Vec<_>>();
+        for ((variant_ident, ty), name) in variants.iter().zip(&resolved) {
+            if resolved.iter().filter(|other| *other == name).count() == 1 {
+                continue;
+            }
+            for boxed in [false, true] {
+                for conversion in compile_conversions(&item.ident, variant_ident, ty, boxed) {
+                    conflicting.insert(conversion.to_token_stream().to_string());
+                }
+            }
+        }
+    });
+    if conflicting.is_empty() {
+        return;
+    }
+
+    visit_mut(items, &mut |item| {
+        if let Item::Impl(_) = item {
+            if conflicting.contains(&item.to_token_stream().to_string()) {
+                *item = Item::Verbatim(TokenStream::new());
+            }
+        }
+    });
+}
+
+/// The type in a `Box`, which the conversions of a boxed variant convert from.
+fn unboxed(ty: Type) -> Type {
+    if let Type::Path(path) = &ty {
+        let segment = path.path.segments.last();
+        if let Some(PathArguments::AngleBracketed(args)) = segment
+            .filter(|segment| segment.ident == "Box")
+            .map(|segment| &segment.arguments)
+        {
+            if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                return inner.clone();
+            }
+        }
+    }
+    ty
+}
+
+/// The type with its aliases resolved, by the names of the types, like `Vec<Post>` for
+/// `super::schemas::Posts`.
+fn resolve(ty: &Type, aliases: &BTreeMap<String, Type>, visiting: &mut BTreeSet<String>) -> String {
+    let Type::Path(path) = ty else {
+        return ty.to_token_stream().to_string();
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return ty.to_token_stream().to_string();
+    };
+    let name = segment.ident.to_string();
+    if let Some(alias) = aliases.get(&name) {
+        if visiting.insert(name.clone()) {
+            let resolved = resolve(alias, aliases, visiting);
+            visiting.remove(&name);
+            return resolved;
+        }
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return name;
+    };
+    let args = args
+        .args
+        .iter()
+        .map(|arg| match arg {
+            GenericArgument::Type(ty) => resolve(ty, aliases, visiting),
+            arg => arg.to_token_stream().to_string(),
+        })
+        .collect::<Vec<_>>();
+    format!("{name}<{}>", args.join(", "))
+}
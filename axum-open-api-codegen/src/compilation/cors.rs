@@ -0,0 +1,130 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use serde_json::Value;
+use syn::Item;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Compiles a function returning the `CorsLayer` of the `x-cors` extension of the spec, like
+/// `x-cors: { allowOrigins: [https://example.com], allowHeaders: [authorization] }`.
+///
+/// Without `allowMethods`, the methods of the operations of the spec are allowed.
+pub fn compile_cors_layer(name: &Ident, root: &Value, items: &mut Vec<Item>) -> syn::Result<()> {
+    let cors = &root["x-cors"];
+    if !cors.is_object() {
+        return Err(err!(
+            name,
+            "`x-cors` extension not found at the root of the OpenAPI spec"
+        ));
+    }
+
+    let origins = compile_list(
+        cors,
+        "allowOrigins",
+        |origin| quote!(::axum::http::HeaderValue::from_static(#origin)),
+    )?;
+    let unknown_method = cors["allowMethods"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .find(|method| !METHODS.contains(&method.to_lowercase().as_str()));
+    if let Some(method) = unknown_method {
+        return Err(err!(name, "`x-cors` allows unknown method `{method}`"));
+    }
+    let methods = match cors.get("allowMethods") {
+        Some(_) => compile_list(cors, "allowMethods", |method| {
+            let method = Ident::new(&method.to_uppercase(), Span::call_site());
+            quote!(::axum::http::Method::#method)
+        })?,
+        None => {
+            let methods = METHODS
+                .into_iter()
+                .filter(|method| {
+                    let mut paths = root["paths"].as_object().into_iter().flatten();
+                    paths.any(|(_, path_item)| path_item.get(method).is_some())
+                })
+                .map(|method| Ident::new(&method.to_uppercase(), Span::call_site()))
+                .collect::<Vec<_>>();
+            (!methods.is_empty()).then(|| quote!([#(::axum::http::Method::#methods),*]))
+        }
+    };
+    // Header names must be lowercase to be created at compile time
+    let headers = compile_list(cors, "allowHeaders", |header| {
+        let header = header.to_lowercase();
+        quote!(::axum::http::HeaderName::from_static(#header))
+    })?;
+    let exposed = compile_list(cors, "exposeHeaders", |header| {
+        let header = header.to_lowercase();
+        quote!(::axum::http::HeaderName::from_static(#header))
+    })?;
+
+    // `tower_http` panics on credentials with a wildcard, so it is rejected here instead
+    let credentials = cors["allowCredentials"] == true;
+    let wildcard = [
+        "allowOrigins",
+        "allowMethods",
+        "allowHeaders",
+        "exposeHeaders",
+    ]
+    .into_iter()
+    .find(|key| cors[key] == "*");
+    if let (true, Some(key)) = (credentials, wildcard) {
+        return Err(err!(
+            name,
+            "`x-cors` can not allow credentials with `{key}: \"*\"`"
+        ));
+    }
+
+    let origins = origins.map(|origins| quote!(.allow_origin(#origins)));
+    let methods = methods.map(|methods| quote!(.allow_methods(#methods)));
+    let headers = headers.map(|headers| quote!(.allow_headers(#headers)));
+    let exposed = exposed.map(|exposed| quote!(.expose_headers(#exposed)));
+    let credentials = credentials.then(|| quote!(.allow_credentials(true)));
+    let max_age = cors["maxAge"]
+        .as_u64()
+        .map(|seconds| quote!(.max_age(::std::time::Duration::from_secs(#seconds))));
+    items.push(parse_quote! {
+        /// The CORS policy of the `x-cors` extension of the OpenAPI spec
+        pub fn #name() -> ::axum_open_api::cors::CorsLayer {
+            ::axum_open_api::require_cors_feature!();
+            ::axum_open_api::cors::CorsLayer::new()
+                #origins
+                #methods
+                #headers
+                #exposed
+                #credentials
+                #max_age
+        }
+    });
+    Ok(())
+}
+
+/// Compiles a list of `x-cors`, which is either `"*"` or an array of strings.
+fn compile_list(
+    cors: &Value,
+    key: &str,
+    compile_value: impl Fn(&str) -> TokenStream,
+) -> syn::Result<Option<TokenStream>> {
+    match &cors[key] {
+        Value::Null => Ok(None),
+        Value::String(wildcard) if wildcard == "*" => Ok(Some(quote!(::axum_open_api::cors::Any))),
+        Value::Array(values) => {
+            let values = values
+                .iter()
+                .map(|value| {
+                    let value = value.as_str().ok_or_else(|| {
+                        err_call_site!("`x-cors` `{key}` must only contain strings: {value}")
+                    })?;
+                    Ok(compile_value(value))
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            // An empty list allows nothing, which is the default
+            Ok((!values.is_empty()).then(|| quote!([#(#values),*])))
+        }
+        other => Err(err_call_site!(
+            "`x-cors` `{key}` must be \"*\" or a list: {other}"
+        )),
+    }
+}
@@ -0,0 +1,328 @@
+use crate::codegen;
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::{BTreeMap, BTreeSet};
+use syn::{
+    punctuated::Punctuated, Attribute, Expr, ExprLit, Fields, Item, ItemEnum, Lit, LitStr,
+    MetaNameValue, Path, Type,
+};
+
+/// The types that are used in requests and in responses, from which the derives of the
+/// generated types are decided with `derives = usage;`.
+#[derive(Default)]
+pub struct Usage {
+    /// The types that are extracted from requests, which must implement `Deserialize`
+    pub requests: Vec<Type>,
+    /// The types that are sent in responses, which must implement `Serialize`
+    pub responses: Vec<Type>,
+}
+
+/// Removes the `Serialize` derive from the generated types that are only used in requests, and
+/// the `Deserialize` derive from the types that are only used in responses. Types that are used
+/// in neither, or in both, keep both derives. The security module is not changed.
+pub fn gate_derives(items: &mut [codegen::Item], usage: &Usage) {
+    // The generated types by name, with the names they refer to
+    let mut graph = BTreeMap::new();
+    visit(items, &mut |item| {
+        if let Some((name, refs)) = references(item) {
+            graph.entry(name).or_insert_with(BTreeSet::new).extend(refs);
+        }
+    });
+
+    let serialize = reachable(&usage.responses, &graph);
+    let deserialize = reachable(&usage.requests, &graph);
+    visit_mut(items, &mut |item| {
+        let (ident, attrs) = match item {
+            Item::Struct(item) => (&item.ident, &mut item.attrs),
+            Item::Enum(item) => (&item.ident, &mut item.attrs),
+            _ => return,
+        };
+        let name = ident.to_string();
+        let (serialize, deserialize) = (serialize.contains(&name), deserialize.contains(&name));
+        if serialize == deserialize {
+            return;
+        }
+        let removed = if serialize {
+            "Deserialize"
+        } else {
+            "Serialize"
+        };
+        edit_derives(attrs, |derives| {
+            *derives = derives
+                .iter()
+                .filter(|derive| {
+                    derive
+                        .segments
+                        .last()
+                        .is_some_and(|segment| segment.ident != removed)
+                })
+                .cloned()
+                .collect();
+        });
+    });
+}
+
+/// Adds the `EnumIter`, `EnumString` and `IntoStaticStr` derives of `strum` to the enums generated
+/// from string schemas with an `enum`, using the values of the spec. Their own `FromStr` is
+/// removed, because `EnumString` derives it.
+pub fn add_strum_derives(items: &mut [codegen::Item]) {
+    let mut enums = BTreeSet::new();
+    visit(items, &mut |item| match item {
+        Item::Enum(item) if is_string_enum(item) => {
+            enums.insert(item.ident.to_string());
+        }
+        _ => {}
+    });
+
+    visit_mut(items, &mut |item| {
+        if let Item::Impl(impl_item) = item {
+            let from_str = impl_item.trait_.as_ref().is_some_and(|(_, path, _)| {
+                path.segments
+                    .last()
+                    .is_some_and(|segment| segment.ident == "FromStr")
+            });
+            if from_str && enums.contains(&impl_item.self_ty.to_token_stream().to_string()) {
+                *item = Item::Verbatim(TokenStream::new());
+            }
+            return;
+        }
+        let Item::Enum(item) = item else {
+            return;
+        };
+        if !enums.contains(&item.ident.to_string()) {
+            return;
+        }
+        edit_derives(&mut item.attrs, |derives| {
+            derives.push(parse_quote!(strum::EnumIter));
+            derives.push(parse_quote!(strum::EnumString));
+            derives.push(parse_quote!(strum::IntoStaticStr));
+        });
+        for variant in &mut item.variants {
+            if let Some(value) = serde_rename(&variant.attrs) {
+                variant
+                    .attrs
+                    .push(parse_quote!(#[strum(serialize = #value)]));
+            }
+        }
+    });
+}
+
+/// Adds the `PartialOrd` and `Ord` derives to the enums generated from string schemas with an
+/// `enum`, which are ordered like the values of the spec. Aliases of scalars are already ordered
+/// by their type, except for `f64`.
+pub fn add_ord_derives(items: &mut [codegen::Item]) {
+    visit_mut(items, &mut |item| match item {
+        Item::Enum(item) if is_string_enum(item) => {
+            edit_derives(&mut item.attrs, |derives| {
+                derives.push(parse_quote!(PartialOrd));
+                derives.push(parse_quote!(Ord));
+            });
+        }
+        _ => {}
+    });
+}
+
+/// Marks the structs and enums generated from schemas `#[non_exhaustive]`, so fields and values
+/// can be added to the spec without breaking other crates. Those crates construct the structs
+/// with their builders.
+pub fn add_non_exhaustive(items: &mut [codegen::Item]) {
+    visit_mut(items, &mut |item| {
+        let attrs = match item {
+            Item::Struct(item) => &mut item.attrs,
+            Item::Enum(item) => &mut item.attrs,
+            _ => return,
+        };
+        let derives_serde = attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("derive"))
+            .filter_map(|attr| {
+                attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                    .ok()
+            })
+            .flatten()
+            .any(|derive| {
+                derive
+                    .segments
+                    .first()
+                    .is_some_and(|segment| segment.ident == "serde")
+            });
+        if derives_serde {
+            attrs.push(parse_quote!(#[non_exhaustive]));
+        }
+    });
+}
+
+/// Whether the enum was generated from a string schema with an `enum`: all of its variants are
+/// unit variants, renamed to their value.
+fn is_string_enum(item: &ItemEnum) -> bool {
+    item.variants
+        .iter()
+        .all(|variant| variant.fields.is_empty() && serde_rename(&variant.attrs).is_some())
+}
+
+/// The value of `#[serde(rename = "value")]`, if it exists.
+fn serde_rename(attrs: &[Attribute]) -> Option<LitStr> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .filter_map(|attr| attr.parse_args::<MetaNameValue>().ok())
+        .filter(|meta| meta.path.is_ident("rename"))
+        .find_map(|meta| match meta.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(value),
+                ..
+            }) => Some(value),
+            _ => None,
+        })
+}
+
+/// Edits the derives of the `#[derive(...)]` attributes.
+fn edit_derives(attrs: &mut [Attribute], mut f: impl FnMut(&mut Punctuated<Path, Token![,]>)) {
+    for attr in attrs
+        .iter_mut()
+        .filter(|attr| attr.path().is_ident("derive"))
+    {
+        let Ok(mut derives) = attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+        else {
+            continue;
+        };
+        f(&mut derives);
+        *attr = parse_quote!(#[derive(#derives)]);
+    }
+}
+
+/// The name of a generated type, with the idents in the types it refers to.
+fn references(item: &Item) -> Option<(String, BTreeSet<String>)> {
+    let (name, tokens) = match item {
+        Item::Struct(item) => (&item.ident, fields_tokens(&item.fields)),
+        Item::Enum(item) => {
+            let mut tokens = TokenStream::new();
+            for variant in &item.variants {
+                tokens.extend(fields_tokens(&variant.fields));
+            }
+            (&item.ident, tokens)
+        }
+        Item::Type(item) => (&item.ident, item.ty.to_token_stream()),
+        _ => return None,
+    };
+    Some((name.to_string(), idents(tokens)))
+}
+
+fn fields_tokens(fields: &Fields) -> TokenStream {
+    fields
+        .iter()
+        .map(|field| field.ty.to_token_stream())
+        .collect()
+}
+
+fn idents(tokens: TokenStream) -> BTreeSet<String> {
+    let mut idents = BTreeSet::new();
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                idents.insert(ident.to_string());
+            }
+            TokenTree::Group(group) => idents.extend(self::idents(group.stream())),
+            _ => {}
+        }
+    }
+    idents
+}
+
+/// The names of the generated types that are reachable from the types.
+fn reachable(types: &[Type], graph: &BTreeMap<String, BTreeSet<String>>) -> BTreeSet<String> {
+    let mut reachable = BTreeSet::new();
+    let mut stack = types
+        .iter()
+        .flat_map(|ty| idents(ty.to_token_stream()))
+        .collect::<Vec<_>>();
+    while let Some(name) = stack.pop() {
+        if let Some(refs) = graph.get(&name) {
+            if reachable.insert(name) {
+                stack.extend(refs.iter().cloned());
+            }
+        }
+    }
+    reachable
+}
+
+pub(super) fn visit(items: &[codegen::Item], f: &mut impl FnMut(&Item)) {
+    for item in items {
+        match item {
+            codegen::Item::Module(module) if module.name != "security" => visit(&module.items, f),
+            codegen::Item::Schema(item) => visit_item(item, f),
+            _ => {}
+        }
+    }
+}
+
+fn visit_item(item: &Item, f: &mut impl FnMut(&Item)) {
+    match item {
+        // Schemas can be placed in submodules with `x-rust-module`
+        Item::Mod(module) => {
+            for item in module.content.iter().flat_map(|(_, items)| items) {
+                visit_item(item, f);
+            }
+        }
+        item => f(item),
+    }
+}
+
+pub(super) fn visit_mut(items: &mut [codegen::Item], f: &mut impl FnMut(&mut Item)) {
+    for item in items {
+        match item {
+            codegen::Item::Module(module) if module.name != "security" => {
+                visit_mut(&mut module.items, f)
+            }
+            codegen::Item::Schema(item) => visit_item_mut(item, f),
+            _ => {}
+        }
+    }
+}
+
+fn visit_item_mut(item: &mut Item, f: &mut impl FnMut(&mut Item)) {
+    match item {
+        Item::Mod(module) => {
+            for item in module.content.iter_mut().flat_map(|(_, items)| items) {
+                visit_item_mut(item, f);
+            }
+        }
+        item => f(item),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::expand;
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: Pets
+  version: 0.0.1
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        name:
+          type: string
+    Kind:
+      type: string
+      enum: [cat, dog]
+    Name:
+      type: string
+paths: {}
+"#;
+
+    #[test]
+    fn non_exhaustive_schemas() {
+        let expanded = expand(SPEC, "non_exhaustive = true;");
+        assert!(expanded.contains("#[non_exhaustive]\n    pub struct Pet {"));
+        assert!(expanded.contains("#[non_exhaustive]\n    pub enum Kind {"));
+        // Aliases and builders have no serde derives
+        assert!(!expanded.contains("#[non_exhaustive]\n    pub type Name"));
+        assert!(!expanded.contains("#[non_exhaustive]\n    pub struct PetBuilder"));
+    }
+}
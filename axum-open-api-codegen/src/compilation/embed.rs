@@ -0,0 +1,46 @@
+use proc_macro2::Ident;
+use serde_json::Value;
+use syn::Item;
+
+/// Embeds the spec the code was generated from as the `OPENAPI_SPEC` and `OPENAPI_SPEC_JSON`
+/// constants. With the `serve_spec` option, a router serving them at `/openapi.yaml` and
+/// `/openapi.json` is generated as well.
+pub fn compile_embedded_spec(
+    source: &str,
+    raw: &Value,
+    serve_spec: Option<&Ident>,
+    items: &mut Vec<Item>,
+) {
+    let json = serde_json::to_string_pretty(raw).unwrap();
+    items.push(parse_quote! {
+        /// The OpenAPI spec the code was generated from, exactly as it was written
+        pub const OPENAPI_SPEC: &str = #source;
+    });
+    items.push(parse_quote! {
+        /// The OpenAPI spec the code was generated from, as json
+        pub const OPENAPI_SPEC_JSON: &str = #json;
+    });
+
+    let Some(serve_spec) = serve_spec else {
+        return;
+    };
+    items.push(parse_quote! {
+        /// A router serving the OpenAPI spec at `/openapi.json` and `/openapi.yaml`
+        pub fn #serve_spec<S: Clone + Send + Sync + 'static>() -> ::axum::Router<S> {
+            use ::axum::http::header::CONTENT_TYPE;
+            ::axum::Router::new()
+                .route(
+                    "/openapi.json",
+                    ::axum::routing::get(|| async {
+                        ([(CONTENT_TYPE, "application/json")], OPENAPI_SPEC_JSON)
+                    }),
+                )
+                .route(
+                    "/openapi.yaml",
+                    ::axum::routing::get(|| async {
+                        ([(CONTENT_TYPE, "application/yaml")], OPENAPI_SPEC)
+                    }),
+                )
+        }
+    });
+}
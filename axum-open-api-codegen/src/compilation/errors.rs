@@ -0,0 +1,122 @@
+use super::{raw::resolve, responses::status_name, schema::Depth};
+use crate::codegen;
+use proc_macro2::{Ident, Span};
+use serde_json::Value;
+use syn::Item;
+
+/// A `4XX` or `5XX` json response of an operation, with a schema from `components/schemas`.
+pub struct ErrorResponse {
+    pub code: u16,
+    /// The name of the schema in `components/schemas`
+    pub schema: String,
+    /// The operationId, or `METHOD /path` if it has none
+    pub operation: String,
+}
+
+/// Collects the error responses of the operation, for the catalog of `errors = errors;`.
+pub fn error_responses(operation: &str, raw_operation: &Value, root: &Value) -> Vec<ErrorResponse> {
+    let responses = raw_operation["responses"].as_object().into_iter().flatten();
+    responses
+        .filter_map(|(status, response)| {
+            let code = status.parse::<u16>().ok().filter(|code| *code >= 400)?;
+            let schema = resolve(root, response)["content"]["application/json"]["schema"]["$ref"]
+                .as_str()?
+                .strip_prefix("#/components/schemas/")?;
+            Some(ErrorResponse {
+                code,
+                schema: schema.to_owned(),
+                operation: operation.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Compiles the catalog of the error responses into a module with an `ApiError` enum, with a
+/// variant for every status and schema, like `NotFound(schemas::Error)`. Application errors can
+/// be converted into it in one place, and it responds with the documented status.
+pub fn compile_errors(
+    name: &Ident,
+    errors: &[ErrorResponse],
+    depth: &Depth,
+) -> syn::Result<codegen::ModuleItem> {
+    if errors.is_empty() {
+        return Err(err!(
+            name,
+            "No error responses with a json schema from `components/schemas` in the declared \
+            operations"
+        ));
+    }
+
+    // Every status and schema is a variant, with the operations that document it
+    let mut variants: Vec<(u16, &str, Vec<&str>)> = Vec::new();
+    for error in errors {
+        let existing = variants
+            .iter_mut()
+            .find(|(code, schema, _)| *code == error.code && *schema == error.schema);
+        match existing {
+            Some((_, _, operations)) => operations.push(&error.operation),
+            None => variants.push((error.code, &error.schema, vec![&error.operation])),
+        }
+    }
+    variants.sort_by_key(|(code, _, _)| *code);
+
+    let schemas = depth.schemas();
+    let mut definitions = Vec::new();
+    let mut arms = Vec::new();
+    let mut statuses = Vec::new();
+    for (code, schema, operations) in &variants {
+        // Named after the status, and also after the schema if the status is not unique
+        let unique = variants
+            .iter()
+            .filter(|(other, _, _)| other == code)
+            .count()
+            == 1;
+        let variant = match unique {
+            true => Ident::new(&status_name(*code), Span::call_site()),
+            false => format_ident!("{}{schema}", status_name(*code)),
+        };
+        let schema = Ident::new(schema, Span::call_site());
+        let doc = format!("`{code}`, documented by `{}`", operations.join("`, `"));
+        definitions.push(quote!(#[doc = #doc] #variant(#schemas::#schema)));
+        let status = quote!(::axum::http::StatusCode::from_u16(#code).unwrap());
+        arms.push(quote! {
+            ApiError::#variant(body) => (#status, ::axum::Json(body)).into_response()
+        });
+        statuses.push(quote!(ApiError::#variant(_) => #status));
+    }
+
+    let items: Vec<Item> = vec![
+        parse_quote! {
+            /// The error responses of the OpenAPI spec, by their status and schema
+            #[derive(Debug)]
+            pub enum ApiError {
+                #(#definitions,)*
+            }
+        },
+        parse_quote! {
+            impl ApiError {
+                /// The status of the error response
+                pub fn status(&self) -> ::axum::http::StatusCode {
+                    match self {
+                        #(#statuses,)*
+                    }
+                }
+            }
+        },
+        parse_quote! {
+            impl ::axum::response::IntoResponse for ApiError {
+                fn into_response(self) -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
+                    match self {
+                        #(#arms,)*
+                    }
+                }
+            }
+        },
+    ];
+    Ok(codegen::ModuleItem {
+        vis: parse_quote!(pub),
+        name: name.clone(),
+        items: items.into_iter().map(codegen::Item::Schema).collect(),
+    })
+}
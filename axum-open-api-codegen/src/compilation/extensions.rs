@@ -0,0 +1,90 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span, TokenStream};
+use serde_json::Value;
+use syn::Item;
+
+/// Compiles the vendor extensions of an operation or schema into associated constants of its
+/// type, like `impl GetPosts { pub const X_RATE_LIMIT_TIER: &'static str = "gold"; }`.
+///
+/// Strings, booleans and numbers keep their type, other values are the json they were written
+/// as.
+pub fn compile_extensions(ty: &Ident, raw: &Value, items: &mut Vec<Item>) {
+    let consts = raw
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(name, _)| name.starts_with("x-"))
+        .map(|(name, value)| {
+            let ident = Ident::new(&name.to_case(Case::UpperSnake), Span::call_site());
+            let (ty, value) = extension_value(value);
+            let doc = format!("The `{name}` extension in the OpenAPI spec");
+            quote! {
+                #[doc = #doc]
+                pub const #ident: #ty = #value;
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if !consts.is_empty() {
+        items.push(parse_quote! {
+            impl #ty {
+                #(#consts)*
+            }
+        });
+    }
+}
+
+fn extension_value(value: &Value) -> (TokenStream, TokenStream) {
+    match value {
+        Value::String(string) => (quote!(&'static str), quote!(#string)),
+        Value::Bool(bool) => (quote!(bool), quote!(#bool)),
+        Value::Number(number) => match number.as_i64() {
+            Some(int) => (quote!(i64), quote!(#int)),
+            None => {
+                let float = number.as_f64().unwrap_or_default();
+                (quote!(f64), quote!(#float))
+            }
+        },
+        other => {
+            let json = other.to_string();
+            (quote!(&'static str), quote!(#json))
+        }
+    }
+}
+
+/// Compiles the `x-rate-limit` extension of an operation, like
+/// `x-rate-limit: { requests: 100, period: 60 }` with the period in seconds, into the
+/// `RATE_LIMIT` constant of its struct.
+pub fn compile_rate_limit(ty: &Ident, raw: &Value, items: &mut Vec<Item>) -> syn::Result<()> {
+    let rate_limit = &raw["x-rate-limit"];
+    if rate_limit.is_null() {
+        return Ok(());
+    }
+    let number = |key: &str| {
+        rate_limit[key]
+            .as_u64()
+            .filter(|number| *number > 0 && *number <= u32::MAX as u64)
+            .map(|number| number as u32)
+    };
+    let (Some(requests), Some(period)) = (number("requests"), number("period")) else {
+        return Err(err_call_site!(
+            "`x-rate-limit` of `{ty}` must have a positive `requests` and `period`: {rate_limit}"
+        ));
+    };
+    let period = period as u64;
+    let burst = number("burst").unwrap_or(requests);
+
+    let doc = format!("{requests} requests per {period} seconds, from `x-rate-limit`");
+    items.push(parse_quote! {
+        impl #ty {
+            #[doc = #doc]
+            pub const RATE_LIMIT: ::axum_open_api::rate_limit::RateLimit =
+                ::axum_open_api::rate_limit::RateLimit {
+                    requests: #requests,
+                    period: ::std::time::Duration::from_secs(#period),
+                    burst: #burst,
+                };
+        }
+    });
+    Ok(())
+}
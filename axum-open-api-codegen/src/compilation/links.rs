@@ -0,0 +1,192 @@
+use super::{
+    raw::{resolve, with_path_item_parameters},
+    schema::{field_ident, path_style, Depth},
+};
+use crate::codegen::PathStyle;
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span};
+use serde_json::Value;
+use syn::Item;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Compiles the `links` of the operation's responses into url helpers on the response schemas,
+/// like `impl schemas::User { pub fn get_user_address_url(&self) -> String }`.
+///
+/// Only responses with a json schema from `components/schemas` are supported. Parameters like
+/// `$response.body#/id` are taken from the fields of the schema, other runtime expressions
+/// become arguments of the helper.
+pub fn compile_links(
+    raw_operation: &Value,
+    root: &Value,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<()> {
+    for (_, response) in raw_operation["responses"].as_object().into_iter().flatten() {
+        let response = resolve(root, response);
+        let Some(links) = response["links"].as_object() else {
+            continue;
+        };
+        let Some(schema_name) = response["content"]["application/json"]["schema"]["$ref"]
+            .as_str()
+            .and_then(|reference| reference.strip_prefix("#/components/schemas/"))
+        else {
+            continue;
+        };
+        let schema = &root["components"]["schemas"][schema_name];
+
+        let helpers = links
+            .iter()
+            .map(|(name, link)| compile_link(name, resolve(root, link), schema, root))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        let schemas = depth.schemas();
+        let schema_ident = Ident::new(schema_name, Span::call_site());
+        items.push(parse_quote! {
+            impl #schemas::#schema_ident {
+                #(#helpers)*
+            }
+        });
+    }
+    Ok(())
+}
+
+fn compile_link(
+    name: &str,
+    link: &Value,
+    schema: &Value,
+    root: &Value,
+) -> syn::Result<syn::ImplItemFn> {
+    let (path_template, target) = find_target(link, root).ok_or_else(|| {
+        err_call_site!("Target operation of link {name} not found in OpenAPI spec")
+    })?;
+
+    // Parameters are either in the path or in the query of the target
+    let target_param = |param: &str| {
+        target["parameters"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|target_param| resolve(root, target_param))
+            .find(|target_param| target_param["name"] == param)
+    };
+    let location_of = |param: &str| {
+        target_param(param)
+            .and_then(|target_param| target_param["in"].as_str())
+            .unwrap_or("query")
+    };
+
+    let mut path_format = path_template.to_owned();
+    let mut path_values = Vec::new();
+    let mut query = Vec::new();
+    let mut args = Vec::new();
+    let mut optional = false;
+    for (param, expression) in link["parameters"].as_object().into_iter().flatten() {
+        // Parameter names can be qualified with their location, like `path.id`
+        let (location, param) = match param.split_once('.') {
+            Some((location, param)) => (location, param),
+            None => (location_of(param), param.as_str()),
+        };
+
+        let value = match expression.as_str() {
+            Some(expression) if expression.starts_with("$response.body#/") => {
+                let field = expression.trim_start_matches("$response.body#/");
+                let required = schema["required"]
+                    .as_array()
+                    .is_some_and(|required| required.iter().any(|r| r == field));
+                let field = field_ident(field);
+                if required {
+                    quote!(&self.#field)
+                } else {
+                    optional = true;
+                    quote!(self.#field.as_ref()?)
+                }
+            }
+            Some(expression) if expression.starts_with('$') => {
+                let arg = Ident::new(&param.to_case(Case::Snake), Span::call_site());
+                args.push(quote!(#arg: &dyn ::std::fmt::Display));
+                quote!(#arg)
+            }
+            Some(constant) => quote!(&#constant),
+            None => {
+                let constant = expression.to_string();
+                quote!(&#constant)
+            }
+        };
+        let value = quote!(::axum_open_api::uri::encode_path_param(#value));
+
+        if location == "path" {
+            let style = target_param(param).map_or(PathStyle::Simple, path_style);
+            let segment = style.segment_format(param);
+            path_format = path_format.replace(&format!("{{{param}}}"), &segment);
+            path_values.push((path_template.find(&format!("{{{param}}}")), value));
+        } else {
+            query.push(quote!(format!("{}={}", #param, #value)));
+        }
+    }
+    // The values must be in the same order as their placeholders
+    path_values.sort_by_key(|(position, _)| *position);
+    let path_values = path_values.into_iter().map(|(_, value)| value);
+
+    let url = quote! {
+        {
+            let path = format!(#path_format, #(#path_values),*);
+            let query: Vec<String> = vec![#(#query),*];
+            if query.is_empty() {
+                path
+            } else {
+                format!("{path}?{}", query.join("&"))
+            }
+        }
+    };
+    let (output, url) = if optional {
+        (quote!(Option<String>), quote!(Some(#url)))
+    } else {
+        (quote!(String), url)
+    };
+
+    let helper_ident = Ident::new(
+        &format!("{}_url", name.to_case(Case::Snake)),
+        Span::call_site(),
+    );
+    let doc = match link["description"].as_str() {
+        Some(description) => format!("Builds the url of the `{name}` link: {description}"),
+        None => format!("Builds the url of the `{name}` link."),
+    };
+    Ok(parse_quote! {
+        #[doc = #doc]
+        pub fn #helper_ident(&self, #(#args),*) -> #output {
+            #url
+        }
+    })
+}
+
+/// Finds the path template and the operation targeted by the link, by its `operationId` or its
+/// `operationRef`. The operation includes the parameters of its path item.
+fn find_target<'a>(link: &Value, root: &'a Value) -> Option<(&'a str, Value)> {
+    if let Some(operation_id) = link["operationId"].as_str() {
+        return root["paths"]
+            .as_object()?
+            .iter()
+            .find_map(|(path, path_item)| {
+                METHODS
+                    .into_iter()
+                    .filter_map(|method| path_item.get(method))
+                    .find(|operation| operation["operationId"] == operation_id)
+                    .map(|operation| {
+                        let operation = with_path_item_parameters(root, path_item, operation);
+                        (path.as_str(), operation)
+                    })
+            });
+    }
+
+    // An `operationRef` is a json pointer like `#/paths/~1users~1{id}/get`
+    let pointer = link["operationRef"].as_str()?.strip_prefix("#/paths/")?;
+    let (path, method) = pointer.rsplit_once('/')?;
+    let path = path.replace("~1", "/").replace("~0", "~");
+    let (path, path_item) = root["paths"].as_object()?.get_key_value(&path)?;
+    let operation = with_path_item_parameters(root, path_item, path_item.get(method)?);
+    Some((path.as_str(), operation))
+}
@@ -0,0 +1,66 @@
+use proc_macro2::Ident;
+use serde_json::Value;
+use syn::Item;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Compiles a function adding a fallback to every path of the spec, which responds with
+/// `405 Method Not Allowed` and an `Allow` header with the methods declared in the spec.
+pub fn compile_method_not_allowed(
+    name: &Ident,
+    root: &Value,
+    path_prefix: &str,
+    items: &mut Vec<Item>,
+) {
+    let mut axum_paths = Vec::new();
+    let mut allows = Vec::new();
+    for (path, path_item) in root["paths"].as_object().into_iter().flatten() {
+        let methods = METHODS
+            .into_iter()
+            .filter(|method| path_item.get(method).is_some())
+            .map(str::to_uppercase)
+            .collect::<Vec<_>>();
+        if methods.is_empty() {
+            continue;
+        }
+        allows.push(methods.join(", "));
+        axum_paths.push(format!("{path_prefix}{}", to_axum_path(path)));
+    }
+
+    items.push(parse_quote! {
+        /// Adds a fallback to every path of the OpenAPI spec, responding to the methods that
+        /// are not routed with `405 Method Not Allowed` and the methods of the spec in the
+        /// `Allow` header. Routes nested with `oapi_nest` are shadowed by it.
+        pub fn #name<S: Clone + Send + Sync + 'static>(
+            router: ::axum::Router<S>,
+        ) -> ::axum::Router<S> {
+            router
+                #(
+                    .route(
+                        #axum_paths,
+                        ::axum::routing::MethodRouter::new().fallback(|| async {
+                            (
+                                ::axum::http::StatusCode::METHOD_NOT_ALLOWED,
+                                [(::axum::http::header::ALLOW, #allows)],
+                            )
+                        }),
+                    )
+                )*
+        }
+    });
+}
+
+/// Converts a path of the spec, like `/users/{user_id}`, to an axum path like `/users/:user_id`.
+fn to_axum_path(path: &str) -> String {
+    path.split('/')
+        .map(
+            |segment| match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(param) => format!(":{param}"),
+                None => segment.to_owned(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
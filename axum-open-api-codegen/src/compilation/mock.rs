@@ -0,0 +1,66 @@
+use super::raw::{example, resolve};
+use crate::parsing;
+use proc_macro2::TokenStream;
+use serde_json::Value;
+
+/// Compiles the route of the operation in the mock router, like
+/// `.route("/users/:user_id", get(|| async { ... }))`.
+///
+/// The handler responds with the first successful response of the operation, or else the first
+/// response, using the example of its first media type as the body.
+pub fn compile_mock_route(
+    method: &parsing::MethodItem,
+    raw_operation: &Value,
+    root: &Value,
+    path_prefix: &str,
+) -> TokenStream {
+    let responses = raw_operation["responses"].as_object();
+    let (status, response) = responses
+        .and_then(|responses| {
+            responses
+                .iter()
+                .find(|(status, _)| status.starts_with('2'))
+                .or_else(|| responses.iter().next())
+        })
+        .map(|(status, response)| (status.as_str(), resolve(root, response)))
+        .unwrap_or(("200", &Value::Null));
+
+    // `2XX` and `default` have no exact status code
+    let status = status
+        .parse::<u16>()
+        .unwrap_or(match status.as_bytes().first() {
+            Some(class @ b'1'..=b'5') => u16::from(class - b'0') * 100,
+            _ => 200,
+        });
+
+    let media_type = response["content"]
+        .as_object()
+        .and_then(|content| content.iter().next());
+    let body = match media_type.map(|(name, media_type)| (name, example(root, media_type))) {
+        Some((content_type, Some(example))) => {
+            let body = match example {
+                Value::String(string) => string,
+                other => other.to_string(),
+            };
+            quote! {
+                (
+                    status,
+                    [(::axum::http::header::CONTENT_TYPE, #content_type)],
+                    #body,
+                )
+                    .into_response()
+            }
+        }
+        _ => quote!(status.into_response()),
+    };
+
+    let axum_path = format!("{path_prefix}{}", method.path.to_axum_path());
+    let method_ty = method.method_ty;
+    quote! {
+        .route(#axum_path, ::axum::routing::#method_ty(|| async {
+            use ::axum::response::IntoResponse;
+            let status = ::axum::http::StatusCode::from_u16(#status).unwrap();
+            #body
+        }))
+    }
+}
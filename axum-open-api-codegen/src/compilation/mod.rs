@@ -1,76 +1,481 @@
+mod api_trait;
+mod borrowed;
+mod boxing;
+mod builder;
+mod callback;
+mod client;
+mod contract;
+mod conversions;
+mod cors;
+mod derives;
+mod embed;
+mod errors;
+mod extensions;
+mod links;
+mod method_not_allowed;
+mod mock;
+mod models;
+mod operation_id;
+mod query;
+mod raw;
+mod reachable;
+mod responses;
+mod scaffold;
 mod schema;
+mod security;
+mod servers;
+mod test_request;
+mod typed_headers;
+mod unsupported;
 use crate::{codegen, parsing};
-use oas3::Spec;
-use proc_macro2::{Ident, Span};
-use schema::{compile_param, compile_schema};
-use syn::Item;
+use api_trait::{compile_api_trait, compile_build_router, compile_validated_router, ApiOperation};
+use borrowed::add_borrowed_types;
+use boxing::box_large_variants;
+use callback::compile_callbacks;
+use client::{compile_client, compile_client_method, ClientParams};
+use contract::compile_contract_test;
+use conversions::remove_conflicting_conversions;
+use cors::compile_cors_layer;
+use derives::{add_non_exhaustive, add_ord_derives, add_strum_derives, gate_derives, Usage};
+use embed::compile_embedded_spec;
+use errors::{compile_errors, error_responses, ErrorResponse};
+use extensions::{compile_extensions, compile_rate_limit};
+use links::compile_links;
+use method_not_allowed::compile_method_not_allowed;
+use mock::compile_mock_route;
+use models::write_models;
+use oas3::{spec::Operation, Spec};
+use operation_id::{compile_operation_id, OperationVariant};
+use proc_macro2::{Ident, Span, TokenStream};
+use query::{compile_shared_queries, shared_query, SharedQuery};
+use raw::{resolve, with_path_item_parameters};
+use reachable::reachable_schemas;
+use responses::{
+    compile_response_bounds, compile_response_enum, compile_response_wrappers, compile_responses,
+    ResponseBody,
+};
+use scaffold::scaffold_handler;
+use schema::{compile_param, compile_schema, path_style, variant_ident, Depth};
+use security::{compile_security_requirements, compile_security_scheme};
+use serde_json::Value;
+use servers::compile_servers;
+use std::collections::{BTreeMap, BTreeSet};
+use syn::{spanned::Spanned, Item};
+use test_request::compile_test_request;
+use typed_headers::compile_typed_headers;
+use unsupported::Policies;
 
 pub struct Compiler {
     spec: Spec,
+    /// The spec as plain json, for everything that is not parsed into `spec`.
+    raw: Value,
+    options: parsing::Options,
+    /// The contract tests of the compiled methods, if enabled
+    contract_tests: Vec<Item>,
+    /// The routes of the mock router, if enabled
+    mock_routes: Vec<TokenStream>,
+    /// The prefix of the axum paths, from the base path of the server
+    path_prefix: String,
+    /// The compiled operations, for the `OperationId` enum
+    operations: Vec<OperationVariant>,
+    /// The compiled operations with a path, for the `Api` trait and the `build_router` macro
+    api_operations: Vec<ApiOperation>,
+    /// The modules that are being compiled, from the root
+    module_path: Vec<Ident>,
+    /// The query structs that are shared by operations with the same query parameters
+    queries: Vec<SharedQuery>,
+    /// The types used in requests and responses, for `derives = usage;`
+    usage: Usage,
+    /// What to do with unsupported features, for `unsupported = { composition: warn };`
+    policies: Policies,
+    /// The error responses of the compiled operations, for `errors = errors;`
+    errors: Vec<ErrorResponse>,
+    /// The json bodies of the `#[borrowed]` operations, with their structs
+    borrowed: Vec<(Ident, syn::Type)>,
 }
 
 impl Compiler {
-    pub fn compile(parser: parsing::Root, spec: Spec) -> syn::Result<codegen::Root> {
-        let mut this = Self { spec };
+    pub fn compile(
+        parser: parsing::Root,
+        spec: Spec,
+        raw: Value,
+        source: &str,
+    ) -> syn::Result<codegen::Root> {
+        let mut this = Self {
+            spec,
+            raw,
+            options: parser.options,
+            contract_tests: Vec::new(),
+            mock_routes: Vec::new(),
+            path_prefix: String::new(),
+            operations: Vec::new(),
+            api_operations: Vec::new(),
+            module_path: Vec::new(),
+            queries: Vec::new(),
+            usage: Usage::default(),
+            policies: Policies::default(),
+            errors: Vec::new(),
+            borrowed: Vec::new(),
+        };
 
         // Compile the schemas
         let mut items = Vec::new();
-        items.push(codegen::Item::Module(this.compile_schemas_from_spec()?));
+        // With `components = referenced;`, only the schemas used by the macro are generated
+        let reachable = match &this.options.components {
+            None => None,
+            Some(components) => match components.to_string().as_str() {
+                "all" => None,
+                "referenced" => Some(reachable_schemas(&parser.items, &this.raw)),
+                _ => {
+                    return Err(err!(
+                        components,
+                        "Unknown components mode, expected `all` or `referenced`"
+                    ))
+                }
+            },
+        };
+        if let Some(rule) = &this.options.rename_all {
+            const RULES: [&str; 8] = [
+                "lowercase",
+                "UPPERCASE",
+                "PascalCase",
+                "camelCase",
+                "snake_case",
+                "SCREAMING_SNAKE_CASE",
+                "kebab-case",
+                "SCREAMING-KEBAB-CASE",
+            ];
+            if !RULES.contains(&rule.value().as_str()) {
+                return Err(err!(
+                    rule,
+                    "Unknown rename rule, expected one of {}",
+                    RULES.join(", ")
+                ));
+            }
+        }
+        this.policies = Policies::from_options(&this.options.unsupported)?;
+        // With `schemas_from`, the schemas are generated by another invocation
+        if let Some(schemas_from) = &this.options.schemas_from {
+            if this.options.schemas.is_some() || this.options.models_to.is_some() {
+                return Err(err!(
+                    schemas_from,
+                    "`schemas_from` can not be combined with `schemas as` or `models_to`, which \
+                    apply to the invocation that generates the schemas"
+                ));
+            }
+        } else {
+            items.push(codegen::Item::Module(
+                this.compile_schemas_from_spec(reachable.as_ref())?,
+            ));
+        }
+        // With `schemas_from`, the security schemes are those of the other invocation too, which
+        // generates them next to its schemas
+        let security = this.compile_security_schemes_from_spec()?;
+        match (&this.options.schemas_from, security) {
+            (Some(schemas_from), Some(_)) => {
+                let mut security = schemas_from.clone();
+                if let Some(last) = security.segments.last_mut() {
+                    last.ident = Ident::new("security", Span::call_site());
+                }
+                items.push(codegen::Item::Schema(parse_quote!(pub use #security;)));
+            }
+            (None, Some(security)) => items.push(codegen::Item::Module(security)),
+            (_, None) => {}
+        }
+        let mut embed_items = Vec::new();
+        let serve_spec = this.options.serve_spec.as_ref();
+        compile_embedded_spec(source, &this.raw, serve_spec, &mut embed_items);
+        items.extend(embed_items.into_iter().map(codegen::Item::Schema));
+        let mut server_items = Vec::new();
+        this.path_prefix = compile_servers(
+            &this.raw,
+            this.options.base_path.as_ref(),
+            &mut server_items,
+        )?;
+        items.extend(server_items.into_iter().map(codegen::Item::Schema));
+        if let Some(name) = &this.options.method_not_allowed {
+            let mut allow_items = Vec::new();
+            compile_method_not_allowed(name, &this.raw, &this.path_prefix, &mut allow_items);
+            items.extend(allow_items.into_iter().map(codegen::Item::Schema));
+        }
+        if let Some(cors_layer) = &this.options.cors_layer {
+            let mut cors_items = Vec::new();
+            compile_cors_layer(cors_layer, &this.raw, &mut cors_items)?;
+            items.extend(cors_items.into_iter().map(codegen::Item::Schema));
+        }
+        if let Some(client) = &this.options.client {
+            let mut client_items = Vec::new();
+            compile_client(client, &mut client_items);
+            items.extend(client_items.into_iter().map(codegen::Item::Schema));
+        }
 
         // And then the other items
+        let depth = this.depth(0);
         for item in parser.items {
             let mut schemas = Vec::new();
-            items.push(this.compile_item(item, &mut schemas, 0)?);
+            items.push(this.compile_item(item, &mut schemas, &depth)?);
             for schema in schemas {
                 items.push(codegen::Item::Schema(schema));
             }
         }
 
+        if !this.queries.is_empty() {
+            let mut query_items = Vec::new();
+            compile_shared_queries(&this.queries, &mut query_items);
+            items.extend(query_items.into_iter().map(codegen::Item::Schema));
+        }
+
+        if !this.operations.is_empty() {
+            let mut operation_items = Vec::new();
+            compile_operation_id(&this.operations, &mut operation_items);
+            items.extend(operation_items.into_iter().map(codegen::Item::Schema));
+        }
+
+        if let Some(api) = &this.options.api_trait {
+            let mut api_items = Vec::new();
+            compile_api_trait(api, &this.api_operations, &mut api_items);
+            items.extend(api_items.into_iter().map(codegen::Item::Schema));
+        }
+
+        if let Some(build_router) = &this.options.build_router {
+            let mut build_items = Vec::new();
+            compile_build_router(build_router, &this.api_operations, &mut build_items);
+            items.extend(build_items.into_iter().map(codegen::Item::Schema));
+        }
+
+        if let Some(validated_router) = &this.options.validated_router {
+            let paths = this.raw["paths"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(path, _)| format!("{}{path}", this.path_prefix))
+                .collect::<Vec<_>>();
+            let mut validated_items = Vec::new();
+            compile_validated_router(validated_router, &paths, &mut validated_items);
+            items.extend(validated_items.into_iter().map(codegen::Item::Schema));
+        }
+
+        if let Some(errors) = &this.options.errors {
+            let depth = this.depth(1);
+            items.push(codegen::Item::Module(compile_errors(
+                errors,
+                &this.errors,
+                &depth,
+            )?));
+        }
+
+        if !this.contract_tests.is_empty() {
+            let contract_tests = &this.contract_tests;
+            items.push(codegen::Item::Schema(parse_quote! {
+                /// Generated from the examples in the OpenAPI spec
+                #[cfg(test)]
+                mod contract_tests {
+                    #(#contract_tests)*
+                }
+            }));
+        }
+
+        if let Some(mock_router) = &this.options.mock_router {
+            let mock_routes = &this.mock_routes;
+            items.push(codegen::Item::Schema(parse_quote! {
+                /// A router that responds to every operation with the examples in the OpenAPI spec
+                pub fn #mock_router() -> ::axum::Router {
+                    ::axum::Router::new()
+                        #(#mock_routes)*
+                }
+            }));
+        }
+
+        remove_conflicting_conversions(&mut items);
+        box_large_variants(&mut items);
+
+        // With `derives = usage;`, the types only derive the serde traits they need. The client
+        // sends and receives every type, so it needs both.
+        if let Some(derives) = &this.options.derives {
+            match derives.to_string().as_str() {
+                "all" => {}
+                "usage" if this.options.client.is_some() => {}
+                "usage" => gate_derives(&mut items, &this.usage),
+                _ => {
+                    return Err(err!(
+                        derives,
+                        "Unknown derives mode, expected `all` or `usage`"
+                    ))
+                }
+            }
+        }
+
+        if this.options.strum {
+            add_strum_derives(&mut items);
+        }
+        if this.options.ord {
+            add_ord_derives(&mut items);
+        }
+        if this.options.non_exhaustive {
+            add_non_exhaustive(&mut items);
+        }
+        add_borrowed_types(&mut items, &this.borrowed)?;
+
+        // With `models_to`, the schemas are also written to a file, after every pass
+        if let Some(models_to) = &this.options.models_to {
+            let schemas_name = this.schemas_name();
+            let schemas = items.iter().find_map(|item| match item {
+                codegen::Item::Module(module) if module.name == schemas_name => Some(module),
+                _ => None,
+            });
+            if let Some(schemas) = schemas {
+                write_models(models_to, schemas)?;
+            }
+        }
+
         Ok(codegen::Root { items })
     }
 
-    fn compile_schemas_from_spec(&mut self) -> syn::Result<codegen::ModuleItem> {
-        let mut items = Vec::new();
-        // panic!("{:#?}", self.spec.components.as_ref().unwrap().schemas.clone());
-        for (name, schema) in self.spec.components.as_ref().unwrap().schemas.clone() {
+    /// Compiles the schemas of `components/schemas`, or only the reachable ones if given.
+    fn compile_schemas_from_spec(
+        &mut self,
+        reachable: Option<&BTreeSet<String>>,
+    ) -> syn::Result<codegen::ModuleItem> {
+        // The items of every module, by their path within `schemas`
+        let mut modules: BTreeMap<Vec<String>, Vec<Item>> = BTreeMap::new();
+        let mut reexports = Vec::new();
+        for (name, raw) in self.raw["components"]["schemas"]
+            .as_object()
+            .into_iter()
+            .flatten()
+        {
+            if reachable.is_some_and(|reachable| !reachable.contains(name)) {
+                continue;
+            }
+
+            // Schemas can be placed in a submodule with `x-rust-module: billing::invoices`
+            let module_path = raw["x-rust-module"]
+                .as_str()
+                .map(|path| path.split("::").map(str::to_owned).collect::<Vec<_>>())
+                .unwrap_or_default();
+            let items = modules.entry(module_path.clone()).or_default();
+
             // The depth does not matter, because we discard the type anyway
-            let _ = compile_schema(schema, Some(&name), 1, &mut items)?;
+            let depth = self.depth(1 + module_path.len());
+            let _ = compile_schema(raw, Some(name), &depth, items)?;
+
+            // Extensions can only be implemented on generated structs and enums, not on aliases
+            let ident = Ident::new(name, Span::call_site());
+            let generated = items.iter().any(|item| match item {
+                Item::Struct(item) => item.ident == ident,
+                Item::Enum(item) => item.ident == ident,
+                _ => false,
+            });
+            if generated {
+                compile_extensions(&ident, raw, items);
+            }
+
+            // References are resolved from `schemas`, so the schema is re-exported there
+            if !module_path.is_empty() {
+                let module_path = module_path
+                    .iter()
+                    .map(|name| Ident::new(name, Span::call_site()));
+                reexports.push(parse_quote!(pub use self::#(#module_path::)*#ident;));
+            }
         }
 
+        let mut items = nest_modules(modules);
+        items.extend(reexports);
+        let (vis, name) = match &self.options.schemas {
+            Some((vis, name)) => (vis.clone(), name.clone()),
+            None => (parse_quote!(pub), self.schemas_name()),
+        };
         Ok(codegen::ModuleItem {
-            vis: parse_quote!(pub),
-            name: Ident::new("schemas", Span::call_site()),
+            vis,
+            name,
             items: items.into_iter().map(codegen::Item::Schema).collect(),
         })
     }
 
+    /// The name of the generated `schemas` module, which can be changed with
+    /// `schemas as pub(crate) models;`.
+    fn schemas_name(&self) -> Ident {
+        match &self.options.schemas {
+            Some((_, name)) => name.clone(),
+            None => Ident::new("schemas", Span::call_site()),
+        }
+    }
+
+    /// The depth of the items at the levels of modules below the root.
+    fn depth(&self, levels: usize) -> Depth {
+        let schemas = match &self.options.schemas_from {
+            Some(path) => path.clone(),
+            None => self.schemas_name().into(),
+        };
+        Depth::new(
+            schemas,
+            levels,
+            self.options.rename_all.clone(),
+            self.policies,
+        )
+    }
+
+    /// Compiles the `securitySchemes` into a `security` module, if there are any.
+    fn compile_security_schemes_from_spec(&mut self) -> syn::Result<Option<codegen::ModuleItem>> {
+        let Some(components) = self.spec.components.as_ref() else {
+            return Ok(None);
+        };
+        if components.security_schemes.is_empty() {
+            return Ok(None);
+        }
+
+        let mut items = Vec::new();
+        for (name, scheme) in components.security_schemes.clone() {
+            let scheme = scheme
+                .resolve(&self.spec)
+                .map_err(|e| err_call_site!("Could not resolve security scheme {name}: {e}"))?;
+            let raw = &self.raw["components"]["securitySchemes"][&name];
+            let depth = self.depth(1);
+            compile_security_scheme(&name, scheme, raw, &depth, &mut items)?;
+        }
+
+        Ok(Some(codegen::ModuleItem {
+            vis: parse_quote!(pub),
+            name: Ident::new("security", Span::call_site()),
+            items: items.into_iter().map(codegen::Item::Schema).collect(),
+        }))
+    }
+
     fn compile_item(
         &mut self,
         item: parsing::Item,
         schemas: &mut Vec<Item>,
-        depth: usize,
+        depth: &Depth,
     ) -> syn::Result<codegen::Item> {
-        match item {
-            parsing::Item::Method(method) => Ok(codegen::Item::Method(
-                self.compile_method(method, depth, schemas)?,
-            )),
+        let method = match item {
+            parsing::Item::Method(method) => {
+                let method = self.compile_method(method, depth, schemas)?;
+                self.api_operations
+                    .push(ApiOperation::of(&method, &self.module_path));
+                method
+            }
             parsing::Item::Module(module) => {
-                Ok(codegen::Item::Module(self.compile_module(module, depth)?))
+                return Ok(codegen::Item::Module(self.compile_module(module, depth)?))
             }
-        }
+            parsing::Item::Webhook(webhook) => self.compile_webhook(webhook, depth, schemas)?,
+        };
+        self.operations.push(OperationVariant::of(&method));
+        Ok(codegen::Item::Method(method))
     }
 
     fn compile_module(
         &mut self,
         module: parsing::ModuleItem,
-        depth: usize,
+        depth: &Depth,
     ) -> syn::Result<codegen::ModuleItem> {
         let mut items = Vec::new();
         let mut schemas = Vec::new();
 
+        self.module_path.push(module.name.clone());
         for item in module.items {
-            items.push(self.compile_item(item, &mut schemas, depth + 1)?);
+            items.push(self.compile_item(item, &mut schemas, &depth.nested())?);
         }
+        self.module_path.pop();
 
         for schema in schemas {
             items.push(codegen::Item::Schema(schema));
@@ -86,7 +491,7 @@ impl Compiler {
     fn compile_method(
         &mut self,
         method: parsing::MethodItem,
-        depth: usize,
+        depth: &Depth,
         schemas: &mut Vec<Item>,
     ) -> syn::Result<codegen::MethodItem> {
         let path_item = self
@@ -105,44 +510,219 @@ impl Compiler {
             parsing::MethodType::Trace(_) => path_item.trace.as_ref(),
             parsing::MethodType::Options(_) => path_item.options.as_ref(),
         }
-        .ok_or_else(|| err!(&method.path, "Method not found in OpenAPI spec"))?;
+        .ok_or_else(|| err!(&method.path, "Method not found in OpenAPI spec"))?
+        .clone();
+
+        // Generate the contract test from the examples
+        let raw_path_item = &self.raw["paths"][method.path.to_oapi_path()];
+        let raw_operation = with_path_item_parameters(
+            &self.raw,
+            raw_path_item,
+            &raw_path_item[method.method_ty.to_string().to_lowercase()],
+        );
+        if let Some(router) = &self.options.contract_tests {
+            let test = compile_contract_test(
+                router,
+                &method,
+                &raw_operation,
+                &self.raw,
+                &self.path_prefix,
+            )?;
+            self.contract_tests.extend(test);
+        }
+        if self.options.mock_router.is_some() {
+            let route = compile_mock_route(&method, &raw_operation, &self.raw, &self.path_prefix);
+            self.mock_routes.push(route);
+        }
+
+        let mut item =
+            self.compile_operation(method, &operation, &raw_operation, false, depth, schemas)?;
+        item.axum_path = format!("{}{}", self.path_prefix, item.axum_path);
+        Ok(item)
+    }
+
+    /// Compiles a webhook. Webhooks have no path, so their operation is looked up by name in the
+    /// `webhooks` of the spec.
+    fn compile_webhook(
+        &mut self,
+        webhook: parsing::WebhookItem,
+        depth: &Depth,
+        schemas: &mut Vec<Item>,
+    ) -> syn::Result<codegen::MethodItem> {
+        let name = webhook.name.to_string();
+        let raw_path_item = &self.raw["webhooks"][&name];
+        let raw_operation = with_path_item_parameters(
+            &self.raw,
+            raw_path_item,
+            &raw_path_item[webhook.method_ty.to_string().to_lowercase()],
+        );
+        if raw_operation.is_null() {
+            return Err(err!(&webhook.name, "Webhook not found in OpenAPI spec"));
+        }
+        let operation: Operation = serde_json::from_value(raw_operation.clone())
+            .map_err(|e| err!(&webhook.name, "Invalid webhook in OpenAPI spec: {e}"))?;
+
+        let method = parsing::MethodItem {
+            method_ty: webhook.method_ty,
+            path: parsing::MethodPath(Vec::new()),
+            struct_vis: webhook.struct_vis,
+            struct_name: webhook.struct_name,
+            raw_path: false,
+            borrowed: false,
+            stream: false,
+            docs: webhook.docs,
+        };
+        let mut item =
+            self.compile_operation(method, &operation, &raw_operation, true, depth, schemas)?;
+        item.oapi_path = name;
+        Ok(item)
+    }
+
+    /// The checks of the path parameters, with the status of `invalid_path_params`.
+    fn path_checks(&self) -> syn::Result<Option<codegen::PathChecks>> {
+        let Some(status) = &self.options.invalid_path_params else {
+            return Ok(None);
+        };
+        let status = match status.to_string().as_str() {
+            "not_found" => format_ident!("NOT_FOUND"),
+            "unprocessable_entity" => format_ident!("UNPROCESSABLE_ENTITY"),
+            _ => {
+                return Err(err!(
+                    status,
+                    "Unknown status of invalid path parameters, expected `not_found` or \
+                    `unprocessable_entity`"
+                ))
+            }
+        };
+        Ok(Some(codegen::PathChecks {
+            status,
+            patterns: Vec::new(),
+        }))
+    }
+
+    /// Compiles the operation into the struct of the method.
+    fn compile_operation(
+        &mut self,
+        method: parsing::MethodItem,
+        operation: &Operation,
+        raw_operation: &Value,
+        webhook: bool,
+        depth: &Depth,
+        schemas: &mut Vec<Item>,
+    ) -> syn::Result<codegen::MethodItem> {
+        // The parameters of the operation, resolved
+        let params = raw_operation["parameters"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|param| resolve(&self.raw, param))
+            .collect::<Vec<_>>();
 
         // Get the path parameters
         let mut path_param_types = Vec::new();
+        let mut path_param_styles = Vec::new();
+        let mut path_checks = self.path_checks()?;
         for param_ident in method.path.path_param_idents() {
             // 1. Find it in the spec
-            let path_param = operation
-                .parameters
+            let path_param = params
                 .iter()
-                .map(|p| p.resolve(&self.spec).unwrap())
-                .find(|p| param_ident == p.name)
+                .find(|param| param["name"] == param_ident.to_string())
                 .ok_or_else(|| {
                     err!(param_ident, "Path parameter {param_ident} not found in OpenAPI spec")
                 })?;
             // 2. Check that it's a path parameter
-            if path_param.location != "path" {
+            if path_param["in"] != "path" {
                 return Err(err!(
                     param_ident,
                     "Path parameter {param_ident} is not in: `path` in OpenAPI spec"
                 ));
             }
-            // 3. Add it to the schema map
-            path_param_types.push(compile_param(path_param, depth, schemas)?);
+            // 3. Add it to the schema map. Arrays are separated by commas, like `1,2,3`.
+            let schema = resolve(&self.raw, &path_param["schema"]);
+            let ty = compile_param(path_param, depth, schemas)?;
+            path_param_types.push(match schema["type"] == "array" {
+                true => parse_quote!(::axum_open_api::extract::ArrayParam<#ty>),
+                false => ty,
+            });
+            path_param_styles.push(path_style(path_param));
+            // 4. Check its `pattern`, if it is a plain string
+            let plain = schema["type"] == "string"
+                && ["enum", "const", "not", "if"]
+                    .iter()
+                    .all(|key| schema.get(key).is_none());
+            if let (Some(checks), Some(pattern), true) =
+                (&mut path_checks, schema["pattern"].as_str(), plain)
+            {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    return Err(err!(
+                        param_ident,
+                        "Pattern of path parameter {param_ident} is not supported: {e}"
+                    ));
+                }
+                checks
+                    .patterns
+                    .push((param_ident.clone(), pattern.to_owned()));
+            }
         }
 
         // Get the query parameters
+        let query_params = params
+            .iter()
+            .copied()
+            .filter(|param| param["in"] == "query")
+            .collect::<Vec<_>>();
         let mut query_param_names = Vec::new();
         let mut query_param_types = Vec::new();
-        for query_param in operation
-            .parameters
-            .iter()
-            .map(|p| p.resolve(&self.spec).unwrap())
-            .filter(|p| p.location == "query")
-        {
-            query_param_names.push(Ident::new(&query_param.name, Span::call_site()));
+        let mut query_options = codegen::QueryOptions::default();
+        for query_param in &query_params {
+            let name = query_param["name"].as_str().unwrap_or_default();
+            query_param_names.push(Ident::new(name, Span::call_site()));
             query_param_types.push(compile_param(query_param, depth, schemas)?);
+
+            // With `allowEmptyValue`, an empty value is a valid empty string, a `true` flag, or
+            // else the absence of a value
+            if query_param["allowEmptyValue"] == true {
+                match resolve(&self.raw, &query_param["schema"])["type"].as_str() {
+                    Some("string") => {}
+                    Some("boolean") => query_options.true_if_empty.push(name.to_owned()),
+                    _ => query_options.absent_if_empty.push(name.to_owned()),
+                }
+            }
+            if query_param["allowReserved"] == true {
+                query_options.reserved.push(name.to_owned());
+            }
         }
 
+        // Query parameters without inline schemas share a query struct at the root, so they are
+        // compiled again relative to the root
+        let mut query_path = None;
+        if !query_params.is_empty() {
+            let root_depth = self.depth(0);
+            let mut inline_items = Vec::new();
+            let root_types = query_params
+                .into_iter()
+                .map(|query_param| compile_param(query_param, &root_depth, &mut inline_items))
+                .collect::<syn::Result<Vec<_>>>()?;
+            if inline_items.is_empty() {
+                let ident = shared_query(
+                    &mut self.queries,
+                    &method.struct_name,
+                    &query_param_names,
+                    &root_types,
+                );
+                let prefix = depth.prefix();
+                query_path = Some(parse_quote!(#prefix #ident));
+            }
+        }
+
+        // Get the security checks. The operation's `security` overrides the global one, even if
+        // it is empty. The raw spec is used, since `oas3` treats a missing `security` as empty.
+        let requirements = match raw_operation.get("security") {
+            Some(_) => &operation.security,
+            None => &self.spec.security,
+        };
+        let security = compile_security_requirements(requirements, &self.spec, depth)?;
+
         // Get the body-extractor if it exists
         let extractor = if let Some(req_body) =
             operation.request_body.as_ref().map(|b| b.resolve(&self.spec).unwrap())
@@ -151,30 +731,75 @@ impl Compiler {
                 return Err(err_call_site!("Exactly one media type is supported: \n{req_body:#?}"));
             }
             let (media_type_name, media_type) = req_body.content.first_key_value().unwrap();
-            let media_schema = media_type.schema.clone().ok_or_else(|| {
+            let raw_body = resolve(&self.raw, &raw_operation["requestBody"]);
+            let media_schema = raw_body["content"][media_type_name].get("schema");
+            let media_schema = media_schema.ok_or_else(|| {
                 err_call_site!("Schema not found in media type: \n{media_type:#?}")
             })?;
+            // An untitled body is named after the operationId, like `CreatePostBody` for
+            // `createPost`, or else after the struct of the operation
+            let body_name = match &operation.operation_id {
+                Some(operation_id) => variant_ident(operation_id),
+                None => method.struct_name.clone(),
+            };
+            let body_depth = depth.named(Some(format!("{body_name}Body")));
             Some(
                 match (
                     media_type_name.split('/').next().unwrap(),
                     media_type_name.split('/').last().unwrap(),
                 ) {
                     ("application", "json") => {
-                        let body_ty = compile_schema(media_schema, None, depth, schemas)?;
+                        let body_ty = compile_schema(media_schema, None, &body_depth, schemas)?;
+                        // With `#[borrowed]`, the body schema gets a borrowed form later on
+                        if method.borrowed {
+                            self.borrowed
+                                .push((method.struct_name.clone(), body_ty.clone()));
+                        }
+                        // With `#[stream]`, the items are parsed as the body is received
+                        if method.stream && resolve(&self.raw, media_schema)["type"] != "array" {
+                            return Err(err!(
+                                method.struct_name,
+                                "`#[stream]` requires a json body with an array schema"
+                            ));
+                        }
+                        let wrapper = match (method.borrowed, method.stream) {
+                            (true, _) => Some(parse_quote! {
+                                ::axum_open_api::borrowed::BorrowedJson<#body_ty>
+                            }),
+                            (_, true) => Some(parse_quote! {
+                                ::axum_open_api::stream::JsonArray<
+                                    <#body_ty as ::axum_open_api::stream::ArrayBody>::Item,
+                                >
+                            }),
+                            _ => None,
+                        };
                         codegen::Extractor {
                             body_ident: parse_quote!(body),
-                            extractor_ty: parse_quote!(::axum::extract::Json),
+                            extractor_ty: parse_quote!(::axum_open_api::json::Json),
                             rejection_var: parse_quote!(Json),
                             body_ty,
+                            wrapper,
                         }
                     }
+                    _ if method.borrowed || method.stream => {
+                        let attr = if method.borrowed {
+                            "borrowed"
+                        } else {
+                            "stream"
+                        };
+                        return Err(err!(
+                            method.struct_name,
+                            "`#[{attr}]` requires an `application/json` request body"
+                        ));
+                    }
                     ("application", "x-www-form-urlencoded") => {
-                        let body_ty = compile_schema(media_schema, None, depth, schemas)?;
+                        let body_ty = compile_schema(media_schema, None, &body_depth, schemas)?;
                         codegen::Extractor {
                             body_ident: parse_quote!(body),
                             extractor_ty: parse_quote!(::axum::extract::Form),
                             rejection_var: parse_quote!(Form),
                             body_ty,
+                            wrapper: None,
                         }
                     }
                     ("multipart", "form-data") => codegen::Extractor {
@@ -182,42 +807,244 @@ impl Compiler {
                         extractor_ty: parse_quote!(::axum::extract::Multipart),
                         rejection_var: parse_quote!(Multipart),
                         body_ty: parse_quote!(::axum::extract::Multipart),
+                        wrapper: None,
                     },
                     ("text", _) => codegen::Extractor {
                         body_ident: parse_quote!(body),
                         extractor_ty: parse_quote!(::axum::extract::Text),
                         rejection_var: parse_quote!(Text),
                         body_ty: parse_quote!(::axum::extract::Text),
+                        wrapper: None,
                     },
                     _ => codegen::Extractor {
                         body_ident: parse_quote!(body),
                         extractor_ty: parse_quote!(::axum::extract::Bytes),
                         rejection_var: parse_quote!(Bytes),
                         body_ty: parse_quote!(::axum::extract::Bytes),
+                        wrapper: None,
                     },
                 },
             )
+        } else if method.borrowed || method.stream {
+            let attr = if method.borrowed {
+                "borrowed"
+            } else {
+                "stream"
+            };
+            return Err(err!(
+                method.struct_name,
+                "`#[{attr}]` requires a request body"
+            ));
         } else {
             None
         };
 
+        // Get the responses, and the types a handler may return
+        let responses = compile_responses(
+            &method.struct_name,
+            raw_operation,
+            &self.raw,
+            depth,
+            schemas,
+        )?;
+        compile_response_bounds(&method.struct_name, &responses, schemas);
+        if !webhook {
+            compile_response_wrappers(&method.struct_name, &responses, schemas);
+        }
+        let response_enum = self.options.client.is_some() || self.options.api_trait.is_some();
+        if response_enum {
+            compile_response_enum(&method.struct_name, &responses, schemas);
+        }
+
+        // The error responses of the operations are collected for `errors = errors;`
+        if let (Some(_), false) = (&self.options.errors, webhook) {
+            let name = match &operation.operation_id {
+                Some(operation_id) => operation_id.clone(),
+                None => format!("{} {}", method.method_ty, method.path.to_oapi_path()),
+            };
+            self.errors
+                .extend(error_responses(&name, raw_operation, &self.raw));
+        }
+
+        // With `scaffold_to`, operations without a handler file get a stub
+        if let (Some(dir), false) = (&self.options.scaffold_to, webhook) {
+            let module_path = &self.module_path;
+            scaffold_handler(
+                dir,
+                &method,
+                module_path,
+                raw_operation,
+                &responses,
+                response_enum,
+            )?;
+        }
+
+        // Requests are deserialized and responses serialized, except for webhooks, which are sent
+        // by the API. The query parameters are also serialized by `uri()`.
+        self.usage.requests.extend(path_param_types.iter().cloned());
+        self.usage
+            .requests
+            .extend(query_param_types.iter().cloned());
+        self.usage
+            .responses
+            .extend(query_param_types.iter().cloned());
+        let body_ty = extractor
+            .as_ref()
+            .map(|extractor| extractor.body_ty.clone());
+        let response_tys = responses
+            .iter()
+            .filter_map(|response| match &response.body {
+                ResponseBody::Json(ty) => Some((**ty).clone()),
+                _ => None,
+            });
+        if webhook {
+            self.usage.responses.extend(body_ty.clone());
+            self.usage.requests.extend(response_tys.clone());
+        }
+        self.usage.requests.extend(body_ty);
+        self.usage.responses.extend(response_tys);
+
+        // Generate the url helpers of the response links
+        compile_links(raw_operation, &self.raw, depth, schemas)?;
+
+        // Generate the constants of the vendor extensions
+        compile_extensions(&method.struct_name, raw_operation, schemas);
+        compile_rate_limit(&method.struct_name, raw_operation, schemas)?;
+
+        // Generate the client method and the test request builder. Webhooks are sent by the API,
+        // so they have neither.
+        let client_params = ClientParams {
+            path_param_types: &path_param_types,
+            path_param_styles: &path_param_styles,
+            query_param_names: &query_param_names,
+            query_param_types: &query_param_types,
+            query_reserved: &query_options.reserved,
+            extractor: extractor.as_ref(),
+            responses: &responses,
+        };
+        if !webhook {
+            // With `derives = usage;`, request bodies can't be serialized without a client
+            let serialize_body = self.options.client.is_some()
+                || !matches!(&self.options.derives, Some(derives) if derives == "usage");
+            compile_test_request(&method, client_params, serialize_body, schemas);
+        }
+        if let (Some(client), false) = (&self.options.client, webhook) {
+            compile_client_method(client, &method, client_params, depth, schemas);
+            compile_callbacks(&method, raw_operation, &self.raw, depth, schemas)?;
+        }
+
+        // With `services = true;`, the operation is also a `tower::Service`, like `GetPostsService`
+        if self.options.services && !webhook {
+            let struct_name = &method.struct_name;
+            let struct_vis = &method.struct_vis;
+            let service = format_ident!("{struct_name}Service");
+            let doc = format!("[`{struct_name}`] as a standalone `tower::Service`.");
+            schemas.push(parse_quote! {
+                #[doc = #doc]
+                #struct_vis type #service =
+                    ::axum_open_api::service::OperationService<#struct_name>;
+            });
+        }
+
+        let header_param = |header: &str| {
+            params.iter().find(|param| {
+                param["in"] == "header"
+                    && param["name"]
+                        .as_str()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(header))
+            })
+        };
+
+        // An `Idempotency-Key` header is checked with the `IdempotencyStore` of the state
+        let idempotency = header_param("Idempotency-Key").map(|param| codegen::Idempotency {
+            operation: match &operation.operation_id {
+                Some(operation_id) => operation_id.clone(),
+                None => format!("{} {}", method.method_ty, method.path.to_oapi_path()),
+            },
+            required: param["required"] == true,
+        });
+
+        // Downloads with a `Range` header or a `206` response get the requested range, and are
+        // sent with the media type of the partial or successful response
+        let responses = &raw_operation["responses"];
+        let byte_range =
+            (header_param("Range").is_some() || responses.get("206").is_some()).then(|| {
+                let response = responses.get("206").unwrap_or(&responses["200"]);
+                let response = resolve(&self.raw, response);
+                let content_type = response["content"]
+                    .as_object()
+                    .and_then(|content| content.keys().next())
+                    .filter(|media_type| !media_type.contains('*'))
+                    .cloned();
+                codegen::ByteRange { content_type }
+            });
+
+        // With `typed_headers = true;`, standard headers like `User-Agent` are extracted as their
+        // `TypedHeader`
+        let typed_headers = match self.options.typed_headers {
+            true => compile_typed_headers(&params),
+            false => Vec::new(),
+        };
+
         Ok(codegen::MethodItem {
             method_ty: method.method_ty,
             axum_path: method.path.to_axum_path(),
             oapi_path: method.path.to_oapi_path(),
+            webhook,
             struct_name: method.struct_name,
             struct_vis: method.struct_vis,
+            docs: method.docs,
             path_param_names: method.path.path_param_idents().collect(),
             path_param_types,
+            path_param_styles,
+            raw_path: method.raw_path,
+            path_checks,
             query_param_names,
             query_param_types,
-            extractor,         // todo
+            operation_id: operation.operation_id.clone(),
+            tags: operation.tags.clone(),
+            extractor, // todo
+            security,
+            headers: self.options.headers,
+            idempotency,
+            if_match: header_param("If-Match").is_some(),
+            if_none_match: header_param("If-None-Match").is_some(),
+            byte_range,
+            typed_headers,
+            query_path,
+            query_options: (!query_options.is_empty()).then_some(query_options),
             summary: None,     // todo
             description: None, // todo
         })
     }
 }
 
+/// Nests the items of every module path into `pub mod` items, merging modules with the same
+/// parent.
+fn nest_modules(modules: BTreeMap<Vec<String>, Vec<Item>>) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut children: BTreeMap<String, BTreeMap<Vec<String>, Vec<Item>>> = BTreeMap::new();
+    for (path, module_items) in modules {
+        match path.split_first() {
+            None => items.extend(module_items),
+            Some((first, rest)) => {
+                let child = children.entry(first.clone()).or_default();
+                child.entry(rest.to_vec()).or_default().extend(module_items);
+            }
+        }
+    }
+    for (name, child) in children {
+        let name = Ident::new(&name, Span::call_site());
+        let child_items = nest_modules(child);
+        items.push(parse_quote! {
+            pub mod #name {
+                #(#child_items)*
+            }
+        });
+    }
+    items
+}
+
 impl parsing::MethodPath {
     pub fn to_axum_path(&self) -> String {
         let mut path = String::new();
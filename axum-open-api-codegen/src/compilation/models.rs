@@ -0,0 +1,91 @@
+use crate::codegen;
+use std::{env, fs, path::PathBuf};
+use syn::LitStr;
+
+/// Writes the generated `schemas` module to the file, like `../my-api-types/src/lib.rs`, so the
+/// models can be published in a crate of their own.
+///
+/// The file is only written if its contents changed, to not trigger rebuilds of the crate.
+pub fn write_models(path: &LitStr, schemas: &codegen::ModuleItem) -> syn::Result<()> {
+    let codegen::ModuleItem { name, items, .. } = schemas;
+    let file: syn::File = parse_quote! {
+        //! Generated by `validate_routes!` from the schemas of the OpenAPI spec. Do not edit.
+
+        pub mod #name {
+            #(#items)*
+        }
+    };
+    let contents = prettyplease::unparse(&file);
+
+    // Relative paths are relative to the crate, like the path of the spec
+    let mut file_path = env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    file_path.push(path.value());
+    if fs::read_to_string(&file_path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    let write = || {
+        fs::create_dir_all(file_path.parent().unwrap_or(&file_path))?;
+        fs::write(&file_path, contents)
+    };
+    write().map_err(|e| err!(path, "Could not write models {}: {e}", file_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::expand;
+    use std::{
+        env,
+        fs::{self, File},
+        time::{Duration, SystemTime},
+    };
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: Pets
+  version: 0.0.1
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        name:
+          type: string
+paths:
+  /pets:
+    get:
+      responses:
+        '204':
+          description: The pets
+"#;
+
+    #[test]
+    fn written_models() {
+        let path = env::temp_dir().join(format!("axum-open-api-models-{}.rs", std::process::id()));
+        let body = format!(
+            "models_to = {:?}; GET /pets as pub GetPets;",
+            path.to_str().unwrap()
+        );
+        expand(SPEC, &body);
+        let models = fs::read_to_string(&path).unwrap();
+        assert!(models.starts_with("//! Generated by `validate_routes!`"));
+        assert!(models.contains("pub mod schemas {"));
+        assert!(models.contains("pub struct Pet {"));
+        assert!(!models.contains("GetPets"));
+
+        // The file is not written again if the models did not change
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(60);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        expand(SPEC, &body);
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified);
+        fs::remove_file(&path).unwrap();
+    }
+}
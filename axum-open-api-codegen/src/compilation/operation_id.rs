@@ -0,0 +1,107 @@
+use crate::codegen;
+use proc_macro2::{Ident, Span};
+use syn::Item;
+
+/// An operation of the `OperationId` enum.
+pub struct OperationVariant {
+    pub variant: Ident,
+    /// The operationId, or `METHOD /path` if it has none
+    pub name: String,
+    pub method: Ident,
+    pub path_template: String,
+    pub operation_id: Option<String>,
+    pub tags: Vec<String>,
+    pub webhook: bool,
+}
+
+impl OperationVariant {
+    pub fn of(method: &codegen::MethodItem) -> Self {
+        let name = match &method.operation_id {
+            Some(operation_id) => operation_id.clone(),
+            None => format!("{} {}", method.method_ty, method.oapi_path),
+        };
+        Self {
+            variant: method.struct_name.clone(),
+            name,
+            method: Ident::new(&method.method_ty.to_string(), Span::call_site()),
+            path_template: method.oapi_path.clone(),
+            operation_id: method.operation_id.clone(),
+            tags: method.tags.clone(),
+            webhook: method.webhook,
+        }
+    }
+}
+
+/// Compiles the `OperationId` enum, with a variant for every generated struct.
+pub fn compile_operation_id(operations: &[OperationVariant], items: &mut Vec<Item>) {
+    let variants = operations
+        .iter()
+        .map(|operation| &operation.variant)
+        .collect::<Vec<_>>();
+    let names = operations.iter().map(|operation| &operation.name);
+    let methods = operations.iter().map(|operation| &operation.method);
+    let path_templates = operations.iter().map(|operation| &operation.path_template);
+
+    items.push(parse_quote! {
+        /// The operations of the OpenAPI spec, with a variant for every generated struct
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum OperationId {
+            #(#variants,)*
+        }
+    });
+    items.push(parse_quote! {
+        impl OperationId {
+            /// All operations, in order of declaration
+            pub const ALL: &'static [OperationId] = &[#(OperationId::#variants),*];
+
+            /// The operationId, or `METHOD /path` if the operation has none
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    #(OperationId::#variants => #names,)*
+                }
+            }
+
+            pub fn method(&self) -> ::axum::http::Method {
+                match self {
+                    #(OperationId::#variants => ::axum::http::Method::#methods,)*
+                }
+            }
+
+            /// The path as written in the spec, or the name of the webhook
+            pub fn path_template(&self) -> &'static str {
+                match self {
+                    #(OperationId::#variants => #path_templates,)*
+                }
+            }
+        }
+    });
+
+    // Webhooks are not in the `paths` of the spec, so they are not implemented operations
+    let implemented = operations
+        .iter()
+        .filter(|operation| !operation.webhook)
+        .map(|operation| {
+            let method = &operation.method;
+            let path_template = &operation.path_template;
+            let operation_id = match &operation.operation_id {
+                Some(operation_id) => quote!(Some(#operation_id)),
+                None => quote!(None),
+            };
+            let tags = &operation.tags;
+            quote! {
+                ::axum_open_api::MatchedOperation {
+                    method: ::axum::http::Method::#method,
+                    path_template: #path_template,
+                    operation_id: #operation_id,
+                    tags: &[#(#tags),*],
+                }
+            }
+        });
+    items.push(parse_quote! {
+        /// The declared operations with a path, for
+        /// `axum_open_api::testing::spec_coverage_report`
+        pub const IMPLEMENTED_OPERATIONS: &[::axum_open_api::MatchedOperation] = &[
+            #(#implemented),*
+        ];
+    });
+}
@@ -0,0 +1,87 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span};
+use quote::ToTokens;
+use syn::{Item, Type};
+
+/// A query struct that is shared by the operations with the same query parameters, like
+/// `pub struct PagePerPageQuery { pub page: Option<i64>, pub per_page: Option<i64> }`.
+pub struct SharedQuery {
+    pub ident: Ident,
+    pub names: Vec<Ident>,
+    /// The types of the parameters, relative to the root of the macro
+    pub types: Vec<Type>,
+    /// The structs of the operations that extract it
+    pub operations: Vec<Ident>,
+}
+
+impl SharedQuery {
+    fn matches(&self, names: &[Ident], types: &[Type]) -> bool {
+        let tokens = |types: &[Type]| {
+            types
+                .iter()
+                .map(|ty| ty.to_token_stream().to_string())
+                .collect::<Vec<_>>()
+        };
+        self.names == names && tokens(&self.types) == tokens(types)
+    }
+}
+
+/// Returns the shared query struct with these parameters, adding it if there is none yet. The
+/// struct is named after its parameters, with a number if another struct has the same name.
+pub fn shared_query<'a>(
+    queries: &'a mut Vec<SharedQuery>,
+    operation: &Ident,
+    names: &[Ident],
+    types: &[Type],
+) -> &'a Ident {
+    let index = match queries.iter().position(|query| query.matches(names, types)) {
+        Some(index) => index,
+        None => {
+            let base = names
+                .iter()
+                .map(|name| name.to_string().to_case(Case::Pascal));
+            let base = format!("{}Query", base.collect::<String>());
+            let mut ident = Ident::new(&base, Span::call_site());
+            let mut n = 1;
+            while queries.iter().any(|query| query.ident == ident) {
+                n += 1;
+                ident = Ident::new(&format!("{base}{n}"), Span::call_site());
+            }
+            queries.push(SharedQuery {
+                ident,
+                names: names.to_vec(),
+                types: types.to_vec(),
+                operations: Vec::new(),
+            });
+            queries.len() - 1
+        }
+    };
+    let query = &mut queries[index];
+    query.operations.push(operation.clone());
+    &query.ident
+}
+
+/// Compiles the shared query structs.
+pub fn compile_shared_queries(queries: &[SharedQuery], items: &mut Vec<Item>) {
+    for SharedQuery {
+        ident,
+        names,
+        types,
+        operations,
+    } in queries
+    {
+        let operations = operations
+            .iter()
+            .map(|operation| format!("`{operation}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let doc = format!("The query parameters of {operations}.");
+        items.push(parse_quote! {
+            #[doc = #doc]
+            #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+            pub struct #ident {
+                #(pub #names: #types,)*
+            }
+        });
+    }
+}
@@ -0,0 +1,97 @@
+//! Helpers for the raw spec, for everything that is not parsed by `oas3`.
+
+use serde_json::Value;
+
+/// The maximum amount of `$ref`s followed when resolving, to prevent cycles.
+const MAX_REF_DEPTH: usize = 32;
+
+/// Follows the local `$ref`s of the value.
+pub fn resolve<'a>(root: &'a Value, mut value: &'a Value) -> &'a Value {
+    for _ in 0..MAX_REF_DEPTH {
+        let Some(target) = value
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|reference| reference.strip_prefix('#'))
+            .and_then(|pointer| root.pointer(pointer))
+        else {
+            break;
+        };
+        value = target;
+    }
+    value
+}
+
+/// Returns the example of a parameter, media type or schema: either its `example`, the first
+/// of its `examples`, or the example of its `schema`.
+pub fn example(root: &Value, value: &Value) -> Option<Value> {
+    let value = resolve(root, value);
+    if let Some(example) = value.get("example") {
+        return Some(example.clone());
+    }
+    match value.get("examples") {
+        // The `examples` of parameters and media types are maps of example objects
+        Some(Value::Object(examples)) => {
+            let example = resolve(root, examples.values().next()?);
+            return example.get("value").cloned();
+        }
+        // The `examples` of 3.1 schemas are arrays of values
+        Some(Value::Array(examples)) => return examples.first().cloned(),
+        _ => {}
+    }
+    example(root, value.get("schema")?)
+}
+
+/// The operation with the `parameters` of its path item, which apply to every operation of the
+/// path, unless the operation overrides them with the same name and location.
+pub fn with_path_item_parameters(root: &Value, path_item: &Value, operation: &Value) -> Value {
+    let mut operation = operation.clone();
+    let (Some(inherited), Some(object)) = (
+        path_item["parameters"].as_array(),
+        operation.as_object_mut(),
+    ) else {
+        return operation;
+    };
+    let parameters = object.get("parameters").and_then(Value::as_array);
+    let mut parameters = parameters.cloned().unwrap_or_default();
+    for param in inherited {
+        let param = resolve(root, param);
+        let overridden = parameters
+            .iter()
+            .map(|other| resolve(root, other))
+            .any(|other| other["name"] == param["name"] && other["in"] == param["in"]);
+        if !overridden {
+            parameters.push(param.clone());
+        }
+    }
+    object.insert("parameters".to_owned(), Value::Array(parameters));
+    operation
+}
+
+/// Returns the example as it would appear in a path or query, with the items of arrays
+/// separated by commas.
+pub fn example_to_param(example: &Value) -> String {
+    match example {
+        Value::String(string) => string.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(example_to_param)
+            .collect::<Vec<_>>()
+            .join(","),
+        other => other.to_string(),
+    }
+}
+
+/// Percent-encodes a value for a uri, keeping the unreserved characters, and the `,` that
+/// separates the items of arrays.
+pub fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b',' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,72 @@
+use crate::parsing;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Collects the names of the schemas in `components/schemas` that are reachable from the
+/// declared operations and the security schemes, following every `$ref`.
+pub fn reachable_schemas(items: &[parsing::Item], root: &Value) -> BTreeSet<String> {
+    let mut values = Vec::new();
+    declared_operations(items, root, &mut values);
+    values.push(&root["components"]["securitySchemes"]);
+
+    let mut visited = BTreeSet::new();
+    let mut schemas = BTreeSet::new();
+    for value in values {
+        walk(value, root, &mut visited, &mut schemas);
+    }
+    schemas
+}
+
+/// The raw operations of the declared methods and webhooks, with the parameters of their path.
+fn declared_operations<'a>(items: &[parsing::Item], root: &'a Value, out: &mut Vec<&'a Value>) {
+    for item in items {
+        match item {
+            parsing::Item::Module(module) => declared_operations(&module.items, root, out),
+            parsing::Item::Method(method) => {
+                let path_item = &root["paths"][method.path.to_oapi_path()];
+                out.push(&path_item[method.method_ty.to_string().to_lowercase()]);
+                out.push(&path_item["parameters"]);
+            }
+            parsing::Item::Webhook(webhook) => {
+                let path_item = &root["webhooks"][webhook.name.to_string()];
+                out.push(&path_item[webhook.method_ty.to_string().to_lowercase()]);
+                out.push(&path_item["parameters"]);
+            }
+        }
+    }
+}
+
+fn walk(
+    value: &Value,
+    root: &Value,
+    visited: &mut BTreeSet<String>,
+    schemas: &mut BTreeSet<String>,
+) {
+    match value {
+        Value::Object(object) => {
+            if let Some(reference) = object.get("$ref").and_then(Value::as_str) {
+                if let Some(name) = reference.strip_prefix("#/components/schemas/") {
+                    schemas.insert(name.to_owned());
+                }
+                // The reference is a json pointer, like `#/components/schemas/User`
+                if visited.insert(reference.to_owned()) {
+                    let target = reference
+                        .strip_prefix('#')
+                        .and_then(|path| root.pointer(path));
+                    if let Some(target) = target {
+                        walk(target, root, visited, schemas);
+                    }
+                }
+            }
+            for value in object.values() {
+                walk(value, root, visited, schemas);
+            }
+        }
+        Value::Array(array) => {
+            for value in array {
+                walk(value, root, visited, schemas);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,311 @@
+use super::{
+    raw::resolve,
+    schema::{compile_schema, field_ident, Depth},
+};
+use convert_case::{Case, Casing};
+use proc_macro2::Ident;
+use serde_json::Value;
+use syn::{Item, Type};
+
+/// A documented response of an operation.
+pub struct Response {
+    /// The status as written in the spec, like `200`, `4XX` or `default`
+    pub status: String,
+    pub body: ResponseBody,
+    /// The media type of the body as written in the spec, like `text/csv; charset=utf-8`
+    pub media_type: Option<String>,
+}
+
+/// The body of a response, by the first media type of its `content`.
+pub enum ResponseBody {
+    Json(Box<Type>),
+    Text,
+    Binary,
+    Empty,
+    /// A redirect without a body, like `303`, whose variant carries the `Location`
+    Redirect,
+}
+
+/// Compiles the responses of the operation, adding the types of inline json schemas to the
+/// items, like `GetPostsResponse200`.
+pub fn compile_responses(
+    struct_name: &Ident,
+    raw_operation: &Value,
+    root: &Value,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<Vec<Response>> {
+    let mut responses = Vec::new();
+    for (status, response) in raw_operation["responses"].as_object().into_iter().flatten() {
+        let response = resolve(root, response);
+        let media_type = response["content"]
+            .as_object()
+            .and_then(|content| content.iter().next());
+        let body = match media_type {
+            Some((name, media_type)) if name == "application/json" => {
+                let schema = media_type.get("schema").ok_or_else(|| {
+                    err_call_site!("Schema not found in media type: \n{media_type:#}")
+                })?;
+                let title = format!("{struct_name}Response{}", status.to_case(Case::Pascal));
+                ResponseBody::Json(Box::new(compile_schema(
+                    schema,
+                    Some(&title),
+                    depth,
+                    items,
+                )?))
+            }
+            Some((name, _)) if name.starts_with("text/") => ResponseBody::Text,
+            Some(_) => ResponseBody::Binary,
+            // `304 Not Modified` is not a redirect
+            None if status.starts_with('3') && status != "304" => ResponseBody::Redirect,
+            None => ResponseBody::Empty,
+        };
+        responses.push(Response {
+            status: status.clone(),
+            body,
+            media_type: media_type.map(|(name, _)| name.clone()),
+        });
+    }
+    Ok(responses)
+}
+
+/// Compiles the implementations of `ResponseFor` for the types a handler of the operation may
+/// return without a body, like `impl ResponseFor<DeletePost> for NoContent`.
+///
+/// Responses with a body are returned as their wrapper or the response enum instead. The types
+/// of their bodies can't be used, as two schemas can be the same type, like two aliases of
+/// `Vec<schemas::Post>`, which can't both implement `ResponseFor`.
+pub fn compile_response_bounds(struct_name: &Ident, responses: &[Response], items: &mut Vec<Item>) {
+    let mut tys: Vec<Type> = Vec::new();
+    for response in responses {
+        let response_tys: Vec<Type> = match &response.body {
+            ResponseBody::Json(_) | ResponseBody::Text | ResponseBody::Binary => continue,
+            // `()` is `200 OK`, so it is only a response of operations that document it
+            ResponseBody::Empty => match response.status.as_str() {
+                "200" => vec![parse_quote!(()), parse_quote!(::axum::http::StatusCode)],
+                "204" => vec![
+                    parse_quote!(::axum_open_api::NoContent),
+                    parse_quote!(::axum::http::StatusCode),
+                ],
+                _ => vec![parse_quote!(::axum::http::StatusCode)],
+            },
+            ResponseBody::Redirect => vec![
+                parse_quote!(::axum::http::StatusCode),
+                parse_quote!(::axum::response::Redirect),
+            ],
+        };
+        // Multiple responses can have the same type, but it can only be implemented once
+        for ty in response_tys {
+            if !tys.contains(&ty) {
+                tys.push(ty);
+            }
+        }
+    }
+
+    for ty in tys {
+        items.push(parse_quote! {
+            impl ::axum_open_api::ResponseFor<#struct_name> for #ty {}
+        });
+    }
+}
+
+/// Compiles the enum of the responses with a documented status code, like
+/// `pub enum GetPostsResponse { Status200(schemas::Post), Other(StatusCode, Bytes) }`. It is
+/// received by the client, and returned by the handlers of the `Api` trait.
+///
+/// Text and binary responses are sent with the media type of the spec, and have a constructor
+/// named after it, like `GetReportResponse::csv(body)` for `text/csv`.
+pub fn compile_response_enum(struct_name: &Ident, responses: &[Response], items: &mut Vec<Item>) {
+    let response_ident = format_ident!("{struct_name}Response");
+    let mut variants = Vec::new();
+    let mut arms = Vec::new();
+    let mut constructors = Vec::new();
+    for response in responses {
+        let Ok(code) = response.status.parse::<u16>() else {
+            continue;
+        };
+        let variant = format_ident!("Status{code}");
+        let status = quote!(::axum::http::StatusCode::from_u16(#code).unwrap());
+        // Wildcards like `*/*` are not a content type
+        let media_type = response
+            .media_type
+            .as_ref()
+            .filter(|media_type| !media_type.contains('*'));
+        let content_type = media_type
+            .map(|media_type| quote!([(::axum::http::header::CONTENT_TYPE, #media_type)],));
+        let body_ty = match &response.body {
+            ResponseBody::Json(_) | ResponseBody::Empty | ResponseBody::Redirect => None,
+            ResponseBody::Text => Some(quote!(String)),
+            ResponseBody::Binary => Some(quote!(::axum::body::Bytes)),
+        };
+        if let (Some(body_ty), Some(media_type)) = (&body_ty, media_type) {
+            // Named after the subtype, and also after the status if the subtype is not unique
+            let subtype = media_subtype(media_type);
+            let unique = responses
+                .iter()
+                .filter_map(|response| response.media_type.as_deref())
+                .filter(|media_type| media_subtype(media_type) == subtype)
+                .count()
+                == 1;
+            let constructor = match unique {
+                true => field_ident(subtype),
+                false => field_ident(&format!("{subtype}_{code}")),
+            };
+            let doc = format!("A `{code}` response with `Content-Type: {media_type}`.");
+            constructors.push(quote! {
+                #[doc = #doc]
+                pub fn #constructor(body: impl Into<#body_ty>) -> Self {
+                    #response_ident::#variant(body.into())
+                }
+            });
+        }
+        match &response.body {
+            ResponseBody::Json(ty) => {
+                variants.push(quote!(#variant(#ty)));
+                arms.push(quote! {
+                    #response_ident::#variant(body) => (#status, ::axum::Json(body)).into_response()
+                });
+            }
+            ResponseBody::Text | ResponseBody::Binary => {
+                variants.push(quote!(#variant(#body_ty)));
+                arms.push(quote! {
+                    #response_ident::#variant(body) => {
+                        (#status, #content_type body).into_response()
+                    }
+                });
+            }
+            ResponseBody::Empty => {
+                variants.push(quote!(#variant));
+                arms.push(quote!(#response_ident::#variant => #status.into_response()));
+            }
+            ResponseBody::Redirect => {
+                variants.push(quote!(#variant(::axum_open_api::redirect::Location)));
+                arms.push(quote! {
+                    #response_ident::#variant(location) => (#status, location, ()).into_response()
+                });
+            }
+        }
+    }
+
+    let response_doc = format!("The responses of [`{struct_name}`] with a documented status code.");
+    items.push(parse_quote! {
+        #[doc = #response_doc]
+        #[derive(Debug)]
+        pub enum #response_ident {
+            #(#variants,)*
+            /// A response with an undocumented status code
+            Other(::axum::http::StatusCode, ::axum::body::Bytes),
+        }
+    });
+    if !constructors.is_empty() {
+        items.push(parse_quote! {
+            impl #response_ident {
+                #(#constructors)*
+            }
+        });
+    }
+    items.push(parse_quote! {
+        impl ::axum::response::IntoResponse for #response_ident {
+            fn into_response(self) -> ::axum::response::Response {
+                use ::axum::response::IntoResponse;
+                match self {
+                    #(#arms,)*
+                    #response_ident::Other(status, body) => (status, body).into_response(),
+                }
+            }
+        }
+    });
+    items.push(parse_quote! {
+        impl ::axum_open_api::ResponseFor<#struct_name> for #response_ident {}
+    });
+}
+
+/// Compiles a wrapper for every documented response with a body, pairing its type with its
+/// status and media type, like `pub struct CreatePostCreated(pub schemas::Post)` for a `201`.
+/// They are responses of the operation, for handlers that don't return the response enum.
+pub fn compile_response_wrappers(
+    struct_name: &Ident,
+    responses: &[Response],
+    items: &mut Vec<Item>,
+) {
+    for response in responses {
+        let Ok(code) = response.status.parse::<u16>() else {
+            continue;
+        };
+        let status = quote!(::axum::http::StatusCode::from_u16(#code).unwrap());
+        let content_type = response
+            .media_type
+            .as_ref()
+            .filter(|media_type| !media_type.contains('*'))
+            .map(|media_type| quote!([(::axum::http::header::CONTENT_TYPE, #media_type)],));
+        let (body_ty, into_response) = match &response.body {
+            ResponseBody::Json(ty) => (quote!(#ty), quote!((#status, ::axum::Json(self.0)))),
+            ResponseBody::Text => (quote!(String), quote!((#status, #content_type self.0))),
+            ResponseBody::Binary => (
+                quote!(::axum::body::Bytes),
+                quote!((#status, #content_type self.0)),
+            ),
+            ResponseBody::Empty | ResponseBody::Redirect => continue,
+        };
+
+        let wrapper = format_ident!("{struct_name}{}", status_name(code));
+        let doc = format!("The `{code}` response of [`{struct_name}`], with its media type.");
+        items.push(parse_quote! {
+            #[doc = #doc]
+            #[derive(Debug)]
+            pub struct #wrapper(pub #body_ty);
+        });
+        items.push(parse_quote! {
+            impl ::axum::response::IntoResponse for #wrapper {
+                fn into_response(self) -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
+                    #into_response.into_response()
+                }
+            }
+        });
+        items.push(parse_quote! {
+            impl ::axum_open_api::ResponseFor<#struct_name> for #wrapper {}
+        });
+    }
+}
+
+/// The name of a status code, like `Created` for `201`, or `Status299` for an unknown one.
+pub fn status_name(code: u16) -> String {
+    const NAMES: [(u16, &str); 25] = [
+        (200, "Ok"),
+        (201, "Created"),
+        (202, "Accepted"),
+        (203, "NonAuthoritativeInformation"),
+        (206, "PartialContent"),
+        (207, "MultiStatus"),
+        (400, "BadRequest"),
+        (401, "Unauthorized"),
+        (402, "PaymentRequired"),
+        (403, "Forbidden"),
+        (404, "NotFound"),
+        (405, "MethodNotAllowed"),
+        (406, "NotAcceptable"),
+        (409, "Conflict"),
+        (410, "Gone"),
+        (412, "PreconditionFailed"),
+        (413, "PayloadTooLarge"),
+        (415, "UnsupportedMediaType"),
+        (422, "UnprocessableEntity"),
+        (429, "TooManyRequests"),
+        (500, "InternalServerError"),
+        (501, "NotImplemented"),
+        (502, "BadGateway"),
+        (503, "ServiceUnavailable"),
+        (504, "GatewayTimeout"),
+    ];
+    match NAMES.iter().find(|(known, _)| *known == code) {
+        Some((_, name)) => (*name).to_owned(),
+        None => format!("Status{code}"),
+    }
+}
+
+/// The subtype of a media type, without its parameters, like `csv` for `text/csv; charset=utf-8`.
+fn media_subtype(media_type: &str) -> &str {
+    let essence = media_type.split(';').next().unwrap_or_default();
+    essence.rsplit('/').next().unwrap_or_default().trim()
+}
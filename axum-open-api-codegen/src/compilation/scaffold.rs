@@ -0,0 +1,155 @@
+use super::responses::{Response, ResponseBody};
+use crate::parsing;
+use convert_case::{Case, Casing};
+use proc_macro2::Ident;
+use serde_json::Value;
+use std::{env, fs, path::PathBuf};
+use syn::LitStr;
+
+/// Writes a stub of the handler of the operation to `{dir}/{handler}.rs`, like
+/// `src/handlers/get_user.rs`, unless the file exists already.
+///
+/// The stub returns the response enum if it is generated, or else the first successful response.
+pub fn scaffold_handler(
+    dir: &LitStr,
+    method: &parsing::MethodItem,
+    module_path: &[Ident],
+    raw_operation: &Value,
+    responses: &[Response],
+    response_enum: bool,
+) -> syn::Result<()> {
+    let struct_name = &method.struct_name;
+    let handler = struct_name.to_string().to_case(Case::Snake);
+
+    // Relative paths are relative to the crate, like the path of the spec
+    let mut path = env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    path.push(dir.value());
+    path.push(format!("{handler}.rs"));
+    if path.exists() {
+        return Ok(());
+    }
+
+    let output = if response_enum {
+        format!("{struct_name}Response")
+    } else {
+        let response = responses
+            .iter()
+            .find(|response| response.status.starts_with('2'))
+            .or(responses.first());
+        match response.map(|response| (response.status.as_str(), &response.body)) {
+            Some(("204", ResponseBody::Empty)) => "axum_open_api::NoContent".to_owned(),
+            Some((_, ResponseBody::Json(ty))) => format!("axum::Json<{}>", type_name(ty)),
+            Some((_, ResponseBody::Text)) => "String".to_owned(),
+            Some((_, ResponseBody::Binary)) => "Vec<u8>".to_owned(),
+            Some((_, ResponseBody::Empty | ResponseBody::Redirect)) | None => {
+                "axum::http::StatusCode".to_owned()
+            }
+        }
+    };
+    let struct_path = module_path
+        .iter()
+        .chain([struct_name])
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("::");
+    let summary = raw_operation["summary"]
+        .as_str()
+        .map(|summary| format!("\n//! {summary}"))
+        .unwrap_or_default();
+    let stub = format!(
+        "//! `{} {}`{summary}
+//!
+//! Generated by `validate_routes!`, because `scaffold_to` is set and this file did not exist.
+//! Declare it with `mod {handler};`, and import `{struct_path}` and the schemas from the module
+//! of the macro.
+
+pub async fn {handler}(req: {struct_name}) -> {output} {{
+    todo!()
+}}
+",
+        method.method_ty,
+        method.path.to_oapi_path()
+    );
+
+    let write = || {
+        fs::create_dir_all(path.parent().unwrap_or(&path))?;
+        fs::write(&path, stub)
+    };
+    write().map_err(|e| err!(dir, "Could not write handler stub {}: {e}", path.display()))
+}
+
+/// The type as it is written in a handler, without the `super::` of the generated modules.
+fn type_name(ty: &syn::Type) -> String {
+    quote!(#ty)
+        .to_string()
+        .replace(' ', "")
+        .replace("super::", "")
+        .replace("::axum::", "axum::")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::expand;
+    use std::{env, fs};
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: Pets
+  version: 0.0.1
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        name:
+          type: string
+paths:
+  /pets/{pet_id}:
+    parameters:
+      - in: path
+        name: pet_id
+        required: true
+        schema:
+          type: integer
+    get:
+      summary: Gets a pet
+      responses:
+        '200':
+          description: The pet
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pet'
+    delete:
+      responses:
+        '204':
+          description: Deleted
+"#;
+
+    #[test]
+    fn handler_stubs() {
+        let dir = env::temp_dir().join(format!("axum-open-api-scaffold-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        // Existing files are not overwritten
+        fs::write(dir.join("delete_pet.rs"), "// edited").unwrap();
+
+        let body = format!(
+            "scaffold_to = {:?}; mod pets {{ GET /pets/{{pet_id}} as pub GetPet; }} \
+            DELETE /pets/{{pet_id}} as pub DeletePet;",
+            dir.to_str().unwrap()
+        );
+        expand(SPEC, &body);
+        let stub = fs::read_to_string(dir.join("get_pet.rs")).unwrap();
+        assert!(stub.starts_with("//! `GET /pets/{pet_id}`\n//! Gets a pet\n"));
+        assert!(stub.contains("import `pets::GetPet` and the schemas"));
+        assert!(stub.contains("pub async fn get_pet(req: GetPet) -> axum::Json<schemas::Pet> {"));
+        let edited = fs::read_to_string(dir.join("delete_pet.rs")).unwrap();
+        assert_eq!(edited, "// edited");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,51 +1,224 @@
-use convert_case::{Case, Casing};
-use oas3::{
-    spec::{ObjectOrReference, Parameter, SchemaType},
-    Schema,
+use super::{
+    builder::{compile_builder, Field},
+    unsupported::Policies,
 };
+use crate::codegen::PathStyle;
+use convert_case::{Case, Casing};
+use oas3::{spec::SchemaType, Schema};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::ToTokens;
+use serde_json::Value;
 use std::iter::repeat;
-use syn::{Item, Type};
+use syn::{Item, LitStr, Type};
+
+/// How deep the generated code is nested in the modules of the macro, for the paths to the
+/// generated modules.
+#[derive(Debug, Clone)]
+pub struct Depth {
+    levels: usize,
+    /// The path of the generated `schemas` module, which is its name unless it is generated by
+    /// another invocation, from `schemas_from = crate::api::schemas;`
+    schemas: syn::Path,
+    /// The serde `rename_all` rule of the properties, from `rename_all = "camelCase";`
+    rename_all: Option<LitStr>,
+    /// What to do with unsupported features, from `unsupported = { composition: warn };`
+    policies: Policies,
+    /// The name of an untitled object or enum at this place, derived from where it is, like
+    /// `GetPostsResponse200Item`
+    anonymous: Option<String>,
+}
+
+impl Depth {
+    pub fn new(
+        schemas: syn::Path,
+        levels: usize,
+        rename_all: Option<LitStr>,
+        policies: Policies,
+    ) -> Self {
+        Self {
+            levels,
+            schemas,
+            rename_all,
+            policies,
+            anonymous: None,
+        }
+    }
+
+    /// The depth of the items in a module at this depth.
+    pub fn nested(&self) -> Self {
+        Self::new(
+            self.schemas.clone(),
+            self.levels + 1,
+            self.rename_all.clone(),
+            self.policies,
+        )
+    }
+
+    /// The same depth, where an untitled object or enum is named `name`.
+    pub fn named(&self, name: Option<String>) -> Self {
+        Self {
+            anonymous: name,
+            ..self.clone()
+        }
+    }
 
-pub fn compile_param(param: Parameter, depth: usize, items: &mut Vec<Item>) -> syn::Result<Type> {
-    let Some(schema) = param.schema else {
+    /// `super::` for every level, which is the path to the root of the macro.
+    pub fn prefix(&self) -> TokenStream {
+        repeat(quote!(super::)).take(self.levels).collect()
+    }
+
+    /// The path to the generated `schemas` module. Paths from the root of the crate, like
+    /// `crate::api::schemas`, are the same at every depth.
+    pub fn schemas(&self) -> TokenStream {
+        let schemas = &self.schemas;
+        let from_root = schemas.leading_colon.is_some()
+            || schemas
+                .segments
+                .first()
+                .is_some_and(|segment| segment.ident == "crate");
+        if from_root {
+            return quote!(#schemas);
+        }
+        let prefix = self.prefix();
+        quote!(#prefix #schemas)
+    }
+}
+
+/// Returns the type of the parameter, which is optional unless the parameter is `required`.
+pub fn compile_param(param: &Value, depth: &Depth, items: &mut Vec<Item>) -> syn::Result<Type> {
+    let optional = |ty: Type| match param["required"].as_bool() {
+        Some(true) => ty,
+        _ => parse_quote!(Option<#ty>),
+    };
+
+    // Parameters with `content` are json encoded in their value, like
+    // `?filter={"status":"active"}`
+    if let Some((media_type, media)) = param["content"].as_object().and_then(|c| c.iter().next()) {
+        if !media_type.ends_with("json") {
+            return Err(err_call_site!(
+                "Parameter `{}` with content `{media_type}` is not supported, only json",
+                param["name"].as_str().unwrap_or_default()
+            ));
+        }
+        let ty = compile_schema(&media["schema"], None, depth, items)?;
+        return Ok(optional(
+            parse_quote!(::axum_open_api::extract::JsonParam<#ty>),
+        ));
+    }
+
+    let Some(schema) = param.get("schema") else {
         return Err(err_call_site!(
-            "Query parameter does not have a schema in OpenAPI spec: \n{param:#?}"
+            "Query parameter does not have a schema in OpenAPI spec: \n{param:#}"
         ));
     };
 
-    let ty = compile_schema(ObjectOrReference::Object(schema), None, depth, items)?;
+    // Parameters are parsed in their default style, except for the prefixed styles of path
+    // parameters, whose values are single segments whether they are exploded or not
+    let default_style = match param["in"].as_str() {
+        Some("path" | "header") => "simple",
+        _ => "form",
+    };
+    let style = param["style"].as_str().unwrap_or(default_style);
+    let explode = param["explode"].as_bool().unwrap_or(style == "form");
+    let default = style == default_style && explode == (style == "form");
+    if !default && path_style(param) == PathStyle::Simple {
+        let message = format!(
+            "Parameter `{}` with style `{style}` and explode `{explode}` is not supported, and is \
+            parsed with style `{default_style}`",
+            param["name"].as_str().unwrap_or_default()
+        );
+        depth.policies.styles.apply(&message, items)?;
+    }
+
+    let ty = compile_schema(schema, None, depth, items)?;
+    Ok(optional(ty))
+}
 
-    match param.required {
-        Some(true) => Ok(ty),
-        _ => Ok(parse_quote!(Option<#ty>)),
+/// The style of a path parameter, which is `simple` unless it is `label` or `matrix`.
+pub fn path_style(param: &Value) -> PathStyle {
+    match (param["in"].as_str(), param["style"].as_str()) {
+        (Some("path"), Some("label")) => PathStyle::Label,
+        (Some("path"), Some("matrix")) => PathStyle::Matrix,
+        _ => PathStyle::Simple,
     }
 }
 
+/// The formats of json schema and OpenAPI, which are plain strings and numbers in rust. The
+/// unsigned and small integers are those of specs generated by `utoipa` and `aide`.
+const FORMATS: [&str; 33] = [
+    "int8",
+    "int16",
+    "int32",
+    "int64",
+    "uint",
+    "uint8",
+    "uint16",
+    "uint32",
+    "uint64",
+    "float",
+    "double",
+    "byte",
+    "binary",
+    "password",
+    "date-time",
+    "date",
+    "time",
+    "duration",
+    "email",
+    "idn-email",
+    "hostname",
+    "idn-hostname",
+    "ipv4",
+    "ipv6",
+    "uri",
+    "uri-reference",
+    "iri",
+    "iri-reference",
+    "uuid",
+    "uri-template",
+    "json-pointer",
+    "relative-json-pointer",
+    "regex",
+];
+
 /// Returns the type, while recursively compiling all schemas and adding any new types to the items
+///
+/// The schema is the raw json of the spec, because `oas3` does not parse every keyword of 3.1,
+/// like `const`.
 pub fn compile_schema(
-    schema_ref: ObjectOrReference<Schema>,
+    raw: &Value,
     title: Option<&str>,
-    depth: usize,
+    depth: &Depth,
     items: &mut Vec<Item>,
 ) -> syn::Result<Type> {
     // If it's a reference, we can just use that as our type, and don't have to create an item
-    let schema = match schema_ref {
-        ObjectOrReference::Ref { ref_path } => return compile_schema_ref(&ref_path, depth),
-        ObjectOrReference::Object(schema) => schema,
-    };
+    if let Some(ref_path) = raw.get("$ref").and_then(Value::as_str) {
+        return compile_schema_ref(ref_path, depth);
+    }
+    let schema: Schema = serde_json::from_value(raw.clone())
+        .map_err(|e| err_call_site!("Invalid schema in OpenAPI spec: {e}\n{raw:#}"))?;
 
-    // We don't support `all_of`` or `any_of``
+    // We don't support `all_of`` or `any_of``, but they can fall back to any json value
     if !schema.all_of.is_empty() || !schema.any_of.is_empty() {
-        return Err(err_call_site!(
-            "allOf and anyOf are not (yet) supported: \n{schema:#?}"
-        ));
+        let message = format!("allOf and anyOf are not (yet) supported: {raw}");
+        depth.policies.composition.apply(&message, items)?;
+        let value_ty = parse_quote!(serde_json::Value);
+        return compile_base_type(value_ty, title, &schema, items);
+    }
+
+    // handle `not` and `if` by checking the value after deserialization
+    if raw.get("not").is_some() || raw.get("if").is_some() {
+        return compile_checked(raw, title, depth, items);
+    }
+
+    // handle `const` by generating a unit struct
+    if let Some(constant) = raw.get("const") {
+        return compile_const(constant, title, &schema, items);
     }
 
     // handle `oneOf` by generating an enum
     if !schema.one_of.is_empty() {
-        return compile_one_of(schema, title, depth, items);
+        return compile_one_of(raw, schema, title, depth, items);
     }
 
     // If it is not `oneOf`, `schema_type` must be set
@@ -53,10 +226,21 @@ pub fn compile_schema(
         "schema {schema:#?} is missing `schema_type` and `one_of`"
     ))?;
 
+    // Unknown formats are generated as the type without the format
+    if let Some(format) = raw["format"].as_str() {
+        if !FORMATS.contains(&format) {
+            let message = format!("Format `{format}` is not supported, and is ignored: {raw}");
+            depth.policies.formats.apply(&message, items)?;
+        }
+    }
+
     // Now we go on to calculate the schema types
     match schema_type {
-        SchemaType::Object => compile_object(schema, title, depth, items),
-        SchemaType::Array => compile_array(schema, title, depth, items),
+        SchemaType::Object => compile_object(raw, schema, title, depth, items),
+        SchemaType::Array => compile_array(raw, schema, title, depth, items),
+        SchemaType::String if raw.get("enum").is_some() => {
+            compile_string_enum(raw, title, &schema, items)
+        }
         SchemaType::String => compile_base_type(parse_quote!(String), title, &schema, items),
         SchemaType::Number => compile_base_type(parse_quote!(f64), title, &schema, items),
         SchemaType::Integer => compile_base_type(parse_quote!(i64), title, &schema, items),
@@ -64,67 +248,554 @@ pub fn compile_schema(
     }
 }
 
-fn compile_schema_ref(ref_path: &str, depth: usize) -> syn::Result<Type> {
-    let depth_prefix = repeat(quote!(super::)).take(depth).collect::<TokenStream>();
+fn compile_schema_ref(ref_path: &str, depth: &Depth) -> syn::Result<Type> {
+    let schemas = depth.schemas();
     let ref_name = ref_path.split('/').last().unwrap();
     let ident = Ident::new(ref_name, Span::call_site());
-    Ok(parse_quote!(#depth_prefix schemas::#ident))
+    Ok(parse_quote!(#schemas::#ident))
+}
+
+/// Compiles a schema with a `const` value into a unit struct, which (de)serializes only the
+/// constant value, like `pub struct ApiVersion;` for `const: v1`.
+///
+/// Without a title, the type of the value is used instead, and the value is not checked.
+fn compile_const(
+    constant: &Value,
+    title: Option<&str>,
+    schema: &Schema,
+    items: &mut Vec<Item>,
+) -> syn::Result<Type> {
+    let (ty, value): (Type, TokenStream) = match constant {
+        Value::String(string) => (parse_quote!(String), quote!(#string)),
+        Value::Bool(boolean) => (parse_quote!(bool), quote!(#boolean)),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => (parse_quote!(i64), quote!(#integer)),
+            None => {
+                let number = number.as_f64().unwrap_or_default();
+                (parse_quote!(f64), quote!(#number))
+            }
+        },
+        _ => {
+            return Err(err_call_site!(
+                "Only string, number and boolean constants are supported: \n{constant}"
+            ))
+        }
+    };
+
+    let Ok(ident) = try_merge_titles(title, schema) else {
+        return Ok(ty);
+    };
+    // A `String` can't be a constant, so string values are a `&str`
+    let value_ty: Type = match constant {
+        Value::String(_) => parse_quote!(&'static str),
+        _ => ty.clone(),
+    };
+    let doc = format!("Generated from OpenAPI schema, with the constant value `{constant}`");
+    items.push(parse_quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        pub struct #ident;
+    });
+    items.push(parse_quote! {
+        impl #ident {
+            /// The constant value
+            pub const VALUE: #value_ty = #value;
+        }
+    });
+    items.push(parse_quote! {
+        impl serde::Serialize for #ident {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&#value, serializer)
+            }
+        }
+    });
+    items.push(parse_quote! {
+        impl<'de> serde::Deserialize<'de> for #ident {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = <#ty as serde::Deserialize>::deserialize(deserializer)?;
+                if value == #value {
+                    Ok(Self)
+                } else {
+                    Err(serde::de::Error::custom(format!(
+                        "expected constant {:?}, found {:?}",
+                        #value, value
+                    )))
+                }
+            }
+        }
+    });
+
+    Ok(parse_quote!(#ident))
+}
+
+/// Compiles a schema with `not` or `if` into the schema without them, which is checked after
+/// deserialization, like `Checked<UsernameUnchecked, UsernameCheck>` for a string that is not
+/// `admin`.
+///
+/// Only values excluded with `enum` or `const` are checked, for strings, numbers and booleans.
+/// Conditionals are checked when the `if` compares properties to values or requires them, and
+/// the `then` and `else` require properties, like `cardNumber` when `type` is `card`. Other
+/// forms, and schemas without a title, are not checked, which is left to the `validation`
+/// feature.
+fn compile_checked(
+    raw: &Value,
+    title: Option<&str>,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<Type> {
+    let mut base = raw.clone();
+    let mut remove = |keyword: &str| {
+        base.as_object_mut()
+            .and_then(|base| base.remove(keyword))
+            .unwrap_or_default()
+    };
+    let not = remove("not");
+    let (if_schema, then_schema, else_schema) = (remove("if"), remove("then"), remove("else"));
+    let excluded = match (&not["enum"], not.get("const")) {
+        (Value::Array(values), _) => values.clone(),
+        (_, Some(constant)) => vec![constant.clone()],
+        _ => Vec::new(),
+    };
+
+    // Without a type, it is the type of the excluded values, like `not: { enum: [admin] }`
+    if base.get("type").is_none() && base.get("oneOf").is_none() {
+        let schema_type = match excluded.first() {
+            Some(Value::String(_)) => Some("string"),
+            Some(Value::Bool(_)) => Some("boolean"),
+            Some(Value::Number(number)) if number.is_i64() => Some("integer"),
+            Some(Value::Number(_)) => Some("number"),
+            _ => None,
+        };
+        if let Some(schema_type) = schema_type {
+            base["type"] = schema_type.into();
+        }
+    }
+
+    let checks: Vec<TokenStream> = [
+        not_checks(&excluded, &base),
+        conditional_check(&if_schema, &then_schema, &else_schema, &base),
+    ]
+    .into_iter()
+    .flatten()
+    .flatten()
+    .collect();
+    let title = title.or(raw["title"].as_str());
+    let name = title.or(depth.anonymous.as_deref());
+    let (Some(name), false) = (name, checks.is_empty()) else {
+        return compile_schema(&base, title, depth, items);
+    };
+
+    let ident = Ident::new(name, Span::call_site());
+    let unchecked = format_ident!("{ident}Unchecked");
+    let check = format_ident!("{ident}Check");
+    let base_ty = compile_schema(&base, Some(&unchecked.to_string()), depth, items)?;
+
+    let doc = format!("Generated from OpenAPI schema, which is checked by [`{check}`]");
+    items.push(parse_quote! {
+        #[doc = #doc]
+        pub type #ident = ::axum_open_api::check::Checked<#base_ty, #check>;
+    });
+    items.push(parse_quote! {
+        /// Checks the keywords of the schema that are not expressed by its type
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct #check;
+    });
+    items.push(parse_quote! {
+        impl ::axum_open_api::check::Check<#base_ty> for #check {
+            fn check(value: &#base_ty) -> Result<(), String> {
+                #(#checks)*
+                Ok(())
+            }
+        }
+    });
+    Ok(parse_quote!(#ident))
+}
+
+/// The checks that the value is none of the values excluded by `not`, or `None` if they can't be
+/// compared to the type of the schema.
+fn not_checks(excluded: &[Value], schema: &Value) -> Option<Vec<TokenStream>> {
+    if excluded.is_empty() {
+        return None;
+    }
+    let schema_type = schema["type"].as_str()?;
+    excluded
+        .iter()
+        .map(|excluded| {
+            let condition = equals(quote!(value), schema_type, excluded)?;
+            let message = format!("{excluded} is not allowed");
+            Some(quote! {
+                if #condition {
+                    return Err(#message.to_owned());
+                }
+            })
+        })
+        .collect()
+}
+
+/// The check that the properties required by `then` are set if the object matches the `if`, and
+/// those required by `else` otherwise, or `None` if the conditional can't be checked.
+fn conditional_check(
+    if_schema: &Value,
+    then_schema: &Value,
+    else_schema: &Value,
+    schema: &Value,
+) -> Option<Vec<TokenStream>> {
+    if if_schema.is_null() || schema["type"] != "object" {
+        return None;
+    }
+    let is_property = |property: &str| schema["properties"].get(property).is_some();
+    let is_required = |property: &str| {
+        schema["required"]
+            .as_array()
+            .is_some_and(|required| required.iter().any(|required| required == property))
+    };
+
+    // The `if` matches when its properties, if they are set, have the values, and its required
+    // properties are set
+    let mut conditions = Vec::new();
+    for (property, if_property) in if_schema["properties"].as_object().into_iter().flatten() {
+        let values = match (&if_property["enum"], if_property.get("const")) {
+            (Value::Array(values), _) => values.clone(),
+            (_, Some(constant)) => vec![constant.clone()],
+            _ => return None,
+        };
+        let schema_type = schema["properties"][property]["type"].as_str()?;
+        let matches = values
+            .iter()
+            .map(|value| equals(quote!(field), schema_type, value))
+            .collect::<Option<Vec<_>>>()?;
+        let field = field_ident(property);
+        conditions.push(if is_required(property) {
+            quote!({ let field = &value.#field; #(#matches)||* })
+        } else {
+            quote! {
+                match &value.#field {
+                    Some(field) => #(#matches)||*,
+                    None => true,
+                }
+            }
+        });
+    }
+    for property in if_schema["required"].as_array().into_iter().flatten() {
+        let property = property.as_str().filter(|property| is_property(property))?;
+        if !is_required(property) {
+            let field = field_ident(property);
+            conditions.push(quote!(value.#field.is_some()));
+        }
+    }
+    if conditions.is_empty() {
+        return None;
+    }
+
+    let required_checks = |branch: &Value, keyword: &str| {
+        branch["required"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .filter(|property| is_property(property) && !is_required(property))
+            .map(|property| {
+                let field = field_ident(property);
+                let message = format!("`{property}` is required by the `{keyword}` of the schema");
+                quote! {
+                    if value.#field.is_none() {
+                        return Err(#message.to_owned());
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let then_checks = required_checks(then_schema, "then");
+    let else_checks = required_checks(else_schema, "else");
+    if then_checks.is_empty() && else_checks.is_empty() {
+        return None;
+    }
+    Some(vec![quote! {
+        if #(#conditions)&&* {
+            #(#then_checks)*
+        } else {
+            #(#else_checks)*
+        }
+    }])
+}
+
+/// The condition that the value behind the reference `value` equals the json value, or `None` if
+/// they can't be compared.
+fn equals(value: TokenStream, schema_type: &str, json: &Value) -> Option<TokenStream> {
+    match (schema_type, json) {
+        ("string", Value::String(string)) => Some(quote!(#value.as_str() == #string)),
+        ("boolean", Value::Bool(boolean)) => Some(quote!(*#value == #boolean)),
+        ("integer", Value::Number(number)) => {
+            let number = number.as_i64()?;
+            Some(quote!(*#value == #number))
+        }
+        ("number", Value::Number(number)) => {
+            let number = number.as_f64()?;
+            Some(quote!(*#value == #number))
+        }
+        _ => None,
+    }
+}
+
+/// Compiles a string schema with an `enum` into an enum with a variant for every value, like
+/// `pub enum Status { Active, Archived }`, with `Display` and `FromStr` for the values in the
+/// spec.
+///
+/// Without a title, the type is a plain `String`.
+fn compile_string_enum(
+    raw: &Value,
+    title: Option<&str>,
+    schema: &Schema,
+    items: &mut Vec<Item>,
+) -> syn::Result<Type> {
+    let Ok(ident) = try_merge_titles(title, schema) else {
+        return Ok(parse_quote!(String));
+    };
+    let values = raw["enum"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|value| {
+            value.as_str().ok_or_else(|| {
+                err_call_site!("Only string values are supported in a string enum: \n{value}")
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let variants = values
+        .iter()
+        .map(|value| variant_ident(value))
+        .collect::<Vec<_>>();
+
+    items.push(parse_quote! {
+        /// Generated from OpenAPI schema
+        #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum #ident {
+            #(
+                #[serde(rename = #values)]
+                #variants,
+            )*
+        }
+    });
+    items.push(parse_quote! {
+        impl #ident {
+            /// The value as written in the spec
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    #(#ident::#variants => #values,)*
+                }
+            }
+        }
+    });
+    items.push(parse_quote! {
+        impl ::std::fmt::Display for #ident {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    });
+    items.push(parse_quote! {
+        impl ::std::str::FromStr for #ident {
+            type Err = ::axum_open_api::UnknownVariant;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                match value {
+                    #(#values => Ok(#ident::#variants),)*
+                    _ => Err(::axum_open_api::UnknownVariant {
+                        value: value.to_owned(),
+                        expected: &[#(#values),*],
+                    }),
+                }
+            }
+        }
+    });
+
+    Ok(parse_quote!(#ident))
+}
+
+/// The variant of an enum value, like `InProgress` for `in-progress`, or `V2` for `2`.
+pub fn variant_ident(value: &str) -> Ident {
+    let name = value
+        .to_case(Case::Pascal)
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>();
+    match name.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() => Ident::new(&name, Span::call_site()),
+        _ => format_ident!("V{name}"),
+    }
 }
 
 fn compile_one_of(
+    raw: &Value,
     schema: Schema,
     title: Option<&str>,
-    depth: usize,
+    depth: &Depth,
     items: &mut Vec<Item>,
 ) -> syn::Result<Type> {
-    let ident = try_merge_titles(title, &schema)?;
+    let ident = type_ident(title, &schema, depth)?;
 
     // First we collect all the variants
     let mut variants = Vec::new();
-    for variant_schema in schema.one_of {
-        let variant_ty = compile_schema(variant_schema, None, depth, items)?;
-        let variant_ident = Ident::new(
-            &variant_ty
-                .to_token_stream()
-                .to_string()
-                .split("::")
-                .last()
-                .unwrap()
-                .to_case(Case::UpperCamel),
-            Span::call_site(),
-        );
-        variants.push(quote! { #variant_ident(#variant_ty) });
+    let mut conversions = Vec::new();
+    for (index, variant_schema) in raw["oneOf"].as_array().into_iter().flatten().enumerate() {
+        let variant_depth = depth.named(Some(format!("{ident}Variant{}", index + 1)));
+        let variant_ty = compile_schema(variant_schema, None, &variant_depth, items)?;
+        // Large variants can be boxed with `x-rust-box: true`
+        let boxed = variant_schema["x-rust-box"] == true;
+        // With a `discriminator.mapping`, the variant is named after its key in the mapping
+        let mapping_key = variant_schema["$ref"]
+            .as_str()
+            .and_then(|reference| mapping_key(raw, reference));
+        let (variant_ident, rename) = match mapping_key {
+            Some(key) => (variant_ident(key), Some(quote!(#[serde(rename = #key)]))),
+            None => {
+                let variant_ident = Ident::new(
+                    &variant_ty
+                        .to_token_stream()
+                        .to_string()
+                        .split("::")
+                        .last()
+                        .unwrap()
+                        .to_case(Case::UpperCamel),
+                    Span::call_site(),
+                );
+                (variant_ident, None)
+            }
+        };
+        if boxed {
+            variants.push(quote! { #rename #variant_ident(Box<#variant_ty>) });
+        } else {
+            variants.push(quote! { #rename #variant_ident(#variant_ty) });
+        }
+        conversions.push((variant_ident, variant_ty, boxed));
     }
 
+    let tagging = compile_tagging(raw)?;
     items.push(parse_quote! {
         /// Generated from OpenAPI schema
         #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+        #tagging
         pub enum #ident {
             #(#variants),*
         }
     });
 
+    // Values are converted into the enum with `From`, and narrowed back with `TryFrom`
+    for (variant_ident, variant_ty, boxed) in conversions {
+        items.extend(compile_conversions(
+            &ident,
+            &variant_ident,
+            &variant_ty,
+            boxed,
+        ));
+    }
+
     Ok(parse_quote!(#ident))
 }
 
+/// The serde representation of a `oneOf` enum, chosen with `x-rust-tagging` on the schema:
+/// `external` like serde, `untagged`, `internal` or `adjacent`. The tag is the `propertyName` of
+/// the `discriminator`, or else `x-rust-tag`, and the content of adjacently tagged enums is
+/// `x-rust-content`, or else `content`.
+fn compile_tagging(raw: &Value) -> syn::Result<Option<TokenStream>> {
+    let Some(tagging) = raw.get("x-rust-tagging") else {
+        return Ok(None);
+    };
+    // Tagged enums are tagged by the discriminator, if there is one
+    let tag = raw["discriminator"]["propertyName"]
+        .as_str()
+        .or_else(|| raw["x-rust-tag"].as_str());
+    let tag = || {
+        tag.ok_or_else(|| {
+            err_call_site!("A tagged `oneOf` needs a `discriminator.propertyName` or `x-rust-tag`")
+        })
+    };
+    match tagging.as_str() {
+        Some("external") => Ok(None),
+        Some("untagged") => Ok(Some(quote!(#[serde(untagged)]))),
+        Some("internal") => {
+            let tag = tag()?;
+            Ok(Some(quote!(#[serde(tag = #tag)])))
+        }
+        Some("adjacent") => {
+            let tag = tag()?;
+            let content = raw["x-rust-content"].as_str().unwrap_or("content");
+            Ok(Some(quote!(#[serde(tag = #tag, content = #content)])))
+        }
+        _ => Err(err_call_site!(
+            "Unknown `x-rust-tagging` {tagging}, expected `external`, `untagged`, `internal` or \
+             `adjacent`"
+        )),
+    }
+}
+
+/// The key of the reference in the `discriminator.mapping` of the `oneOf`. The values of the
+/// mapping are either references or the names of schemas.
+fn mapping_key<'a>(raw: &'a Value, reference: &str) -> Option<&'a str> {
+    let name = reference.rsplit('/').next()?;
+    raw["discriminator"]["mapping"]
+        .as_object()?
+        .iter()
+        .find(|(_, value)| value.as_str().and_then(|value| value.rsplit('/').next()) == Some(name))
+        .map(|(key, _)| key.as_str())
+}
+
+/// Compiles the `From` and `TryFrom` conversions between a variant of a `oneOf` enum and the
+/// enum, with the value of the variant in a `Box` if it is boxed.
+pub fn compile_conversions(
+    ident: &Ident,
+    variant_ident: &Ident,
+    variant_ty: &Type,
+    boxed: bool,
+) -> [Item; 2] {
+    let (wrap, unwrap) = if boxed {
+        (quote!(Box::new(value)), quote!(*value))
+    } else {
+        (quote!(value), quote!(value))
+    };
+    [
+        parse_quote! {
+            impl From<#variant_ty> for #ident {
+                fn from(value: #variant_ty) -> Self {
+                    #ident::#variant_ident(#wrap)
+                }
+            }
+        },
+        parse_quote! {
+            impl TryFrom<#ident> for #variant_ty {
+                type Error = #ident;
+
+                fn try_from(value: #ident) -> Result<Self, Self::Error> {
+                    #[allow(unreachable_patterns)]
+                    match value {
+                        #ident::#variant_ident(value) => Ok(#unwrap),
+                        other => Err(other),
+                    }
+                }
+            }
+        },
+    ]
+}
+
 fn compile_array(
+    raw: &Value,
     schema: Schema,
     title: Option<&str>,
-    depth: usize,
+    depth: &Depth,
     items: &mut Vec<Item>,
 ) -> syn::Result<Type> {
     let merged_title = try_merge_titles(title, &schema);
 
-    // if title == Some("NestedInlineSchema") {
-    //     panic!()
-    // }
-
+    // Untitled items are named after the array, like `GetPostsResponse200Item`
+    let name = match &merged_title {
+        Ok(ident) => Some(ident.to_string()),
+        Err(_) => depth.anonymous.clone(),
+    };
     let item_ty = compile_schema(
-        *schema
-            .items
+        raw.get("items")
             .ok_or_else(|| err_call_site!("Array must contain `items` field"))?,
         None,
-        depth,
+        &depth.named(name.map(|name| format!("{name}Item"))),
         items,
     )?;
     let ty = parse_quote!(Vec<#item_ty>);
@@ -141,37 +812,218 @@ fn compile_array(
 }
 
 fn compile_object(
+    raw: &Value,
     schema: Schema,
     title: Option<&str>,
-    depth: usize,
+    depth: &Depth,
     items: &mut Vec<Item>,
 ) -> syn::Result<Type> {
-    let ident = try_merge_titles(title, &schema)?;
+    // Objects with only `patternProperties` or `additionalProperties`, like a map by locale
+    // code, are maps
+    if schema.properties.is_empty() {
+        let name = title
+            .or(schema.title.as_deref())
+            .or(depth.anonymous.as_deref());
+        let value_depth = depth.named(name.map(|name| format!("{name}Value")));
+        let value_ty = match compile_pattern_properties(raw, &value_depth, items)? {
+            Some(value_ty) => Some(value_ty),
+            None => compile_additional_properties(raw, &value_depth, items)?,
+        };
+        if let Some(value_ty) = value_ty {
+            let map_ty = compile_map_type(raw, value_ty);
+            return compile_base_type(map_ty, title, &schema, items);
+        }
+    }
+
+    let ident = type_ident(title, &schema, depth)?;
 
     // First we parse all the fields
     let mut fields: Vec<TokenStream> = Vec::new();
-    for (prop_name, prop_schema) in schema.properties {
-        let prop_name = Ident::new(&prop_name, Span::call_site());
-        let prop_ty = compile_schema(prop_schema, None, depth, items)?;
+    let mut builder_fields = Vec::new();
+    for wire_name in schema.properties.keys() {
+        // Untitled properties are named after the object, like `PostAuthor`
+        let prop_depth = depth.named(Some(format!("{ident}{}", variant_ident(wire_name))));
+        let prop_ty = compile_schema(&raw["properties"][wire_name], None, &prop_depth, items)?;
+        let required = schema.required.contains(wire_name);
+        let prop_name = field_ident(wire_name);
+        // Fields are renamed to their property, unless the `rename_all` rule already does
+        let renamed = match &depth.rename_all {
+            Some(rule) => rename_field(&prop_name.to_string(), &rule.value()) != *wire_name,
+            None => prop_name != wire_name,
+        };
+        let rename = renamed.then(|| quote!(#[serde(rename = #wire_name)]));
         // If the property is required, we don't wrap it in an Option
-        if schema.required.contains(&prop_name.to_string()) {
-            fields.push(quote! { pub #prop_name: #prop_ty});
+        if required {
+            fields.push(quote! { #rename pub #prop_name: #prop_ty});
         } else {
-            fields.push(quote! { pub #prop_name: Option<#prop_ty>});
+            fields.push(quote! { #rename pub #prop_name: Option<#prop_ty>});
         }
+        builder_fields.push(Field {
+            ident: prop_name,
+            ty: prop_ty,
+            required,
+            default: false,
+        });
     }
 
+    // The other properties are collected in a map, if they match one of the patterns, or else
+    // if they have a schema. Other properties are allowed by default, so they are only
+    // collected with an `additionalProperties` schema that is not empty.
+    let value_depth = depth.named(Some(format!("{ident}Value")));
+    let map_field = match compile_pattern_properties(raw, &value_depth, items)? {
+        Some(value_ty) => Some((format_ident!("pattern_properties"), value_ty)),
+        None if raw["additionalProperties"]
+            .as_object()
+            .is_some_and(|schema| !schema.is_empty()) =>
+        {
+            compile_additional_properties(raw, &value_depth, items)?
+                .map(|value_ty| (format_ident!("additional_properties"), value_ty))
+        }
+        None => None,
+    };
+    if let Some((map_ident, value_ty)) = map_field {
+        let map_ty: Type = parse_quote!(::std::collections::HashMap<String, #value_ty>);
+        fields.push(quote! { #[serde(flatten)] pub #map_ident: #map_ty });
+        builder_fields.push(Field {
+            ident: map_ident,
+            ty: map_ty,
+            required: false,
+            default: true,
+        });
+    }
+
+    // Structs of only optional fields, like filters and patches, can be built from the default
+    let default = builder_fields
+        .iter()
+        .all(|field| !field.required)
+        .then(|| quote!(, Default));
+    let rename_all = depth
+        .rename_all
+        .as_ref()
+        .map(|rule| quote!(#[serde(rename_all = #rule)]));
     items.push(parse_quote! {
         /// Generated from OpenAPI schema
-        #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+        #[derive(serde::Serialize, serde::Deserialize, Debug, Clone #default)]
+        #rename_all
         pub struct #ident {
             #(#fields),*
         }
     });
+    compile_builder(&ident, &builder_fields, items);
 
     Ok(parse_quote!(#ident))
 }
 
+/// The map of an object with only `patternProperties` or `additionalProperties`. With
+/// `minProperties` or `maxProperties` it is a `BoundedMap`, which rejects maps with too many
+/// entries while deserializing them.
+fn compile_map_type(raw: &Value, value_ty: Type) -> Type {
+    let min = raw["minProperties"].as_u64().map(|min| min as usize);
+    let max = raw["maxProperties"].as_u64().map(|max| max as usize);
+    if min.is_none() && max.is_none() {
+        return parse_quote!(::std::collections::HashMap<String, #value_ty>);
+    }
+    let min = min.unwrap_or(0);
+    let max = match max {
+        Some(max) => quote!(#max),
+        None => quote!({ usize::MAX }),
+    };
+    parse_quote!(::axum_open_api::map::BoundedMap<#value_ty, #min, #max>)
+}
+
+/// Compiles the schemas of the `patternProperties`, returning the type of their values. The
+/// patterns of the keys are not checked.
+fn compile_pattern_properties(
+    raw: &Value,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<Option<Type>> {
+    let mut value_ty: Option<Type> = None;
+    for (pattern, schema) in raw["patternProperties"].as_object().into_iter().flatten() {
+        let ty = compile_schema(schema, None, depth, items)?;
+        if value_ty.as_ref().is_some_and(|value_ty| *value_ty != ty) {
+            return Err(err_call_site!(
+                "All `patternProperties` must have the same schema, but `{pattern}` differs"
+            ));
+        }
+        value_ty = Some(ty);
+    }
+    Ok(value_ty)
+}
+
+/// Compiles the schema of the `additionalProperties`, returning the type of their values. With
+/// `true` or an empty schema, the values can be any json value.
+fn compile_additional_properties(
+    raw: &Value,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<Option<Type>> {
+    match raw.get("additionalProperties") {
+        Some(Value::Bool(true)) => Ok(Some(parse_quote!(serde_json::Value))),
+        Some(Value::Object(schema)) if schema.is_empty() => {
+            Ok(Some(parse_quote!(serde_json::Value)))
+        }
+        Some(schema @ Value::Object(_)) => compile_schema(schema, None, depth, items).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// The field of a property in snake case, like `created_at` for `createdAt`. Keywords become raw
+/// identifiers, like `r#type`.
+pub fn field_ident(property: &str) -> Ident {
+    let name = property
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .collect::<String>()
+        .to_case(Case::Snake);
+    let first = name.chars().next();
+    let name = match first {
+        _ if name.trim_matches('_').is_empty() => "field".to_owned(),
+        Some(first) if first.is_ascii_digit() => format!("_{name}"),
+        _ => name,
+    };
+    match syn::parse_str::<Ident>(&name) {
+        Ok(ident) => ident,
+        Err(_) if matches!(name.as_str(), "self" | "super" | "crate" | "Self") => {
+            format_ident!("{name}_")
+        }
+        Err(_) => Ident::new_raw(&name, Span::call_site()),
+    }
+}
+
+/// The name of a snake case field after the serde `rename_all` rule, like serde renames it.
+fn rename_field(field: &str, rule: &str) -> String {
+    let pascal = || {
+        let mut pascal = String::new();
+        let mut capitalize = true;
+        for ch in field.chars() {
+            if ch == '_' {
+                capitalize = true;
+            } else if capitalize {
+                pascal.push(ch.to_ascii_uppercase());
+                capitalize = false;
+            } else {
+                pascal.push(ch);
+            }
+        }
+        pascal
+    };
+    match rule {
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field.to_ascii_uppercase(),
+        "PascalCase" => pascal(),
+        "camelCase" => {
+            let pascal = pascal();
+            match pascal.chars().next() {
+                Some(first) => first.to_ascii_lowercase().to_string() + &pascal[first.len_utf8()..],
+                None => pascal,
+            }
+        }
+        "kebab-case" => field.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field.to_ascii_uppercase().replace('_', "-"),
+        _ => field.to_owned(),
+    }
+}
+
 fn compile_base_type(
     ty: Type,
     title: Option<&str>,
@@ -193,13 +1045,85 @@ fn compile_base_type(
     }
 }
 
+/// The name of an object or enum: its title, or else the name derived from where it is.
+fn type_ident(title: Option<&str>, schema: &Schema, depth: &Depth) -> syn::Result<Ident> {
+    match (try_merge_titles(title, schema), &depth.anonymous) {
+        (Err(_), Some(name)) => Ok(Ident::new(name, Span::call_site())),
+        (merged, _) => merged,
+    }
+}
+
 fn try_merge_titles(title: Option<&str>, schema: &Schema) -> syn::Result<Ident> {
     let title = title.or(schema.title.as_deref()).ok_or_else(|| {
         err_call_site!(
-            "Anonymous schema's are not supported: 
+            "Untitled objects and oneOfs are named after the body, property or item they are in, \
+             but this one is not in any, like the schema of a parameter: 
 Please add a `title`, or create a schema in `components/schemas` and reference it.
 {schema:#?}"
         )
     })?;
     Ok(Ident::new(title, Span::call_site()))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::{expand, expand_err};
+
+    #[test]
+    fn untitled_schemas_are_named_after_their_place() {
+        let spec = r#"
+openapi: 3.0.0
+info:
+  title: Untitled
+  version: 0.0.1
+paths:
+  /posts:
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema:
+              type: object
+              properties:
+                tags:
+                  type: array
+                  items:
+                    type: object
+                    properties:
+                      name:
+                        type: string
+      responses:
+        '201':
+          description: The post was created
+"#;
+        let code = expand(spec, "POST /posts as CreatePost;");
+        assert!(code.contains("pub struct CreatePostBody {"));
+        assert!(code.contains("pub struct CreatePostBodyTagsItem {"));
+    }
+
+    #[test]
+    fn untitled_parameter_objects_need_a_title() {
+        let spec = r#"
+openapi: 3.0.0
+info:
+  title: Untitled
+  version: 0.0.1
+paths:
+  /posts:
+    get:
+      parameters:
+        - in: query
+          name: filter
+          schema:
+            type: object
+            properties:
+              author:
+                type: string
+      responses:
+        '200':
+          description: The posts
+"#;
+        let message = expand_err(spec, "GET /posts as GetPosts;");
+        assert!(message.starts_with("Untitled objects and oneOfs are named after the body"));
+    }
+}
@@ -0,0 +1,449 @@
+use super::schema::{compile_schema, Depth};
+use crate::codegen;
+use convert_case::{Case, Casing};
+use oas3::{
+    spec::{SecurityRequirement, SecurityScheme},
+    Spec,
+};
+use proc_macro2::{Ident, Span};
+use serde_json::Value;
+use syn::{Item, Type};
+
+/// Compiles the security requirements of an operation into the checks of the generated
+/// extractor.
+pub fn compile_security_requirements(
+    requirements: &[SecurityRequirement],
+    spec: &Spec,
+    depth: &Depth,
+) -> syn::Result<Option<codegen::Security>> {
+    let mut alternatives = Vec::new();
+    for requirement in requirements {
+        let mut checks = Vec::new();
+        for (scheme_name, scopes) in &requirement.0 {
+            let scheme = spec
+                .components
+                .as_ref()
+                .and_then(|components| components.security_schemes.get(scheme_name))
+                .ok_or_else(|| {
+                    err_call_site!("Security scheme {scheme_name} not found in OpenAPI spec")
+                })?
+                .resolve(spec)
+                .map_err(|e| {
+                    err_call_site!("Could not resolve security scheme {scheme_name}: {e}")
+                })?;
+
+            checks.push(codegen::SchemeCheck {
+                scheme_name: scheme_name.clone(),
+                scheme_ty: scheme_ty(scheme_name, depth),
+                field_ident: Ident::new(&scheme_name.to_case(Case::Snake), Span::call_site()),
+                // Scopes are only defined for oauth2
+                scopes: match scheme {
+                    SecurityScheme::OAuth2 { .. } => scopes.clone(),
+                    _ => Vec::new(),
+                },
+            });
+        }
+        // An empty alternative (`{}`) is always satisfied, so security is optional
+        if checks.is_empty() {
+            return Ok(None);
+        }
+        alternatives.push(checks);
+    }
+
+    if alternatives.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(codegen::Security { alternatives }))
+}
+
+/// Returns the type of the extractor generated for the security scheme.
+fn scheme_ty(scheme_name: &str, depth: &Depth) -> Type {
+    let depth_prefix = depth.prefix();
+    let ident = Ident::new(&scheme_name.to_case(Case::UpperCamel), Span::call_site());
+    parse_quote!(#depth_prefix security::#ident)
+}
+
+/// Compiles a security scheme into an extractor struct, and adds it to the items.
+///
+/// `raw` is the scheme as plain json, used for its extensions.
+pub fn compile_security_scheme(
+    name: &str,
+    scheme: SecurityScheme,
+    raw: &Value,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<()> {
+    let ident = Ident::new(&name.to_case(Case::UpperCamel), Span::call_site());
+
+    match scheme {
+        SecurityScheme::ApiKey {
+            name: key_name,
+            location,
+            ..
+        } => compile_api_key(ident, name, &key_name, &location, items),
+        SecurityScheme::Http { scheme, .. } if scheme.eq_ignore_ascii_case("basic") => {
+            compile_http_basic(ident, name, items)
+        }
+        SecurityScheme::Http { scheme, .. } if scheme.eq_ignore_ascii_case("bearer") => {
+            compile_http_bearer(ident, name, raw, depth, items)
+        }
+        SecurityScheme::OAuth2 { .. } => compile_oauth2(ident, name, items),
+        SecurityScheme::OpenIdConnect {
+            open_id_connect_url,
+            ..
+        } => compile_open_id_connect(ident, name, &open_id_connect_url, raw, depth, items),
+        SecurityScheme::MutualTls { .. } => compile_mutual_tls(ident, name, items),
+        // Other http schemes, like `digest`, are not (yet) supported
+        SecurityScheme::Http { scheme, .. } => Err(err_call_site!(
+            "Security scheme {name} has an unsupported http scheme: `{scheme}`"
+        )),
+    }
+}
+
+fn compile_api_key(
+    ident: Ident,
+    scheme_name: &str,
+    key_name: &str,
+    location: &str,
+    items: &mut Vec<Item>,
+) -> syn::Result<()> {
+    let location = match location {
+        "header" => quote!(Header),
+        "query" => quote!(Query),
+        "cookie" => quote!(Cookie),
+        _ => {
+            return Err(err_call_site!(
+                "Security scheme {scheme_name} has an invalid api-key location: `{location}`"
+            ))
+        }
+    };
+    let doc = format!("Generated from OpenAPI security scheme `{scheme_name}`");
+
+    items.push(parse_quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone)]
+        pub struct #ident {
+            pub key: String,
+        }
+    });
+    items.push(parse_quote! {
+        #[axum::async_trait]
+        impl<S> ::axum::extract::FromRequestParts<S> for #ident
+        where
+            S: ::axum_open_api::security::ApiKeyVerifier + Send + Sync,
+        {
+            type Rejection = ::axum_open_api::Rejection;
+
+            async fn from_request_parts(
+                parts: &mut ::axum::http::request::Parts,
+                state: &S,
+            ) -> Result<Self, Self::Rejection> {
+                use ::axum_open_api::security::{extract_api_key, ApiKeyLocation, SecurityRejection};
+
+                let Some(key) = extract_api_key(parts, ApiKeyLocation::#location, #key_name) else {
+                    return Err(SecurityRejection::Missing(#scheme_name).into());
+                };
+                if !state.verify_api_key(#scheme_name, &key).await {
+                    return Err(SecurityRejection::Invalid(#scheme_name).into());
+                }
+
+                Ok(Self { key })
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn compile_http_basic(ident: Ident, scheme_name: &str, items: &mut Vec<Item>) -> syn::Result<()> {
+    let doc = format!("Generated from OpenAPI security scheme `{scheme_name}`");
+    let challenge = format!("Basic realm=\"{scheme_name}\"");
+
+    items.push(parse_quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone)]
+        pub struct #ident {
+            pub username: String,
+            pub password: String,
+        }
+    });
+    items.push(parse_quote! {
+        #[axum::async_trait]
+        impl<S> ::axum::extract::FromRequestParts<S> for #ident
+        where
+            S: ::axum_open_api::security::BasicVerifier + Send + Sync,
+        {
+            type Rejection = ::axum_open_api::Rejection;
+
+            async fn from_request_parts(
+                parts: &mut ::axum::http::request::Parts,
+                state: &S,
+            ) -> Result<Self, Self::Rejection> {
+                use ::axum_open_api::security::{extract_basic_credentials, SecurityRejection};
+
+                let rejection = SecurityRejection::Challenge {
+                    scheme: #scheme_name,
+                    challenge: #challenge,
+                };
+                let Some((username, password)) = extract_basic_credentials(parts) else {
+                    return Err(rejection.into());
+                };
+                if !state.verify_basic(#scheme_name, &username, &password).await {
+                    return Err(rejection.into());
+                }
+
+                Ok(Self { username, password })
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn compile_http_bearer(
+    ident: Ident,
+    scheme_name: &str,
+    raw: &Value,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<()> {
+    let doc = format!(
+        "Generated from OpenAPI security scheme `{scheme_name}`
+
+The `claims` are produced by the state's `TokenVerifier<C>`."
+    );
+    let challenge = format!("Bearer realm=\"{scheme_name}\"");
+
+    // The claims are described by the `x-claims` schema, or else they are any json object, so
+    // that `JwtVerifier` can decode them
+    let claims_ty = compile_claims(&ident, raw, depth, items)?
+        .unwrap_or_else(|| parse_quote!(::axum_open_api::security::JsonClaims));
+
+    items.push(parse_quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone)]
+        pub struct #ident<C = #claims_ty> {
+            pub token: String,
+            pub claims: C,
+        }
+    });
+    items.push(parse_quote! {
+        #[axum::async_trait]
+        impl<S, C> ::axum::extract::FromRequestParts<S> for #ident<C>
+        where
+            S: ::axum_open_api::security::TokenVerifier<C> + Send + Sync,
+            C: Send + 'static,
+        {
+            type Rejection = ::axum_open_api::Rejection;
+
+            async fn from_request_parts(
+                parts: &mut ::axum::http::request::Parts,
+                state: &S,
+            ) -> Result<Self, Self::Rejection> {
+                use ::axum_open_api::security::{extract_bearer_token, SecurityRejection};
+
+                let rejection = SecurityRejection::Challenge {
+                    scheme: #scheme_name,
+                    challenge: #challenge,
+                };
+                let Some(token) = extract_bearer_token(parts) else {
+                    return Err(rejection.into());
+                };
+                let Some(claims) = state.verify_token(#scheme_name, &token).await else {
+                    return Err(rejection.into());
+                };
+
+                Ok(Self { token, claims })
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn compile_oauth2(ident: Ident, scheme_name: &str, items: &mut Vec<Item>) -> syn::Result<()> {
+    let doc = format!(
+        "Generated from OpenAPI security scheme `{scheme_name}`
+
+The `scopes` are provided by the state's `ScopeSource`."
+    );
+
+    items.push(parse_quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone)]
+        pub struct #ident {
+            pub scopes: Vec<String>,
+        }
+    });
+    items.push(parse_quote! {
+        #[axum::async_trait]
+        impl<S> ::axum::extract::FromRequestParts<S> for #ident
+        where
+            S: ::axum_open_api::security::ScopeSource + Send + Sync,
+        {
+            type Rejection = ::axum_open_api::Rejection;
+
+            async fn from_request_parts(
+                parts: &mut ::axum::http::request::Parts,
+                state: &S,
+            ) -> Result<Self, Self::Rejection> {
+                use ::axum_open_api::security::SecurityRejection;
+
+                let Some(scopes) = state.scopes(#scheme_name, parts).await else {
+                    return Err(SecurityRejection::Missing(#scheme_name).into());
+                };
+
+                Ok(Self { scopes })
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn compile_open_id_connect(
+    ident: Ident,
+    scheme_name: &str,
+    discovery_url: &str,
+    raw: &Value,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<()> {
+    let doc = format!(
+        "Generated from OpenAPI security scheme `{scheme_name}`
+
+The `claims` are produced by the state's `OidcValidator<C>`."
+    );
+
+    // The claims are described by the `x-claims` schema, if it exists
+    let claims_ty = compile_claims(&ident, raw, depth, items)?.unwrap_or_else(|| parse_quote!(()));
+
+    // The issuer is the discovery url, without the well-known suffix
+    let issuer = discovery_url
+        .strip_suffix("/.well-known/openid-configuration")
+        .map(|issuer| quote!(pub const ISSUER: &'static str = #issuer;));
+
+    items.push(parse_quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone)]
+        pub struct #ident<C = #claims_ty> {
+            pub token: String,
+            pub claims: C,
+        }
+    });
+    items.push(parse_quote! {
+        impl #ident {
+            pub const DISCOVERY_URL: &'static str = #discovery_url;
+            #issuer
+        }
+    });
+    items.push(parse_quote! {
+        #[axum::async_trait]
+        impl<S, C> ::axum::extract::FromRequestParts<S> for #ident<C>
+        where
+            S: ::axum_open_api::security::OidcValidator<C> + Send + Sync,
+            C: Send + 'static,
+        {
+            type Rejection = ::axum_open_api::Rejection;
+
+            async fn from_request_parts(
+                parts: &mut ::axum::http::request::Parts,
+                state: &S,
+            ) -> Result<Self, Self::Rejection> {
+                use ::axum_open_api::security::{extract_bearer_token, SecurityRejection};
+
+                let rejection = SecurityRejection::Challenge {
+                    scheme: #scheme_name,
+                    challenge: "Bearer",
+                };
+                let Some(token) = extract_bearer_token(parts) else {
+                    return Err(rejection.into());
+                };
+                let Some(claims) = state.validate(#scheme_name, #discovery_url, &token).await else {
+                    return Err(rejection.into());
+                };
+
+                Ok(Self { token, claims })
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Compiles the `x-claims` schema of a token based scheme into its claims type, like
+/// `OidcClaims`, if the scheme has one.
+fn compile_claims(
+    ident: &Ident,
+    raw: &Value,
+    depth: &Depth,
+    items: &mut Vec<Item>,
+) -> syn::Result<Option<Type>> {
+    let Some(claims) = raw.get("x-claims") else {
+        return Ok(None);
+    };
+    let claims_title = format!("{ident}Claims");
+    compile_schema(claims, Some(&claims_title), depth, items).map(Some)
+}
+
+fn compile_mutual_tls(ident: Ident, scheme_name: &str, items: &mut Vec<Item>) -> syn::Result<()> {
+    let doc = format!(
+        "Generated from OpenAPI security scheme `{scheme_name}`
+
+The `cert` is taken from the `ClientCertInfo` extension, inserted by the TLS acceptor."
+    );
+
+    items.push(parse_quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone)]
+        pub struct #ident {
+            pub cert: ::axum_open_api::security::ClientCertInfo,
+        }
+    });
+    items.push(parse_quote! {
+        #[axum::async_trait]
+        impl<S: Send + Sync> ::axum::extract::FromRequestParts<S> for #ident {
+            type Rejection = ::axum_open_api::Rejection;
+
+            async fn from_request_parts(
+                parts: &mut ::axum::http::request::Parts,
+                _state: &S,
+            ) -> Result<Self, Self::Rejection> {
+                use ::axum_open_api::security::{ClientCertInfo, SecurityRejection};
+
+                let Some(cert) = parts.extensions.get::<ClientCertInfo>().cloned() else {
+                    return Err(SecurityRejection::Missing(#scheme_name).into());
+                };
+
+                Ok(Self { cert })
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::expand_err;
+
+    #[test]
+    fn unsupported_http_scheme() {
+        let spec = r#"
+openapi: 3.0.0
+info:
+  title: Digest
+  version: 0.0.1
+components:
+  securitySchemes:
+    DigestAuth:
+      type: http
+      scheme: digest
+paths: {}
+"#;
+        assert_eq!(
+            expand_err(spec, ""),
+            "Security scheme DigestAuth has an unsupported http scheme: `digest`"
+        );
+    }
+}
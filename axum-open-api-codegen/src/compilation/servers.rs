@@ -0,0 +1,103 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span};
+use serde_json::Value;
+use syn::Item;
+
+/// Compiles the first of the `servers` of the spec. Returns the prefix of the generated axum
+/// paths, which is the base path of the server with `base_path = prefix;`, and is empty
+/// otherwise.
+///
+/// With `base_path = nest;`, a `BASE_PATH` constant and a `nest` function are generated
+/// instead. A `ServerVariables` struct is generated if the server declares variables.
+pub fn compile_servers(
+    root: &Value,
+    base_path: Option<&Ident>,
+    items: &mut Vec<Item>,
+) -> syn::Result<String> {
+    let server = &root["servers"][0];
+    let url = server["url"].as_str().unwrap_or("/");
+    let variables = server["variables"].as_object();
+
+    // The url with the default values of the variables
+    let mut default_url = url.to_owned();
+    for (name, variable) in variables.into_iter().flatten() {
+        let default = variable["default"].as_str().unwrap_or_default();
+        default_url = default_url.replace(&format!("{{{name}}}"), default);
+    }
+    let path = match default_url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("", |start| &rest[start..]),
+        None => default_url.as_str(),
+    };
+    let path = path.trim_end_matches('/').to_owned();
+
+    if let Some(variables) = variables {
+        let fields = variables
+            .keys()
+            .map(|name| Ident::new(&name.to_case(Case::Snake), Span::call_site()))
+            .collect::<Vec<_>>();
+        let names = variables.keys();
+        let docs = variables
+            .values()
+            .map(|variable| variable["description"].as_str().unwrap_or_default());
+        let defaults = variables
+            .values()
+            .map(|variable| variable["default"].as_str().unwrap_or_default());
+        items.push(parse_quote! {
+            /// The variables of the server in the OpenAPI spec
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct ServerVariables {
+                #(
+                    #[doc = #docs]
+                    pub #fields: String,
+                )*
+            }
+        });
+        items.push(parse_quote! {
+            impl Default for ServerVariables {
+                fn default() -> Self {
+                    Self {
+                        #(#fields: #defaults.to_owned(),)*
+                    }
+                }
+            }
+        });
+        items.push(parse_quote! {
+            impl ServerVariables {
+                /// The url of the server, with the variables filled in
+                pub fn url(&self) -> String {
+                    #url.to_owned() #(.replace(concat!("{", #names, "}"), &self.#fields))*
+                }
+            }
+        });
+    }
+
+    let Some(base_path) = base_path else {
+        return Ok(String::new());
+    };
+    match base_path.to_string().as_str() {
+        "prefix" => Ok(path),
+        "nest" => {
+            items.push(parse_quote! {
+                /// The base path of the server in the OpenAPI spec
+                pub const BASE_PATH: &str = #path;
+            });
+            items.push(parse_quote! {
+                /// Nests the router at the base path of the server in the OpenAPI spec
+                pub fn nest<S: Clone + Send + Sync + 'static>(
+                    router: ::axum::Router<S>,
+                ) -> ::axum::Router<S> {
+                    if BASE_PATH.is_empty() {
+                        router
+                    } else {
+                        ::axum::Router::new().nest(BASE_PATH, router)
+                    }
+                }
+            });
+            Ok(String::new())
+        }
+        _ => Err(err!(
+            base_path,
+            "Unknown base path mode, expected `prefix` or `nest`"
+        )),
+    }
+}
@@ -0,0 +1,159 @@
+use super::client::ClientParams;
+use crate::parsing;
+use proc_macro2::{Ident, Span};
+use syn::{GenericArgument, Item, PathArguments, Type};
+
+/// Compiles the request builder of the operation for tests, like
+/// `GetPosts::request().id(3).query_limit(10).build()`, which builds the `Request` that the
+/// operation extracts. It is only compiled with feature `test-helpers` of `axum-open-api`.
+pub fn compile_test_request(
+    method: &parsing::MethodItem,
+    params: ClientParams,
+    serialize_body: bool,
+    items: &mut Vec<Item>,
+) {
+    let ClientParams {
+        path_param_types,
+        query_param_names,
+        query_param_types,
+        extractor,
+        ..
+    } = params;
+    let struct_name = &method.struct_name;
+    let struct_vis = &method.struct_vis;
+    let builder = format_ident!("{struct_name}Request");
+    let http_method = Ident::new(&method.method_ty.to_string(), Span::call_site());
+
+    // Required parameters must be set before the request is built
+    let path_param_idents = method.path.path_param_idents().collect::<Vec<_>>();
+    let path_args = path_param_idents.iter().map(|ident| {
+        let message = format!("path parameter `{ident}` of `{struct_name}` should be set");
+        quote!(&self.#ident.expect(#message))
+    });
+
+    // Optional query parameters are `Option`s, and are set with the type they contain
+    let query_setters = query_param_names
+        .iter()
+        .map(|ident| format_ident!("query_{ident}"));
+    let mut query_tys = Vec::new();
+    let mut query_args = Vec::new();
+    for (ident, ty) in query_param_names.iter().zip(query_param_types) {
+        match option_inner(ty) {
+            Some(inner) => {
+                query_tys.push(inner);
+                query_args.push(quote!(&self.#ident));
+            }
+            None => {
+                let message = format!("query parameter `{ident}` of `{struct_name}` should be set");
+                query_tys.push(ty);
+                query_args.push(quote!(&self.#ident.expect(#message)));
+            }
+        }
+    }
+
+    // Bodies that are serialized get a setter of their type
+    let body_setter = extractor.filter(|_| serialize_body).and_then(|extractor| {
+        let body_ty = &extractor.body_ty;
+        let kind = extractor.rejection_var.segments.last()?.ident.to_string();
+        match kind.as_str() {
+            "Json" => Some(quote! {
+                pub fn json_body(mut self, body: &#body_ty) -> Self {
+                    self.request = self.request.json_body(body);
+                    self
+                }
+            }),
+            "Form" => Some(quote! {
+                pub fn form_body(mut self, body: &#body_ty) -> Self {
+                    self.request = self.request.form_body(body);
+                    self
+                }
+            }),
+            "Text" => Some(quote! {
+                pub fn text_body(mut self, body: impl Into<String>) -> Self {
+                    self.request = self.request.body("text/plain", body.into());
+                    self
+                }
+            }),
+            _ => None,
+        }
+    });
+
+    let request_doc = format!("Builds a request to [`{struct_name}`] for tests.");
+    let builder_doc = format!("The builder of requests to [`{struct_name}`] for tests.");
+    items.push(parse_quote! {
+        ::axum_open_api::test_helpers! {
+            impl #struct_name {
+                #[doc = #request_doc]
+                #struct_vis fn request() -> #builder {
+                    #builder::default()
+                }
+            }
+
+            #[doc = #builder_doc]
+            #[derive(Debug, Default)]
+            #struct_vis struct #builder {
+                #(#path_param_idents: Option<#path_param_types>,)*
+                #(#query_param_names: Option<#query_tys>,)*
+                request: ::axum_open_api::testing::TestRequest,
+            }
+
+            impl #builder {
+                #(
+                    pub fn #path_param_idents(
+                        mut self,
+                        #path_param_idents: impl Into<#path_param_types>,
+                    ) -> Self {
+                        self.#path_param_idents = Some(#path_param_idents.into());
+                        self
+                    }
+                )*
+
+                #(
+                    pub fn #query_setters(
+                        mut self,
+                        #query_param_names: impl Into<#query_tys>,
+                    ) -> Self {
+                        self.#query_param_names = Some(#query_param_names.into());
+                        self
+                    }
+                )*
+
+                pub fn header(mut self, name: &str, value: &str) -> Self {
+                    self.request = self.request.header(name, value);
+                    self
+                }
+
+                pub fn body(
+                    mut self,
+                    content_type: &str,
+                    body: impl Into<::axum::body::Body>,
+                ) -> Self {
+                    self.request = self.request.body(content_type, body);
+                    self
+                }
+
+                #body_setter
+
+                pub fn build(self) -> ::axum::http::Request<::axum::body::Body> {
+                    let uri = #struct_name::uri(#(#path_args,)* #(#query_args,)*);
+                    self.request.build(::axum::http::Method::#http_method, &uri)
+                }
+            }
+        }
+    });
+}
+
+/// The `T` of an `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) if segment.ident == "Option" => Some(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,41 @@
+use crate::codegen;
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span};
+use serde_json::Value;
+
+/// The standard headers that are extracted as a `TypedHeader`, with their type in `headers`.
+/// `Authorization` is missing, since header parameters with that name are ignored by OpenAPI in
+/// favour of the security schemes. So are `If-Match`, `If-None-Match` and `Range`, which are
+/// extracted as `EntityTagMatch` and `ByteRange`.
+const TYPED_HEADERS: [(&str, &str); 9] = [
+    ("Cache-Control", "CacheControl"),
+    ("Cookie", "Cookie"),
+    ("Date", "Date"),
+    ("Host", "Host"),
+    ("If-Modified-Since", "IfModifiedSince"),
+    ("If-Unmodified-Since", "IfUnmodifiedSince"),
+    ("Origin", "Origin"),
+    ("Referer", "Referer"),
+    ("User-Agent", "UserAgent"),
+];
+
+/// Compiles the header parameters of the operation that are standard headers, like
+/// `User-Agent`, for `typed_headers = true;`. They are fields like `user_agent`, which are
+/// optional unless the parameter is required.
+pub fn compile_typed_headers(params: &[&Value]) -> Vec<codegen::TypedHeader> {
+    params
+        .iter()
+        .filter(|param| param["in"] == "header")
+        .filter_map(|param| {
+            let name = param["name"].as_str()?;
+            let (_, header_ty) = TYPED_HEADERS
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))?;
+            Some(codegen::TypedHeader {
+                field_ident: Ident::new(&name.to_case(Case::Snake), Span::call_site()),
+                header_ty: Ident::new(header_ty, Span::call_site()),
+                required: param["required"] == true,
+            })
+        })
+        .collect()
+}
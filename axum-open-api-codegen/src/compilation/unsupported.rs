@@ -0,0 +1,121 @@
+use proc_macro2::Ident;
+use syn::Item;
+
+/// What to do with a feature of the spec that can't be generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Fail the compilation.
+    Deny,
+    /// Fall back, with a warning at the macro.
+    Warn,
+    /// Fall back silently.
+    Allow,
+}
+
+/// The policies of the classes of unsupported features, from
+/// `unsupported = { composition: warn, formats: deny, styles: allow };`.
+#[derive(Debug, Clone, Copy)]
+pub struct Policies {
+    /// `allOf` and `anyOf`, which fall back to `serde_json::Value`
+    pub composition: Policy,
+    /// Unknown `format`s, which fall back to the type without the format
+    pub formats: Policy,
+    /// Parameter `style`s other than the defaults, which fall back to the default style
+    pub styles: Policy,
+}
+
+impl Default for Policies {
+    fn default() -> Self {
+        Self {
+            composition: Policy::Deny,
+            formats: Policy::Allow,
+            styles: Policy::Allow,
+        }
+    }
+}
+
+impl Policies {
+    pub fn from_options(options: &[(Ident, Ident)]) -> syn::Result<Self> {
+        let mut policies = Self::default();
+        for (class, policy) in options {
+            let policy = match policy.to_string().as_str() {
+                "deny" => Policy::Deny,
+                "warn" => Policy::Warn,
+                "allow" => Policy::Allow,
+                _ => {
+                    return Err(err!(
+                        policy,
+                        "Unknown policy, expected `deny`, `warn` or `allow`"
+                    ))
+                }
+            };
+            match class.to_string().as_str() {
+                "composition" => policies.composition = policy,
+                "formats" => policies.formats = policy,
+                "styles" => policies.styles = policy,
+                _ => {
+                    return Err(err!(
+                        class,
+                        "Unknown feature class, expected `composition`, `formats` or `styles`"
+                    ))
+                }
+            }
+        }
+        Ok(policies)
+    }
+}
+
+impl Policy {
+    /// Applies the policy to an unsupported feature: `deny` returns the error, and `warn` adds a
+    /// warning to the items. The caller falls back if this returns `Ok`.
+    pub fn apply(self, message: &str, items: &mut Vec<Item>) -> syn::Result<()> {
+        match self {
+            Policy::Deny => Err(err_call_site!("{message}")),
+            Policy::Warn => {
+                items.push(compile_warning(message));
+                Ok(())
+            }
+            Policy::Allow => Ok(()),
+        }
+    }
+}
+
+/// A warning at the macro, which stable proc macros can only emit as the use of a deprecated
+/// item.
+fn compile_warning(message: &str) -> Item {
+    parse_quote! {
+        const _: () = {
+            #[deprecated(note = #message)]
+            struct Unsupported;
+            let _ = Unsupported;
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snapshot::{expand, expand_err};
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: Pets
+  version: 0.0.1
+components:
+  schemas:
+    Chip:
+      type: string
+      format: rfid
+paths: {}
+"#;
+
+    #[test]
+    fn policies() {
+        // Unknown formats are ignored silently by default
+        assert!(!expand(SPEC, "").contains("#[deprecated"));
+        let expanded = expand(SPEC, "unsupported = { formats: warn };");
+        assert!(expanded.contains("Format `rfid` is not supported, and is ignored"));
+        let error = expand_err(SPEC, "unsupported = { formats: deny };");
+        assert!(error.starts_with("Format `rfid` is not supported"));
+    }
+}
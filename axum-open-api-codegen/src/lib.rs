@@ -4,15 +4,19 @@ extern crate syn;
 extern crate quote;
 #[macro_use]
 mod err;
+mod cache;
 mod codegen;
 mod compilation;
 mod parsing;
+mod quirks;
+#[cfg(test)]
+mod snapshot;
+use cache::load_spec;
 use compilation::Compiler;
 use parsing::Root;
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::ToTokens;
-use std::fs::File;
 
 /// # OpenAPI Codegen
 /// This macro generates code for Axum from an OpenAPI spec.
@@ -21,44 +25,63 @@ use std::fs::File;
 /// - Basic datatypes: string, number, integer, boolean, array, object
 /// - required
 /// - oneOf (enums)
+/// - additionalProperties, patternProperties (maps)
 /// - named components
 /// - path parameters
 /// - MIME extractors: application/json, application/x-www-form-urlencoded, text/*,
-/// multipart/form-data. Everything else is treated as bytes.
+///   multipart/form-data. Everything else is treated as bytes.
 /// - Http methods: POST, GET, PUT, DELETE, PATCH, HEAD, TRACE, OPTIONS
+/// - Security schemes: apiKey, http basic, http bearer, oauth2 (with scope checks), openIdConnect,
+///   mutualTLS
 ///
 /// ## Not supported
-/// - additionalProperties (yet)
 /// - allOf, anyOf
 ///
 /// ## Note
-/// - Anonymous schemas must have a title
+/// - Untitled objects and oneOfs are named after the body, property or item they are in, like
+///   `CreatePostBody`. Those in parameters must have a title
 /// - Requires crate `axum` in path (v0.9 is supported)
 ///
 /// # Example
 /// See crate documentation of `axum-open-api` for examples
 #[proc_macro]
 pub fn validate_routes(item: TokenStream) -> TokenStream {
-    fn _validate_routes(item: Root) -> syn::Result<TokenStream2> {
-        // Working directory of cargo and rust-analyzer is different.
-        // This is a hack to get around that, and have it work with both.
-        let spec_path_str = item.spec_path.value();
-        let file: File = match File::open(&spec_path_str) {
-            Ok(file) => file,
-            Err(_) => File::open(format!("../{spec_path_str}")).map_err(|_| {
-                err!(item.spec_path, "File does not exist at path: {spec_path_str}")
-            })?,
-        };
-        let spec = oas3::from_reader(file)
-            .map_err(|e| err!(item.spec_path, "Could not parse OpenAPI spec: {e}"))?;
-
-        let compiler = Compiler::compile(item, spec)?;
-
-        Ok(compiler.into_token_stream())
-    }
-
-    match _validate_routes(parse_macro_input!(item as Root)) {
+    match expand(parse_macro_input!(item as Root)) {
         Ok(code) => code.into_token_stream().into(),
         Err(e) => e.into_compile_error().into(),
     }
 }
+
+/// Expands to the pretty-printed code that `validate_routes!` generates for the same input, as a
+/// `&'static str`, for snapshot tests of the generated code:
+/// ```ignore
+/// let expanded = axum_open_api::expand_validate_routes!(
+///     path = "tests/test-api.yaml";
+///     GET /users/{user_id} as pub GetUser;
+/// );
+/// insta::assert_snapshot!(expanded);
+/// ```
+///
+/// Proc macro crates can only export macros, so this is a macro rather than a function. Errors
+/// of the expansion are compile errors, like with `validate_routes!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn expand_validate_routes(item: TokenStream) -> TokenStream {
+    let expanded = match expand(parse_macro_input!(item as Root)) {
+        Ok(code) => code,
+        Err(e) => return e.into_compile_error().into(),
+    };
+    let pretty = match syn::parse2::<syn::File>(expanded.clone()) {
+        Ok(file) => prettyplease::unparse(&file),
+        Err(_) => expanded.to_string(),
+    };
+    quote!(#pretty).into()
+}
+
+/// Runs the full pipeline: loading the spec, and compiling the macro into code.
+fn expand(item: Root) -> syn::Result<TokenStream2> {
+    let spec = load_spec(&item.spec_path)?;
+    let compiler = Compiler::compile(item, spec.spec, spec.raw, &spec.contents)?;
+
+    Ok(compiler.into_token_stream())
+}
@@ -11,14 +11,75 @@ use syn::{
 #[derive(Debug)]
 pub struct Root {
     pub spec_path: LitStr,
+    pub options: Options,
     pub items: Vec<Item>,
 }
 
-/// An item; either a [`ModuleItem`] or a [`MethodItem`].
+/// The options following the spec path, like `contract_tests = crate::router;`
+#[derive(Debug, Default)]
+pub struct Options {
+    /// Generate contract tests, driving the router returned by this function.
+    pub contract_tests: Option<syn::Path>,
+    /// Generate a mock router with this name, responding with the examples of the spec.
+    pub mock_router: Option<Ident>,
+    /// Generate a typed http client with this name.
+    pub client: Option<Ident>,
+    /// How the base path of the server is applied: `prefix` or `nest`.
+    pub base_path: Option<Ident>,
+    /// Generate a router with this name, serving the spec.
+    pub serve_spec: Option<Ident>,
+    /// Generate a trait with this name, with a method for every operation.
+    pub api_trait: Option<Ident>,
+    /// Generate a macro with this name, building a router with a handler for every operation.
+    pub build_router: Option<Ident>,
+    /// Generate a macro with this name, adding routes outside of the spec that may not overlap
+    /// its paths.
+    pub validated_router: Option<Ident>,
+    /// Generate a function with this name, adding spec-driven `405` fallbacks to a router.
+    pub method_not_allowed: Option<Ident>,
+    /// Generate a module with this name, with an `ApiError` enum of the error responses.
+    pub errors: Option<Ident>,
+    /// Generate a function with this name, returning the `CorsLayer` of the `x-cors` extension.
+    pub cors_layer: Option<Ident>,
+    /// Check the path parameters against their schemas, rejecting mismatches with this status:
+    /// `not_found` or `unprocessable_entity`.
+    pub invalid_path_params: Option<Ident>,
+    /// Expose the headers of the request as a `headers` field on the generated structs.
+    pub headers: bool,
+    /// Extract the standard header parameters, like `User-Agent`, as their `TypedHeader`.
+    pub typed_headers: bool,
+    /// The visibility and name of the generated `schemas` module.
+    pub schemas: Option<(Visibility, Ident)>,
+    /// Use the `schemas` module generated by another invocation at this path.
+    pub schemas_from: Option<syn::Path>,
+    /// Which schemas of `components/schemas` are generated: `all` or `referenced`.
+    pub components: Option<Ident>,
+    /// Which serde traits the generated types derive: `all` or `usage`.
+    pub derives: Option<Ident>,
+    /// Derive `EnumIter`, `EnumString` and `IntoStaticStr` of `strum` on the generated enums.
+    pub strum: bool,
+    /// Derive `PartialOrd` and `Ord` on the generated enums.
+    pub ord: bool,
+    /// Mark the generated structs and enums `#[non_exhaustive]`.
+    pub non_exhaustive: bool,
+    /// The serde `rename_all` rule of the properties, with snake case fields in rust.
+    pub rename_all: Option<LitStr>,
+    /// What to do with the unsupported features of every class, like `composition: warn`.
+    pub unsupported: Vec<(Ident, Ident)>,
+    /// Write a handler stub to this directory for every operation without a file.
+    pub scaffold_to: Option<LitStr>,
+    /// Write the generated `schemas` module to this file, for a crate of only the models.
+    pub models_to: Option<LitStr>,
+    /// Generate an `OperationService` alias for every operation, like `GetPostsService`.
+    pub services: bool,
+}
+
+/// An item; either a [`ModuleItem`], [`MethodItem`] or [`WebhookItem`].
 #[derive(Debug)]
 pub enum Item {
     Module(ModuleItem),
     Method(MethodItem),
+    Webhook(WebhookItem),
 }
 
 /// A module like `pub mod api { ... }`
@@ -29,13 +90,33 @@ pub struct ModuleItem {
     pub items: Vec<Item>,
 }
 
-/// A method like `GET /api/feed/get_posts as pub GetPosts;`
+/// A method like `GET /api/feed/get_posts as pub GetPosts;`, with flags as attributes like
+/// `#[raw_path]`, `#[borrowed]` or `#[stream]`.
 #[derive(Debug)]
 pub struct MethodItem {
     pub method_ty: MethodType,
     pub path: MethodPath,
     pub struct_vis: Visibility,
     pub struct_name: Ident,
+    /// Expose the undecoded path parameters as `raw_` fields, for `#[raw_path]`
+    pub raw_path: bool,
+    /// Keep the json body as a `BorrowedJson` of a borrowed schema, for `#[borrowed]`
+    pub borrowed: bool,
+    /// Yield the items of the json array body as a `JsonArray` stream, for `#[stream]`
+    pub stream: bool,
+    /// The doc comments above the declaration, which come first in the docs of the struct
+    pub docs: Vec<syn::Attribute>,
+}
+
+/// A webhook like `POST webhook newPet as pub NewPet;`
+#[derive(Debug)]
+pub struct WebhookItem {
+    pub method_ty: MethodType,
+    pub name: Ident,
+    pub struct_vis: Visibility,
+    pub struct_name: Ident,
+    /// The doc comments above the declaration, which come first in the docs of the struct
+    pub docs: Vec<syn::Attribute>,
 }
 
 /// A path like `/api/{id}/test/{name}`
@@ -66,12 +147,23 @@ pub mod kw {
     custom_keyword!(OPTIONS);
     custom_keyword!(TRACE);
     custom_keyword!(path);
+    custom_keyword!(schemas);
+    custom_keyword!(webhook);
 }
 
 impl Parse for Item {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if let Ok(module) = input.parse::<ModuleItem>() {
-            return Ok(Self::Module(module));
+        // Modules are parsed only if they are one, so the errors of methods are not discarded
+        let fork = input.fork();
+        fork.parse::<Visibility>()?;
+        if fork.peek(Mod) {
+            return Ok(Self::Module(input.parse::<ModuleItem>()?));
+        }
+        // The doc comments of a webhook come before its method
+        let fork = input.fork();
+        fork.call(syn::Attribute::parse_outer)?;
+        if fork.peek2(kw::webhook) {
+            return Ok(Self::Webhook(input.parse::<WebhookItem>()?));
         }
         Ok(Self::Method(input.parse::<MethodItem>()?))
     }
@@ -96,6 +188,32 @@ impl Parse for ModuleItem {
 
 impl Parse for MethodItem {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut raw_path = false;
+        let mut borrowed = false;
+        let mut stream = false;
+        let mut docs = Vec::new();
+        for attr in input.call(syn::Attribute::parse_outer)? {
+            match attr.path().get_ident().map(Ident::to_string).as_deref() {
+                Some("doc") => docs.push(attr),
+                Some("raw_path") => raw_path = true,
+                Some("borrowed") if stream => {
+                    let message = "`#[borrowed]` can't be combined with `#[stream]`";
+                    return Err(syn::Error::new_spanned(attr, message));
+                }
+                Some("stream") if borrowed => {
+                    let message = "`#[stream]` can't be combined with `#[borrowed]`";
+                    return Err(syn::Error::new_spanned(attr, message));
+                }
+                Some("borrowed") => borrowed = true,
+                Some("stream") => stream = true,
+                _ => {
+                    let message =
+                        "Unknown attribute, expected `#[raw_path]`, `#[borrowed]` or `#[stream]`";
+                    return Err(syn::Error::new_spanned(attr, message));
+                }
+            }
+        }
+
         let ty = input.parse::<MethodType>()?;
         let path = input.parse::<MethodPath>()?;
 
@@ -109,6 +227,10 @@ impl Parse for MethodItem {
             path,
             struct_vis,
             struct_name: name,
+            raw_path,
+            borrowed,
+            stream,
+            docs,
         })
     }
 }
@@ -121,12 +243,113 @@ impl Parse for Root {
         let spec_path = input.parse::<LitStr>()?;
         input.parse::<Token![;]>()?;
 
+        let options = input.parse::<Options>()?;
+
         let mut items = Vec::new();
-        while let Ok(item) = input.parse::<Item>() {
-            items.push(item);
+        while !input.is_empty() {
+            items.push(input.parse::<Item>()?);
+        }
+
+        Ok(Self {
+            spec_path,
+            options,
+            items,
+        })
+    }
+}
+
+impl Parse for Options {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Self::default();
+
+        // Options look like `name = value;`, or `schemas as pub(crate) models;`
+        loop {
+            if input.peek(kw::schemas) && input.peek2(Token![as]) {
+                input.parse::<kw::schemas>()?;
+                input.parse::<Token![as]>()?;
+                options.schemas = Some((input.parse()?, input.parse()?));
+                input.parse::<Token![;]>()?;
+                continue;
+            }
+            if !(input.peek(syn::Ident) && input.peek2(Token![=])) {
+                break;
+            }
+            let name = input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+            match name.to_string().as_str() {
+                "contract_tests" => options.contract_tests = Some(input.parse()?),
+                "mock_router" => options.mock_router = Some(input.parse()?),
+                "client" => options.client = Some(input.parse()?),
+                "base_path" => options.base_path = Some(input.parse()?),
+                "serve_spec" => options.serve_spec = Some(input.parse()?),
+                "api_trait" => options.api_trait = Some(input.parse()?),
+                "build_router" => options.build_router = Some(input.parse()?),
+                "validated_router" => options.validated_router = Some(input.parse()?),
+                "method_not_allowed" => options.method_not_allowed = Some(input.parse()?),
+                "errors" => options.errors = Some(input.parse()?),
+                "cors_layer" => options.cors_layer = Some(input.parse()?),
+                "invalid_path_params" => options.invalid_path_params = Some(input.parse()?),
+                "headers" => options.headers = input.parse::<syn::LitBool>()?.value,
+                "typed_headers" => options.typed_headers = input.parse::<syn::LitBool>()?.value,
+                "components" => options.components = Some(input.parse()?),
+                "derives" => options.derives = Some(input.parse()?),
+                "strum" => options.strum = input.parse::<syn::LitBool>()?.value,
+                "ord" => options.ord = input.parse::<syn::LitBool>()?.value,
+                "non_exhaustive" => options.non_exhaustive = input.parse::<syn::LitBool>()?.value,
+                "rename_all" => options.rename_all = Some(input.parse()?),
+                "unsupported" => options.unsupported = parse_policies(input)?,
+                "scaffold_to" => options.scaffold_to = Some(input.parse()?),
+                "models_to" => options.models_to = Some(input.parse()?),
+                "schemas_from" => options.schemas_from = Some(input.parse()?),
+                "services" => options.services = input.parse::<syn::LitBool>()?.value,
+                _ => return Err(syn::Error::new(name.span(), "Unknown option")),
+            }
+            input.parse::<Token![;]>()?;
         }
 
-        Ok(Self { spec_path, items })
+        Ok(options)
+    }
+}
+
+/// Parses the policies of `unsupported = { composition: warn, formats: deny };`.
+fn parse_policies(input: ParseStream) -> syn::Result<Vec<(Ident, Ident)>> {
+    let inner;
+    braced!(inner in input);
+    let mut policies = Vec::new();
+    while !inner.is_empty() {
+        let class = inner.parse::<Ident>()?;
+        inner.parse::<Token![:]>()?;
+        policies.push((class, inner.parse::<Ident>()?));
+        if !inner.is_empty() {
+            inner.parse::<Token![,]>()?;
+        }
+    }
+    Ok(policies)
+}
+
+impl Parse for WebhookItem {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let docs = input.call(syn::Attribute::parse_outer)?;
+        if let Some(attr) = docs.iter().find(|attr| !attr.path().is_ident("doc")) {
+            let message = "Unknown attribute, webhooks only take doc comments";
+            return Err(syn::Error::new_spanned(attr, message));
+        }
+        let method_ty = input.parse::<MethodType>()?;
+        input.parse::<kw::webhook>()?;
+        let name = input.parse::<Ident>()?;
+
+        input.parse::<As>()?;
+        let struct_vis = input.parse::<Visibility>()?;
+        let struct_name = input.parse::<Ident>()?;
+        input.parse::<Token![;]>()?;
+
+        Ok(Self {
+            method_ty,
+            name,
+            struct_vis,
+            struct_name,
+            docs,
+        })
     }
 }
 
@@ -197,3 +420,30 @@ impl MethodPath {
         self.0.first().unwrap().0.span()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Root;
+
+    fn parse_err(body: &str) -> String {
+        let input = format!("path = \"openapi.yaml\"; {body}");
+        syn::parse_str::<Root>(&input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn errors_of_items() {
+        assert_eq!(
+            parse_err("GET /users as GetUsers; #[cached] GET /posts as GetPosts;"),
+            "Unknown attribute, expected `#[raw_path]`, `#[borrowed]` or `#[stream]`"
+        );
+        assert_eq!(parse_err("GET /users GetUsers;"), "expected `as`");
+    }
+
+    #[test]
+    fn errors_in_modules() {
+        assert_eq!(
+            parse_err("pub mod users { GET /users as GetUsers }"),
+            "expected `;`"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Normalizes the quirks of specs that are generated from code, like by `utoipa` or `aide`, into
+//! the forms the compilation supports.
+
+use serde_json::Value;
+
+/// Normalizes every schema of the spec, recursively:
+/// - `type: [string, "null"]` becomes `type: string`, as nullable values are optional fields.
+/// - `oneOf` or `anyOf` of a schema and `type: "null"` becomes the schema.
+/// - `allOf` of a single schema becomes the schema, as generators use it to document a `$ref`.
+pub fn normalize(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::Array(types)) = object.get_mut("type") {
+                types.retain(|ty| ty != "null");
+                if types.len() == 1 {
+                    let ty = types.remove(0);
+                    object.insert("type".to_owned(), ty);
+                }
+            }
+            for keyword in ["oneOf", "anyOf", "allOf"] {
+                let Some(Value::Array(schemas)) = object.get_mut(keyword) else {
+                    continue;
+                };
+                if keyword != "allOf" {
+                    schemas.retain(|schema| schema["type"] != "null");
+                }
+                if schemas.len() != 1 || !schemas[0].is_object() {
+                    continue;
+                }
+                let Value::Object(schema) = schemas.remove(0) else {
+                    continue;
+                };
+                object.remove(keyword);
+                // The keywords next to the composition, like `description`, are kept
+                for (key, value) in schema {
+                    object.entry(key).or_insert(value);
+                }
+            }
+            object.values_mut().for_each(normalize);
+        }
+        Value::Array(values) => values.iter_mut().for_each(normalize),
+        _ => {}
+    }
+}
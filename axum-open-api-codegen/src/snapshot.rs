@@ -0,0 +1,102 @@
+//! Expands the macro from strings, for snapshot tests of the generated code and tests of its
+//! errors. A proc macro crate can only export macros, so other crates use
+//! `expand_validate_routes!` instead.
+
+use crate::{compilation::Compiler, parsing::Root, quirks::normalize};
+use quote::ToTokens;
+
+/// Expands the macro for a spec and the body of the macro after its `path = "...";`, into the
+/// pretty-printed code. Panics if the expansion fails.
+pub fn expand(spec: &str, body: &str) -> String {
+    match try_expand(spec, body) {
+        Ok(code) => code,
+        Err(e) => panic!("Expansion failed: {e}"),
+    }
+}
+
+/// Expands the macro like [`expand`], into the message of its error. Panics if the expansion
+/// succeeds.
+pub fn expand_err(spec: &str, body: &str) -> String {
+    match try_expand(spec, body) {
+        Ok(_) => panic!("Expansion succeeded"),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn try_expand(spec: &str, body: &str) -> syn::Result<String> {
+    let root = syn::parse_str::<Root>(&format!("path = \"openapi.yaml\"; {body}"))?;
+    let mut raw: serde_json::Value = serde_yaml::from_str(spec)
+        .map_err(|e| err_call_site!("Could not parse OpenAPI spec: {e}"))?;
+    normalize(&mut raw);
+    let parsed = serde_json::from_value(raw.clone())
+        .map_err(|e| err_call_site!("Could not parse OpenAPI spec: {e}"))?;
+
+    let code = Compiler::compile(root, parsed, raw, spec)?.into_token_stream();
+    Ok(prettyplease::unparse(&syn::parse2(code)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: Pets
+  version: 0.0.1
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [id, name]
+      properties:
+        id:
+          type: integer
+        name:
+          type: string
+        tag:
+          type: string
+paths:
+  /pets/{pet_id}:
+    get:
+      operationId: getPet
+      parameters:
+        - in: path
+          name: pet_id
+          required: true
+          schema:
+            type: integer
+        - in: query
+          name: verbose
+          schema:
+            type: boolean
+      responses:
+        '200':
+          description: The pet
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pet'
+        '404':
+          description: No pet with the id
+"#;
+
+    #[test]
+    fn schemas_snapshot() {
+        insta::assert_snapshot!(expand(SPEC, ""));
+    }
+
+    #[test]
+    fn operation_snapshot() {
+        insta::assert_snapshot!(expand(
+            SPEC,
+            "pub mod pets { GET /pets/{pet_id} as pub GetPet; }"
+        ));
+    }
+
+    #[test]
+    fn unknown_option() {
+        let message = expand_err(SPEC, "pets = true;");
+        assert_eq!(message, "Unknown option");
+    }
+}
@@ -0,0 +1,146 @@
+//! Runtime support for the json bodies of operations declared with `#[borrowed]`, which are
+//! deserialized into a borrowed form of their schema, like `UserRef<'_>` for `User`. Its strings
+//! borrow from the body, so endpoints that only inspect a few fields don't allocate them:
+//! ```ignore
+//! async fn track_event(req: TrackEvent) -> StatusCode {
+//!     let event = req.body.get();
+//!     if event.kind == "click" {
+//!         store_event(event.into_owned()).await;
+//!     }
+//!     StatusCode::ACCEPTED
+//! }
+//! ```
+
+use crate::{
+    json::{invalid_json, is_json, missing_json_content_type},
+    Rejection,
+};
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+/// A schema with a borrowed form, which is generated for the bodies of `#[borrowed]` operations.
+pub trait Borrowed {
+    /// The schema with its strings as `Cow<'a, str>`, which has an `into_owned()`
+    type Ref<'a>: Deserialize<'a>;
+}
+
+/// A json body that is kept as bytes, and deserialized into the borrowed form of `T` with
+/// [`BorrowedJson::get`]. It is checked when it is extracted, so invalid bodies are still
+/// rejected before the handler, like with `Json`.
+pub struct BorrowedJson<T> {
+    body: Bytes,
+    _body: PhantomData<fn() -> T>,
+}
+
+impl<T: Borrowed> BorrowedJson<T> {
+    /// Deserializes the body, borrowing its strings unless they contain escapes.
+    pub fn get(&self) -> T::Ref<'_> {
+        serde_json::from_slice(&self.body).expect("the body should be checked when extracted")
+    }
+
+    /// The body as it was received.
+    pub fn bytes(&self) -> &Bytes {
+        &self.body
+    }
+}
+
+impl<T> Clone for BorrowedJson<T> {
+    fn clone(&self) -> Self {
+        Self {
+            body: self.body.clone(),
+            _body: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for BorrowedJson<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BorrowedJson").field(&self.body).finish()
+    }
+}
+
+#[axum::async_trait]
+impl<T: Borrowed, S: Send + Sync> FromRequest<S> for BorrowedJson<T> {
+    type Rejection = Rejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_json(req.headers()) {
+            return Err(missing_json_content_type());
+        }
+        let body = Bytes::from_request(req, state).await?;
+        if let Err(e) = serde_json::from_slice::<T::Ref<'_>>(&body) {
+            return Err(invalid_json(e));
+        }
+        Ok(Self {
+            body,
+            _body: PhantomData,
+        })
+    }
+}
+
+/// Deserializes a string field of a borrowed schema, borrowing it if it has no escapes. Serde
+/// only borrows a `Cow` with `#[serde(borrow)]` when it is not in an `Option`, so the fields use
+/// these with `deserialize_with` instead.
+pub fn borrow_str<'de: 'a, 'a, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Cow<'a, str>, D::Error> {
+    deserializer.deserialize_str(CowStrVisitor)
+}
+
+/// Like [`borrow_str`], for optional string fields.
+pub fn borrow_optional_str<'de: 'a, 'a, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Cow<'a, str>>, D::Error> {
+    deserializer.deserialize_option(OptionalCowStrVisitor)
+}
+
+struct CowStrVisitor;
+
+impl<'de> Visitor<'de> for CowStrVisitor {
+    type Value = Cow<'de, str>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, value: &'de str) -> Result<Self::Value, E> {
+        Ok(Cow::Borrowed(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(value.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(value))
+    }
+}
+
+struct OptionalCowStrVisitor;
+
+impl<'de> Visitor<'de> for OptionalCowStrVisitor {
+    type Value = Option<Cow<'de, str>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string or null")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        borrow_str(deserializer).map(Some)
+    }
+}
@@ -0,0 +1,414 @@
+//! Breaking changes between two versions of a spec, enabled with feature `spec`. Deploys can be
+//! gated on contract compatibility with [`breaking_changes`]:
+//! ```ignore
+//! let old = SpecDocument::parse(&fs::read_to_string("deployed/my-api.yaml")?)?;
+//! let new = SpecDocument::parse(&fs::read_to_string("my-api.yaml")?)?;
+//! assert_eq!(breaking_changes(&old, &new), vec![]);
+//! ```
+
+use crate::spec::{Operation, SpecDocument};
+use serde_json::Value;
+use std::fmt;
+
+/// The maximum depth of nested schemas that are compared, to prevent cycles.
+const MAX_SCHEMA_DEPTH: usize = 32;
+
+/// A change of the spec that breaks existing clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakingChange {
+    /// The operation that changed, like `GET /users/{user_id}`.
+    pub operation: String,
+    /// What changed, like `request body: property `name` is newly required`.
+    pub message: String,
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.operation, self.message)
+    }
+}
+
+/// Whether a schema is received or sent by the API, which decides whether narrowing or widening
+/// it breaks clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Request,
+    Response,
+}
+
+/// Compares the operations of the new spec with those of the old one, and returns the changes
+/// that break clients of the old one:
+/// - Removed operations and responses.
+/// - New required parameters, properties and request bodies.
+/// - Narrowed types and enums in requests, and widened types and enums in responses.
+/// - Properties of responses that are removed or no longer required.
+pub fn breaking_changes(old: &SpecDocument, new: &SpecDocument) -> Vec<BreakingChange> {
+    let specs = Specs { old, new };
+    let mut changes = Vec::new();
+    for old_operation in old.operations() {
+        let name = format!(
+            "{} {}",
+            old_operation.method.to_uppercase(),
+            old_operation.path_template
+        );
+        let new_operation = new.operations().find(|operation| {
+            operation.method == old_operation.method
+                && operation.path_template == old_operation.path_template
+        });
+        let mut messages = Vec::new();
+        match new_operation {
+            Some(new_operation) => {
+                specs.compare_operations(old_operation, new_operation, &mut messages)
+            }
+            None => messages.push("operation removed".to_owned()),
+        }
+        changes.extend(messages.into_iter().map(|message| BreakingChange {
+            operation: name.clone(),
+            message,
+        }));
+    }
+    changes
+}
+
+/// The old and the new spec, to resolve the `$ref`s of their schemas.
+#[derive(Clone, Copy)]
+struct Specs<'a> {
+    old: &'a SpecDocument,
+    new: &'a SpecDocument,
+}
+
+impl Specs<'_> {
+    fn compare_operations(&self, old: Operation, new: Operation, messages: &mut Vec<String>) {
+        // Parameters
+        let new_parameters = self.new.parameters(new);
+        let old_parameters = self.old.parameters(old);
+        for new_parameter in &new_parameters {
+            let name = new_parameter["name"].as_str().unwrap_or_default();
+            let location = new_parameter["in"].as_str().unwrap_or_default();
+            let at = format!("{location} parameter `{name}`");
+            let old_parameter = old_parameters.iter().find(|old_parameter| {
+                old_parameter["name"] == new_parameter["name"]
+                    && old_parameter["in"] == new_parameter["in"]
+            });
+            let required = new_parameter["required"] == true;
+            match old_parameter {
+                None if required => messages.push(format!("{at}: new required parameter")),
+                None => {}
+                Some(old_parameter) => {
+                    if required && old_parameter["required"] != true {
+                        messages.push(format!("{at}: is newly required"));
+                    }
+                    self.compare_schemas(
+                        &old_parameter["schema"],
+                        &new_parameter["schema"],
+                        &at,
+                        Direction::Request,
+                        0,
+                        messages,
+                    );
+                }
+            }
+        }
+
+        // Request body
+        let old_body = self.old.resolve(&old.value["requestBody"]);
+        let new_body = self.new.resolve(&new.value["requestBody"]);
+        if new_body["required"] == true && old_body["required"] != true {
+            messages.push("request body: is newly required".to_owned());
+        }
+        self.compare_schemas(
+            &old_body["content"]["application/json"]["schema"],
+            &new_body["content"]["application/json"]["schema"],
+            "request body",
+            Direction::Request,
+            0,
+            messages,
+        );
+
+        // Responses
+        for (status, old_response) in old.value["responses"].as_object().into_iter().flatten() {
+            let Some(new_response) = new.value["responses"].get(status) else {
+                messages.push(format!("response {status}: removed"));
+                continue;
+            };
+            let old_response = self.old.resolve(old_response);
+            let new_response = self.new.resolve(new_response);
+            self.compare_schemas(
+                &old_response["content"]["application/json"]["schema"],
+                &new_response["content"]["application/json"]["schema"],
+                &format!("response {status}"),
+                Direction::Response,
+                0,
+                messages,
+            );
+        }
+    }
+
+    fn compare_schemas(
+        &self,
+        old: &Value,
+        new: &Value,
+        at: &str,
+        direction: Direction,
+        depth: usize,
+        messages: &mut Vec<String>,
+    ) {
+        let old = self.old.resolve(old);
+        let new = self.new.resolve(new);
+        if old.is_null() || new.is_null() || depth > MAX_SCHEMA_DEPTH {
+            return;
+        }
+
+        // Requests may not accept less than before, and responses may not return more
+        let (narrow, wide) = match direction {
+            Direction::Request => (new, old),
+            Direction::Response => (old, new),
+        };
+        // Integers are numbers, so `integer` can become `number` in requests
+        let narrow_types = types(narrow);
+        let accepted = |ty: &&str| {
+            narrow_types.contains(ty) || (*ty == "integer" && narrow_types.contains(&"number"))
+        };
+        if !narrow_types.is_empty() && !types(wide).iter().all(accepted) {
+            messages.push(format!(
+                "{at}: type changed from {} to {}",
+                types(old).join(" or "),
+                types(new).join(" or ")
+            ));
+        }
+        let (narrow_values, wide_values) = (narrow["enum"].as_array(), wide["enum"].as_array());
+        if let (Some(narrow_values), Some(wide_values)) = (narrow_values, wide_values) {
+            let change = match direction {
+                Direction::Request => "removed",
+                Direction::Response => "added",
+            };
+            for value in wide_values
+                .iter()
+                .filter(|value| !narrow_values.contains(value))
+            {
+                messages.push(format!("{at}: enum value {value} {change}"));
+            }
+        }
+
+        // Requests may not require more properties, and responses may not return fewer
+        let required = |schema: &Value| -> Vec<String> {
+            schema["required"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_owned)
+                .collect()
+        };
+        let (old_required, new_required) = (required(old), required(new));
+        match direction {
+            Direction::Request => {
+                for property in new_required.iter().filter(|p| !old_required.contains(p)) {
+                    messages.push(format!("{at}: property `{property}` is newly required"));
+                }
+            }
+            Direction::Response => {
+                for property in old_required.iter().filter(|p| !new_required.contains(p)) {
+                    match new["properties"].get(property) {
+                        Some(_) => messages
+                            .push(format!("{at}: property `{property}` is no longer required")),
+                        None => messages.push(format!("{at}: property `{property}` removed")),
+                    }
+                }
+            }
+        }
+
+        for (name, old_property) in old["properties"].as_object().into_iter().flatten() {
+            if let Some(new_property) = new["properties"].get(name) {
+                let property_at = format!("{at}.{name}");
+                self.compare_schemas(
+                    old_property,
+                    new_property,
+                    &property_at,
+                    direction,
+                    depth + 1,
+                    messages,
+                );
+            }
+        }
+        if let (Some(old_items), Some(new_items)) = (old.get("items"), new.get("items")) {
+            let items_at = format!("{at}[]");
+            self.compare_schemas(
+                old_items,
+                new_items,
+                &items_at,
+                direction,
+                depth + 1,
+                messages,
+            );
+        }
+    }
+}
+
+/// The types of the schema, which can be a list in 3.1.
+fn types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = r#"
+openapi: 3.0.0
+paths:
+  /users:
+    get:
+      parameters:
+        - name: role
+          in: query
+          schema:
+            $ref: '#/components/schemas/Role'
+      responses:
+        '200':
+          description: The users
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: '#/components/schemas/User'
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/User'
+      responses:
+        '201':
+          description: Created
+components:
+  schemas:
+    Role:
+      type: string
+      enum: [admin, member]
+    User:
+      type: object
+      required: [name]
+      properties:
+        name:
+          type: string
+        age:
+          type: integer
+"#;
+
+    /// The breaking changes from [`OLD`] to the spec with the replacements.
+    fn changes_to(replacements: &[(&str, &str)]) -> Vec<String> {
+        let new = replacements
+            .iter()
+            .fold(OLD.to_owned(), |spec, (from, to)| {
+                assert!(spec.contains(from), "{from}");
+                spec.replacen(from, to, 1)
+            });
+        let old = SpecDocument::parse(OLD).unwrap();
+        let new = SpecDocument::parse(&new).unwrap();
+        breaking_changes(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn unchanged() {
+        assert_eq!(changes_to(&[]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn non_breaking() {
+        let changes = changes_to(&[
+            // A new optional parameter
+            (
+                "      parameters:\n",
+                "      parameters:\n        - name: limit\n          in: query\n",
+            ),
+            // A widened request enum
+            ("enum: [admin, member]", "enum: [admin, member, guest]"),
+            // A new optional property
+            (
+                "      properties:\n",
+                "      properties:\n        email:\n          type: string\n",
+            ),
+            // A new operation
+            (
+                "components:",
+                "  /users/{user_id}:\n    delete:\n      responses:\n        '204':\n          \
+                 description: Deleted\ncomponents:",
+            ),
+        ]);
+        assert_eq!(changes, Vec::<String>::new());
+    }
+
+    #[test]
+    fn removed_operation() {
+        let changes = changes_to(&[("    post:", "    put:")]);
+        assert_eq!(changes, ["POST /users: operation removed"]);
+    }
+
+    #[test]
+    fn newly_required_parameters() {
+        let changes = changes_to(&[(
+            "          in: query\n",
+            "          in: query\n          required: true\n",
+        )]);
+        assert_eq!(
+            changes,
+            ["GET /users: query parameter `role`: is newly required"]
+        );
+
+        let changes = changes_to(&[(
+            "      parameters:\n",
+            "      parameters:\n        - name: X-Tenant\n          in: header\n          \
+             required: true\n",
+        )]);
+        assert_eq!(
+            changes,
+            ["GET /users: header parameter `X-Tenant`: new required parameter"]
+        );
+    }
+
+    #[test]
+    fn narrowed_enum() {
+        let changes = changes_to(&[("enum: [admin, member]", "enum: [admin]")]);
+        assert_eq!(
+            changes,
+            ["GET /users: query parameter `role`: enum value \"member\" removed"]
+        );
+    }
+
+    #[test]
+    fn request_and_response_properties() {
+        let changes = changes_to(&[("required: [name]", "required: [age]")]);
+        assert_eq!(
+            changes,
+            [
+                "GET /users: response 200[]: property `name` is no longer required",
+                "POST /users: request body: property `age` is newly required",
+            ]
+        );
+    }
+
+    #[test]
+    fn widened_response_type() {
+        // An integer property can become a number in the request, but not in the response
+        let changes = changes_to(&[("          type: integer", "          type: number")]);
+        assert_eq!(
+            changes,
+            ["GET /users: response 200[].age: type changed from integer to number"]
+        );
+    }
+
+    #[test]
+    fn removed_response() {
+        let changes = changes_to(&[("        '201':", "        '200':")]);
+        assert_eq!(changes, ["POST /users: response 201: removed"]);
+    }
+}
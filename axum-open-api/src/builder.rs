@@ -0,0 +1,138 @@
+//! A router builder that checks at runtime that every operation of the spec was registered.
+
+use crate::{nest::OapiNested, MatchedOperation, OapiPath, OapiRouter, OapiWebhook, OperationMeta};
+use axum::{extract::Request, handler::Handler, response::IntoResponse, routing::Route, Router};
+use std::{convert::Infallible, fmt};
+use tower::{Layer, Service};
+
+/// Builds a [`Router`] like [`OapiRouter`], recording the registered operations. For routers
+/// that are assembled across modules, where a forgotten operation is not a compile error:
+/// ```ignore
+/// let router = OapiRouterBuilder::new(OperationId::ALL.iter().map(OperationId::as_str))
+///     .oapi_route(get_user)
+///     .oapi_route(add_user)
+///     .build()?;
+/// ```
+#[derive(Debug)]
+pub struct OapiRouterBuilder<S = ()> {
+    router: Router<S>,
+    expected: Vec<&'static str>,
+    registered: Vec<MatchedOperation>,
+}
+
+impl<S: Clone + Send + Sync + 'static> OapiRouterBuilder<S> {
+    /// Creates a builder expecting the operations with these names: the operationId, or
+    /// `METHOD /path` if the operation has none, like `OperationId::as_str` returns.
+    pub fn new(expected: impl IntoIterator<Item = &'static str>) -> Self {
+        Self {
+            router: Router::new(),
+            expected: expected.into_iter().collect(),
+            registered: Vec::new(),
+        }
+    }
+
+    /// Applies `f` to the router, for routes and layers that are not operations of the spec.
+    pub fn map_router(mut self, f: impl FnOnce(Router<S>) -> Router<S>) -> Self {
+        self.router = f(self.router);
+        self
+    }
+
+    /// The registered operations, in order of registration.
+    pub fn registered(&self) -> &[MatchedOperation] {
+        &self.registered
+    }
+
+    /// Returns the router, or the expected operations that were never registered.
+    pub fn build(self) -> Result<Router<S>, MissingOperations> {
+        let registered = self.registered.iter().map(name).collect::<Vec<_>>();
+        let missing = self
+            .expected
+            .into_iter()
+            .filter(|expected| !registered.iter().any(|name| name == expected))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            Ok(self.router)
+        } else {
+            Err(MissingOperations(missing))
+        }
+    }
+}
+
+impl<S: Clone + Send + Sync + 'static> OapiRouter for OapiRouterBuilder<S> {
+    type State = S;
+
+    fn oapi_route<H, T>(mut self, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + OapiPath + OperationMeta,
+    {
+        self.registered.push(MatchedOperation::of::<T>());
+        self.router = self.router.oapi_route(handler);
+        self
+    }
+
+    fn oapi_route_with_layer<H, T, L>(mut self, handler: H, layer: L) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + OapiPath + OperationMeta,
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.registered.push(MatchedOperation::of::<T>());
+        self.router = self.router.oapi_route_with_layer(handler, layer);
+        self
+    }
+
+    fn oapi_route_service<T, Svc>(mut self, service: Svc) -> Self
+    where
+        T: 'static + OapiPath + OperationMeta,
+        Svc: Service<Request, Error = Infallible> + Clone + Send + 'static,
+        Svc::Response: IntoResponse + 'static,
+        Svc::Future: Send + 'static,
+    {
+        self.registered.push(MatchedOperation::of::<T>());
+        self.router = self.router.oapi_route_service::<T, Svc>(service);
+        self
+    }
+
+    fn oapi_webhook<H, T>(mut self, path: &str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + OapiWebhook + OperationMeta,
+    {
+        self.registered.push(MatchedOperation::of::<T>());
+        self.router = self.router.oapi_webhook(path, handler);
+        self
+    }
+
+    fn oapi_nest(mut self, prefix: &str, f: impl FnOnce(OapiNested<S>) -> OapiNested<S>) -> Self {
+        let nested = f(OapiNested::new(prefix));
+        self.registered.extend(nested.operations);
+        self.registered.extend(nested.webhooks);
+        self.router = self.router.nest(prefix, nested.router);
+        self
+    }
+}
+
+/// The name of the operation: the operationId, or `METHOD /path`.
+fn name(operation: &MatchedOperation) -> String {
+    match operation.operation_id {
+        Some(operation_id) => operation_id.to_owned(),
+        None => format!("{} {}", operation.method, operation.path_template),
+    }
+}
+
+/// The operations that were expected by an [`OapiRouterBuilder`], but never registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingOperations(pub Vec<&'static str>);
+
+impl fmt::Display for MissingOperations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "operations without a handler: {}", self.0.join(", "))
+    }
+}
+
+impl std::error::Error for MissingOperations {}
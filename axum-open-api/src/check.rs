@@ -0,0 +1,84 @@
+//! Values that are checked after deserialization, for the keywords of the spec that serde can't
+//! express, like `not`.
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::Deref,
+};
+
+/// A check of a value, generated for a schema, like `UsernameCheck`.
+pub trait Check<T> {
+    /// Returns why the value does not conform to the schema, if it doesn't.
+    fn check(value: &T) -> Result<(), String>;
+}
+
+/// A value of type `T` that passed the check `C`. Deserializing fails if the check fails, so the
+/// value is rejected before reaching the handler.
+pub struct Checked<T, C>(T, PhantomData<fn() -> C>);
+
+impl<T, C: Check<T>> Checked<T, C> {
+    /// Checks the value.
+    pub fn new(value: T) -> Result<Self, String> {
+        C::check(&value)?;
+        Ok(Self(value, PhantomData))
+    }
+}
+
+impl<T, C> Checked<T, C> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, C> Deref for Checked<T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for Checked<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: Clone, C> Clone for Checked<T, C> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<T: PartialEq, C> PartialEq for Checked<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Eq, C> Eq for Checked<T, C> {}
+
+impl<T: Hash, C> Hash for Checked<T, C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<T: Serialize, C> Serialize for Checked<T, C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T, C> Deserialize<'de> for Checked<T, C>
+where
+    T: Deserialize<'de>,
+    C: Check<T>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::new(T::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
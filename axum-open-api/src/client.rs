@@ -0,0 +1,4 @@
+//! Runtime support for the http clients generated with the `client` option of
+//! [`validate_routes!`](crate::validate_routes), enabled with feature `client`.
+
+pub use reqwest;
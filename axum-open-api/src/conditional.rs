@@ -0,0 +1,336 @@
+//! Runtime support for operations with `If-Match` or `If-None-Match` header parameters, which
+//! are extracted as [`EntityTagMatch`] fields and checked with the generated
+//! `check_preconditions` of the operation:
+//! ```ignore
+//! async fn update_post(req: UpdatePost) -> Result<(EntityTag, Json<Post>), Response> {
+//!     let post = load_post(&req.post_id).await;
+//!     req.check_preconditions(Some(&post.etag()))?;
+//!     ...
+//! }
+//! ```
+
+use crate::Rejection;
+use axum::{
+    http::{header, request::Parts, HeaderName, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
+};
+use std::{convert::Infallible, fmt, str::FromStr};
+
+/// An entity tag, like `"v2"` or the weak `W/"v2"`, which identifies a version of a resource.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EntityTag {
+    pub weak: bool,
+    /// The tag, without the quotes
+    pub tag: String,
+}
+
+impl EntityTag {
+    pub fn strong(tag: impl Into<String>) -> Self {
+        Self {
+            weak: false,
+            tag: tag.into(),
+        }
+    }
+
+    pub fn weak(tag: impl Into<String>) -> Self {
+        Self {
+            weak: true,
+            tag: tag.into(),
+        }
+    }
+
+    /// Whether both tags are strong and equal, as compared for `If-Match`.
+    pub fn strong_eq(&self, other: &EntityTag) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Whether the tags are equal, weak or not, as compared for `If-None-Match`.
+    pub fn weak_eq(&self, other: &EntityTag) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl fmt::Display for EntityTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.weak {
+            true => write!(f, "W/\"{}\"", self.tag),
+            false => write!(f, "\"{}\"", self.tag),
+        }
+    }
+}
+
+impl FromStr for EntityTag {
+    type Err = InvalidEntityTag;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (weak, quoted) = match value.strip_prefix("W/") {
+            Some(quoted) => (true, quoted),
+            None => (false, value),
+        };
+        let tag = quoted
+            .strip_prefix('"')
+            .and_then(|quoted| quoted.strip_suffix('"'))
+            .filter(|tag| !tag.contains('"'))
+            .ok_or(InvalidEntityTag)?;
+        Ok(Self {
+            weak,
+            tag: tag.to_owned(),
+        })
+    }
+}
+
+/// Sets the `ETag` header of the response, like `(etag, Json(post))`.
+impl IntoResponseParts for EntityTag {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        if let Ok(value) = HeaderValue::from_str(&self.to_string()) {
+            res.headers_mut().insert(header::ETAG, value);
+        }
+        Ok(res)
+    }
+}
+
+/// The value of an `If-Match` or `If-None-Match` header: `*`, or a list of entity tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityTagMatch {
+    Any,
+    Tags(Vec<EntityTag>),
+}
+
+impl EntityTagMatch {
+    /// Whether the header matches the current tag of the resource, which is `None` if it does
+    /// not exist. `If-Match` compares strongly, `If-None-Match` weakly.
+    fn matches(&self, current: Option<&EntityTag>, strong: bool) -> bool {
+        match (self, current) {
+            (_, None) => false,
+            (EntityTagMatch::Any, Some(_)) => true,
+            (EntityTagMatch::Tags(tags), Some(current)) => tags.iter().any(|tag| match strong {
+                true => tag.strong_eq(current),
+                false => tag.weak_eq(current),
+            }),
+        }
+    }
+}
+
+impl FromStr for EntityTagMatch {
+    type Err = InvalidEntityTag;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.trim() == "*" {
+            return Ok(EntityTagMatch::Any);
+        }
+        let tags = value
+            .split(',')
+            .map(|tag| tag.trim().parse())
+            .collect::<Result<_, _>>()?;
+        Ok(EntityTagMatch::Tags(tags))
+    }
+}
+
+/// An entity tag header that could not be parsed, which is rejected with `400 Bad Request`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidEntityTag;
+
+impl fmt::Display for InvalidEntityTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Invalid entity tag")
+    }
+}
+
+impl IntoResponse for InvalidEntityTag {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+    }
+}
+
+/// Extracts the `If-Match` or `If-None-Match` header, if the request has one.
+pub fn entity_tag_match(
+    parts: &Parts,
+    name: HeaderName,
+) -> Result<Option<EntityTagMatch>, Rejection> {
+    let Some(value) = parts.headers.get(name) else {
+        return Ok(None);
+    };
+    let value = value.to_str().map_err(|_| InvalidEntityTag);
+    value
+        .and_then(str::parse)
+        .map(Some)
+        .map_err(|e| Rejection::Other(Box::new(e)))
+}
+
+/// Evaluates the preconditions of a request against the current tag of the resource, which is
+/// `None` if it does not exist. A failed `If-Match` is `412 Precondition Failed`, and a matching
+/// `If-None-Match` is `304 Not Modified` for `GET` and `HEAD`, or else `412` as well.
+pub fn check_preconditions(
+    method: Method,
+    if_match: Option<&EntityTagMatch>,
+    if_none_match: Option<&EntityTagMatch>,
+    current: Option<&EntityTag>,
+) -> Result<(), FailedPrecondition> {
+    if if_match.is_some_and(|if_match| !if_match.matches(current, true)) {
+        return Err(FailedPrecondition::new(
+            StatusCode::PRECONDITION_FAILED.into_response(),
+        ));
+    }
+    if if_none_match.is_some_and(|if_none_match| if_none_match.matches(current, false)) {
+        if method == Method::GET || method == Method::HEAD {
+            let not_modified = (StatusCode::NOT_MODIFIED, current.cloned(), ()).into_response();
+            return Err(FailedPrecondition::new(not_modified));
+        }
+        return Err(FailedPrecondition::new(
+            StatusCode::PRECONDITION_FAILED.into_response(),
+        ));
+    }
+    Ok(())
+}
+
+/// A failed precondition, which is the `304 Not Modified` or `412 Precondition Failed` response
+/// to return. It converts into a `Response`, so handlers returning `Result<_, Response>` can use
+/// `?` on [`check_preconditions`].
+#[derive(Debug)]
+pub struct FailedPrecondition(Box<Response>);
+
+impl FailedPrecondition {
+    fn new(response: Response) -> Self {
+        Self(Box::new(response))
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.0.status()
+    }
+}
+
+impl IntoResponse for FailedPrecondition {
+    fn into_response(self) -> Response {
+        *self.0
+    }
+}
+
+impl From<FailedPrecondition> for Response {
+    fn from(failed: FailedPrecondition) -> Self {
+        *failed.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(value: &str) -> EntityTagMatch {
+        value.parse().unwrap()
+    }
+
+    fn status(
+        method: Method,
+        if_match: Option<&str>,
+        if_none_match: Option<&str>,
+        current: Option<&EntityTag>,
+    ) -> StatusCode {
+        let (if_match, if_none_match) = (if_match.map(tags), if_none_match.map(tags));
+        match check_preconditions(method, if_match.as_ref(), if_none_match.as_ref(), current) {
+            Ok(()) => StatusCode::OK,
+            Err(failed) => failed.status(),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("\"v1\"".parse(), Ok(EntityTag::strong("v1")));
+        assert_eq!("W/\"v1\"".parse(), Ok(EntityTag::weak("v1")));
+        assert_eq!("\"\"".parse(), Ok(EntityTag::strong("")));
+        assert_eq!("v1".parse::<EntityTag>(), Err(InvalidEntityTag));
+        assert_eq!("\"v\"1\"".parse::<EntityTag>(), Err(InvalidEntityTag));
+        assert_eq!(EntityTag::weak("v1").to_string(), "W/\"v1\"");
+
+        assert_eq!(tags(" * "), EntityTagMatch::Any);
+        assert_eq!(
+            tags("\"v1\", W/\"v2\",\"v3\""),
+            EntityTagMatch::Tags(vec![
+                EntityTag::strong("v1"),
+                EntityTag::weak("v2"),
+                EntityTag::strong("v3"),
+            ])
+        );
+        assert_eq!("\"v1\", *".parse::<EntityTagMatch>(), Err(InvalidEntityTag));
+    }
+
+    #[test]
+    fn comparison() {
+        let (strong, weak) = (EntityTag::strong("v1"), EntityTag::weak("v1"));
+        assert!(strong.strong_eq(&strong));
+        assert!(!strong.strong_eq(&weak));
+        assert!(!weak.strong_eq(&weak));
+        assert!(strong.weak_eq(&weak));
+        assert!(weak.weak_eq(&weak));
+        assert!(!strong.weak_eq(&EntityTag::strong("v2")));
+    }
+
+    #[test]
+    fn if_match() {
+        let current = EntityTag::strong("v1");
+        let put = |if_match, current| status(Method::PUT, Some(if_match), None, current);
+        assert_eq!(put("\"v1\"", Some(&current)), StatusCode::OK);
+        assert_eq!(put("\"v0\", \"v1\"", Some(&current)), StatusCode::OK);
+        assert_eq!(put("*", Some(&current)), StatusCode::OK);
+        assert_eq!(
+            put("\"v2\"", Some(&current)),
+            StatusCode::PRECONDITION_FAILED
+        );
+        // `If-Match` compares strongly, so a weak tag never matches
+        assert_eq!(
+            put("W/\"v1\"", Some(&current)),
+            StatusCode::PRECONDITION_FAILED
+        );
+        let weak = EntityTag::weak("v1");
+        assert_eq!(put("\"v1\"", Some(&weak)), StatusCode::PRECONDITION_FAILED);
+        // `*` does not match a resource that does not exist
+        assert_eq!(put("*", None), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[test]
+    fn if_none_match() {
+        let current = EntityTag::strong("v1");
+        let weak = EntityTag::weak("v1");
+        let check =
+            |method, if_none_match, current| status(method, None, Some(if_none_match), current);
+        assert_eq!(check(Method::GET, "\"v2\"", Some(&current)), StatusCode::OK);
+        assert_eq!(
+            check(Method::GET, "\"v1\"", Some(&current)),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            check(Method::HEAD, "W/\"v1\"", Some(&current)),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            check(Method::GET, "\"v0\", \"v1\"", Some(&weak)),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            check(Method::GET, "*", Some(&current)),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            check(Method::PUT, "\"v1\"", Some(&current)),
+            StatusCode::PRECONDITION_FAILED
+        );
+        assert_eq!(
+            check(Method::POST, "*", Some(&current)),
+            StatusCode::PRECONDITION_FAILED
+        );
+        // `*` lets a resource be created only if it does not exist
+        assert_eq!(check(Method::PUT, "*", None), StatusCode::OK);
+    }
+
+    #[test]
+    fn not_modified_has_etag() {
+        let current = EntityTag::strong("v1");
+        let if_none_match = tags("\"v1\"");
+        let failed = check_preconditions(Method::GET, None, Some(&if_none_match), Some(&current))
+            .unwrap_err();
+        let response = failed.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], "\"v1\"");
+    }
+}
@@ -0,0 +1,4 @@
+//! Runtime support for the CORS layers generated with the `cors_layer` option of
+//! [`validate_routes!`](crate::validate_routes), enabled with feature `cors`.
+
+pub use tower_http::cors::{Any, CorsLayer};
@@ -0,0 +1,65 @@
+//! Interactive documentation of the spec, enabled with feature `docs`.
+
+use axum::{http::header::CONTENT_TYPE, response::Html, routing::get, Router};
+
+/// The interactive documentation served by [`docs_router`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocsUi {
+    SwaggerUi,
+    Redoc,
+}
+
+impl DocsUi {
+    /// The html page, loading the ui from a cdn and pointing it at `spec_url`.
+    fn html(self, spec_url: &str) -> String {
+        match self {
+            DocsUi::SwaggerUi => format!(
+                r##"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>API documentation</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>SwaggerUIBundle({{ url: "{spec_url}", dom_id: "#swagger-ui" }});</script>
+</body>
+</html>"##
+            ),
+            DocsUi::Redoc => format!(
+                r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>API documentation</title>
+</head>
+<body>
+  <redoc spec-url="{spec_url}"></redoc>
+  <script src="https://cdn.redoc.ly/redoc/latest/bundles/redoc.standalone.js"></script>
+</body>
+</html>"#
+            ),
+        }
+    }
+}
+
+/// A router serving the documentation at `/docs`, and the spec at `/docs/openapi.json`.
+///
+/// The spec is usually the `OPENAPI_SPEC_JSON` constant generated by
+/// [`validate_routes!`](crate::validate_routes).
+pub fn docs_router<S>(ui: DocsUi, spec_json: &'static str) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // The url is relative to the page at `/docs`, so it still points at the spec when the router
+    // is nested under a prefix
+    let html = Html(ui.html("docs/openapi.json"));
+    Router::new()
+        .route("/docs", get(move || async move { html }))
+        .route(
+            "/docs/openapi.json",
+            get(move || async move { ([(CONTENT_TYPE, "application/json")], spec_json) }),
+        )
+}
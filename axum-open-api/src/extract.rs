@@ -0,0 +1,375 @@
+//! Helpers for the extractors of the generated structs, so every operation expands to a few
+//! calls instead of the full extraction logic.
+
+use crate::Rejection;
+use axum::{
+    extract::{rejection::PathRejection, FromRequestParts, MatchedPath, OriginalUri, Path, Query},
+    http::{request::Parts, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use serde::{
+    de::{
+        self,
+        value::{Error, SeqDeserializer},
+        DeserializeOwned, IntoDeserializer, Visitor,
+    },
+    ser, Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// Extracts the path parameters, in the order of the path, like `(String, i64)` for
+/// `/users/{user_id}/posts/{post_id}`.
+pub async fn path_params<T>(parts: &mut Parts) -> Result<T, Rejection>
+where
+    T: DeserializeOwned + Send,
+{
+    let Path(params) = Path::<T>::from_request_parts(parts, &()).await?;
+    Ok(params)
+}
+
+/// The value of a path parameter as it is in the uri, without percent-decoding, like `a%2Fb`
+/// for a key containing a `/`. Declarations with `#[raw_path]` get one for every path parameter.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RawPathParam(pub String);
+
+impl RawPathParam {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for RawPathParam {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Display for RawPathParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Extracts the undecoded values of the path parameters with these names, from the segments of
+/// the original uri at the parameters of the matched path. `RawPathParams` of axum decodes them.
+pub fn raw_path_params<const N: usize>(parts: &Parts, names: [&str; N]) -> [RawPathParam; N] {
+    let template = parts
+        .extensions
+        .get::<MatchedPath>()
+        .map_or("", MatchedPath::as_str);
+    let uri = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map_or(&parts.uri, |uri| &uri.0);
+    let segments = template
+        .split('/')
+        .zip(uri.path().split('/'))
+        .filter_map(|(template, segment)| Some((template.strip_prefix(':')?, segment)));
+    names.map(|name| {
+        let value = segments.clone().find(|(key, _)| *key == name);
+        RawPathParam(value.map(|(_, value)| value.to_owned()).unwrap_or_default())
+    })
+}
+
+/// A parameter with `content: application/json`, whose value is json encoded, like a filter in
+/// the query as `?filter={"status":"active"}`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct JsonParam<T>(pub T);
+
+impl<T> Deref for JsonParam<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for JsonParam<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for JsonParam<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        serde_json::from_str(&value)
+            .map(JsonParam)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl<T: Serialize> Serialize for JsonParam<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = serde_json::to_string(&self.0).map_err(ser::Error::custom)?;
+        serializer.serialize_str(&value)
+    }
+}
+
+/// The json of the value, for path parameters.
+impl<T: Serialize> fmt::Display for JsonParam<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = serde_json::to_string(&self.0).map_err(|_| fmt::Error)?;
+        f.write_str(&value)
+    }
+}
+
+/// A path parameter with an `array` schema, whose items are separated by commas, like `1,2,3`,
+/// which `Path` can't deserialize on its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ArrayParam<T>(pub T);
+
+impl<T> Deref for ArrayParam<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ArrayParam<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for ArrayParam<T> {
+    fn from(value: T) -> Self {
+        ArrayParam(value)
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for ArrayParam<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        let items = value
+            .split(',')
+            .filter(|_| !value.is_empty())
+            .map(ParamDeserializer);
+        T::deserialize(SeqDeserializer::<_, Error>::new(items))
+            .map(ArrayParam)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl<T> Serialize for ArrayParam<T>
+where
+    for<'a> &'a T: IntoIterator,
+    for<'a> <&'a T as IntoIterator>::Item: fmt::Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// The items separated by commas, for path parameters.
+impl<T> fmt::Display for ArrayParam<T>
+where
+    for<'a> &'a T: IntoIterator,
+    for<'a> <&'a T as IntoIterator>::Item: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in (&self.0).into_iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{item}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the query parameters into the struct that is generated for them.
+pub fn query_params<T: DeserializeOwned>(parts: &Parts) -> Result<T, Rejection> {
+    let Query(params) = Query::<T>::try_from_uri(&parts.uri)?;
+    Ok(params)
+}
+
+/// How the query parameters with `allowEmptyValue` or `allowReserved` are read, by their names.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QueryOptions {
+    /// Parameters with `allowEmptyValue` that are absent when empty, like `?page=`
+    pub absent_if_empty: &'static [&'static str],
+    /// Boolean parameters with `allowEmptyValue` that are `true` when empty, like `?verbose`
+    pub true_if_empty: &'static [&'static str],
+    /// Parameters with `allowReserved`, in which a `+` is a plus instead of a space
+    pub reserved: &'static [&'static str],
+}
+
+/// Extracts the query parameters like [`query_params`], after rewriting the empty and reserved
+/// values of the [`QueryOptions`].
+pub fn query_params_with<T: DeserializeOwned>(
+    parts: &Parts,
+    options: &QueryOptions,
+) -> Result<T, Rejection> {
+    let query = parts.uri.query().unwrap_or_default();
+    let pairs = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match value.is_empty() {
+                true if options.absent_if_empty.contains(&name) => None,
+                true if options.true_if_empty.contains(&name) => Some(format!("{name}=true")),
+                _ if options.reserved.contains(&name) => {
+                    Some(format!("{name}={}", value.replace('+', "%2B")))
+                }
+                _ => Some(pair.to_owned()),
+            }
+        });
+    let query = pairs.collect::<Vec<_>>().join("&");
+    let uri = format!("/?{query}").parse::<Uri>().unwrap_or_default();
+    let Query(params) = Query::<T>::try_from_uri(&uri)?;
+    Ok(params)
+}
+
+/// Responds to path parameters that could not be deserialized into their types, like values
+/// outside of an `enum`, with `status`, for `invalid_path_params = not_found;`. Other rejections,
+/// like a route without the parameters, are kept.
+pub fn invalid_path_params(rejection: Rejection, status: StatusCode) -> Rejection {
+    match rejection {
+        Rejection::Path(PathRejection::FailedToDeserializePathParams(e)) => {
+            Rejection::Other(Box::new((status, e.body_text())))
+        }
+        rejection => rejection,
+    }
+}
+
+/// Extracts a path parameter with `style: label`, whose segment is prefixed with a `.`, like
+/// `.5` for `/posts/{post_id}`. Invalid segments are rejected with `status`.
+pub fn label_param<T: DeserializeOwned>(
+    segment: &str,
+    name: &'static str,
+    status: StatusCode,
+) -> Result<T, Rejection> {
+    let value = segment.strip_prefix('.').ok_or_else(|| {
+        InvalidPathParam::rejection(name, "expected a `.` prefix".to_owned(), status)
+    })?;
+    styled_param(value, name, status)
+}
+
+/// Extracts a path parameter with `style: matrix`, whose segment is prefixed with its name, like
+/// `;post_id=5` for `/posts/{post_id}`. Invalid segments are rejected with `status`.
+pub fn matrix_param<T: DeserializeOwned>(
+    segment: &str,
+    name: &'static str,
+    status: StatusCode,
+) -> Result<T, Rejection> {
+    let value = segment
+        .strip_prefix(';')
+        .and_then(|pair| pair.strip_prefix(name))
+        .and_then(|pair| pair.strip_prefix('='))
+        .ok_or_else(|| {
+            InvalidPathParam::rejection(name, format!("expected a `;{name}=` prefix"), status)
+        })?;
+    styled_param(value, name, status)
+}
+
+fn styled_param<T: DeserializeOwned>(
+    value: &str,
+    name: &'static str,
+    status: StatusCode,
+) -> Result<T, Rejection> {
+    T::deserialize(ParamDeserializer(value))
+        .map_err(|e| InvalidPathParam::rejection(name, e.to_string(), status))
+}
+
+/// A path parameter with a `label` or `matrix` style that could not be parsed, or a value that
+/// does not match its `pattern`. It is rejected with `400 Bad Request`, or the status of
+/// `invalid_path_params`.
+#[derive(Debug, Clone)]
+pub struct InvalidPathParam {
+    pub name: &'static str,
+    pub reason: String,
+    pub status: StatusCode,
+}
+
+impl InvalidPathParam {
+    pub fn rejection(name: &'static str, reason: String, status: StatusCode) -> Rejection {
+        Rejection::Other(Box::new(Self {
+            name,
+            reason,
+            status,
+        }))
+    }
+}
+
+impl IntoResponse for InvalidPathParam {
+    fn into_response(self) -> Response {
+        let message = format!("Invalid path parameter `{}`: {}", self.name, self.reason);
+        (self.status, message).into_response()
+    }
+}
+
+/// Deserializes the value of a path segment, parsing numbers and booleans like `Path` does.
+struct ParamDeserializer<'a>(&'a str);
+
+macro_rules! parse_value {
+    ($($deserialize:ident => $visit:ident,)*) => {$(
+        fn $deserialize<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            let value = self.0.parse().map_err(de::Error::custom)?;
+            visitor.$visit(value)
+        }
+    )*};
+}
+
+impl<'de> IntoDeserializer<'de, Error> for ParamDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ParamDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.0)
+    }
+
+    parse_value! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(self.0))
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
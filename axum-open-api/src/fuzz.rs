@@ -0,0 +1,118 @@
+//! Runtime support for the `cargo-fuzz` targets of `cargo oapi fuzz`, which feed arbitrary
+//! requests through the extractor of an operation, to catch panics in its deserialization and
+//! validation:
+//! ```ignore
+//! fuzz_target!(|data: &[u8]| {
+//!     axum_open_api::fuzz::extract::<GetUser, _>(data);
+//! });
+//! ```
+
+use crate::{service::OperationService, uri::encode_path_param, OapiPath, OperationMeta};
+use axum::{
+    body::Body,
+    extract::FromRequest,
+    http::{HeaderName, HeaderValue, Request},
+    response::Response,
+};
+use std::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+use tower::ServiceExt;
+
+/// Sends the request of the fuzzer input to the service of the operation `T`, with a handler
+/// that only extracts it. The response is `None` if the input is not a valid request.
+///
+/// The input is split into lines, which are:
+/// - the values of the path parameters, separated by `/`
+/// - the query string
+/// - the headers, like `content-type: application/json`, up to an empty line
+///
+/// and the rest is the body. Invalid header names and values are skipped.
+///
+/// `M` is inferred, and distinguishes the extractors with and without a body.
+pub fn extract<T, M>(data: &[u8]) -> Option<Response>
+where
+    T: FromRequest<(), M> + OapiPath + OperationMeta + Send + 'static,
+    M: Send + 'static,
+{
+    extract_with_state::<T, M, ()>((), data)
+}
+
+/// Like [`extract`], for the operations whose extractors need the state of the router.
+pub fn extract_with_state<T, M, S>(state: S, data: &[u8]) -> Option<Response>
+where
+    T: FromRequest<S, M> + OapiPath + OperationMeta + Send + 'static,
+    M: Send + 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    let request = fuzz_request::<T>(data)?;
+    let service = OperationService::<T>::with_state(|_: T| async {}, state);
+    match block_on(service.oneshot(request)) {
+        Ok(response) => Some(response),
+        Err(infallible) => match infallible {},
+    }
+}
+
+/// Builds the request of the fuzzer input for the operation `T`, see [`extract`].
+fn fuzz_request<T: OapiPath + OperationMeta>(data: &[u8]) -> Option<Request<Body>> {
+    let (values, rest) = split_line(data);
+    let (query, mut rest) = split_line(rest);
+
+    // The parameters of the axum path, like `:user_id` and `*rest`, get the fuzzed values
+    let mut values = values.split(|byte| *byte == b'/');
+    let mut uri = String::new();
+    for segment in T::path().split('/').skip(1) {
+        uri.push('/');
+        match segment.starts_with([':', '*']) {
+            true => {
+                let value = String::from_utf8_lossy(values.next().unwrap_or_default());
+                uri.push_str(&encode_path_param(&value));
+            }
+            false => uri.push_str(segment),
+        }
+    }
+    if !query.is_empty() {
+        uri.push('?');
+        uri.push_str(&String::from_utf8_lossy(query));
+    }
+
+    let mut request = Request::builder().method(T::method()).uri(uri);
+    while !rest.is_empty() {
+        let (line, tail) = split_line(rest);
+        rest = tail;
+        if line.is_empty() {
+            break;
+        }
+        let Some(colon) = line.iter().position(|byte| *byte == b':') else {
+            continue;
+        };
+        let name = HeaderName::from_bytes(&line[..colon]);
+        let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii());
+        if let (Ok(name), Ok(value)) = (name, value) {
+            request = request.header(name, value);
+        }
+    }
+    request.body(Body::from(rest.to_vec())).ok()
+}
+
+/// Splits the first line off the bytes, without its `\n`.
+fn split_line(data: &[u8]) -> (&[u8], &[u8]) {
+    match data.iter().position(|byte| *byte == b'\n') {
+        Some(end) => (&data[..end], &data[end + 1..]),
+        None => (data, &[]),
+    }
+}
+
+/// Runs the future to completion on the current thread. The extractors don't wait on anything
+/// but the body, which is already in memory, so the future is polled without a runtime.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
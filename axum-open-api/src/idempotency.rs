@@ -0,0 +1,99 @@
+//! Runtime support for operations with an `Idempotency-Key` header parameter, whose generated
+//! extractors consult the [`IdempotencyStore`] of the router state before the handler is called.
+
+use axum::{
+    body::Bytes,
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::ops::Deref;
+
+/// The name of the header, which is matched case-insensitively like every header.
+pub const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// The value of the `Idempotency-Key` header of a request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey(pub String);
+
+impl Deref for IdempotencyKey {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// What a store knows about an idempotency key.
+#[derive(Debug, Clone)]
+pub enum IdempotencyStatus {
+    /// The key was not used before, so the request is handled.
+    New,
+    /// A request with the key is still being handled, so this one is rejected with
+    /// `409 Conflict`.
+    InProgress,
+    /// A request with the key was completed with this response, which is sent again instead of
+    /// calling the handler.
+    Completed(StatusCode, Bytes),
+}
+
+/// Tracks the idempotency keys of requests, for the generated extractors of operations with an
+/// `Idempotency-Key` header parameter.
+///
+/// This trait must be implemented by the router state. The handler records the response of a
+/// completed request, so it can be returned by later calls to [`begin`](Self::begin).
+#[axum::async_trait]
+pub trait IdempotencyStore {
+    /// Marks `key` as in progress for the operation, named by its operationId or as
+    /// `METHOD /path`, and returns what was known about it before.
+    async fn begin(&self, operation: &'static str, key: &str) -> IdempotencyStatus;
+}
+
+/// A rejection of a request with an idempotency key.
+#[derive(Debug)]
+pub enum IdempotencyRejection {
+    /// The header of a required key was missing, or was not valid text.
+    Missing,
+    /// A request with the same key is still being handled.
+    InProgress,
+    /// A request with the same key was completed with this response.
+    Completed(StatusCode, Bytes),
+}
+
+impl IntoResponse for IdempotencyRejection {
+    fn into_response(self) -> Response {
+        match self {
+            IdempotencyRejection::Missing => {
+                (StatusCode::BAD_REQUEST, "Missing `Idempotency-Key` header").into_response()
+            }
+            IdempotencyRejection::InProgress => (
+                StatusCode::CONFLICT,
+                "A request with this `Idempotency-Key` is in progress",
+            )
+                .into_response(),
+            IdempotencyRejection::Completed(status, body) => (status, body).into_response(),
+        }
+    }
+}
+
+/// Extracts the idempotency key of the request, and checks it with the store. Requests whose
+/// key is in progress or completed are rejected.
+pub async fn idempotency_key<S>(
+    parts: &Parts,
+    store: &S,
+    operation: &'static str,
+) -> Result<Option<IdempotencyKey>, IdempotencyRejection>
+where
+    S: IdempotencyStore + Sync,
+{
+    let Some(key) = parts.headers.get(IDEMPOTENCY_KEY) else {
+        return Ok(None);
+    };
+    let key = key.to_str().map_err(|_| IdempotencyRejection::Missing)?;
+    match store.begin(operation, key).await {
+        IdempotencyStatus::New => Ok(Some(IdempotencyKey(key.to_owned()))),
+        IdempotencyStatus::InProgress => Err(IdempotencyRejection::InProgress),
+        IdempotencyStatus::Completed(status, body) => {
+            Err(IdempotencyRejection::Completed(status, body))
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! The json body extractor of the generated structs. It is `axum::Json`, unless the `simd-json`
+//! feature is enabled, in which case bodies of at least [`SIMD_JSON_MIN_LEN`] bytes are parsed
+//! with `simd-json`. Smaller bodies, and bodies that `simd-json` rejects, are parsed by
+//! `axum::Json`, so the rejections are the same `Rejection::Json` with and without the feature.
+
+use crate::Rejection;
+use axum::{
+    extract::{FromRequest, Request},
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+
+/// The length from which a body is parsed with `simd-json`, with the `simd-json` feature. Its
+/// setup costs more than it saves on smaller bodies.
+pub const SIMD_JSON_MIN_LEN: usize = 1024;
+
+/// A json body, like `axum::Json`. See the module docs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+#[axum::async_trait]
+impl<T: DeserializeOwned, S: Send + Sync> FromRequest<S> for Json<T> {
+    type Rejection = Rejection;
+
+    #[cfg(not(feature = "simd-json"))]
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let axum::Json(value) = axum::Json::from_request(req, state).await?;
+        Ok(Self(value))
+    }
+
+    #[cfg(feature = "simd-json")]
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_json(req.headers()) {
+            // Rejected by `axum::Json` with its `MissingJsonContentType`
+            let axum::Json(value) = axum::Json::from_request(req, state).await?;
+            return Ok(Self(value));
+        }
+        let body = axum::body::Bytes::from_request(req, state)
+            .await
+            .map_err(axum::extract::rejection::JsonRejection::from)?;
+        if body.len() >= SIMD_JSON_MIN_LEN {
+            // `simd-json` parses in place, so it gets a copy of the body
+            if let Ok(value) = simd_json::serde::from_slice(&mut body.to_vec()) {
+                return Ok(Self(value));
+            }
+        }
+        let axum::Json(value) = axum::Json::from_bytes(&body)?;
+        Ok(Self(value))
+    }
+}
+
+/// Whether the content type is json, like `application/json` or `application/problem+json`.
+pub(crate) fn is_json(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    let Some((kind, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+    kind.eq_ignore_ascii_case("application")
+        && (subtype.eq_ignore_ascii_case("json") || subtype.to_lowercase().ends_with("+json"))
+}
+
+/// The rejection of a json body without a json content type.
+pub(crate) fn missing_json_content_type() -> Rejection {
+    Rejection::Other(Box::new(InvalidJsonBody {
+        status: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        message: "Expected request with `Content-Type: application/json`".to_owned(),
+    }))
+}
+
+/// The rejection of a json body that `serde_json` failed to parse. Bodies that don't match the
+/// schema are `422 Unprocessable Entity`, and other errors are `400 Bad Request`, like with
+/// `axum::Json`.
+pub(crate) fn invalid_json(e: serde_json::Error) -> Rejection {
+    let (status, message) = match e.classify() {
+        serde_json::error::Category::Data => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Failed to deserialize the JSON body into the target type: {e}"),
+        ),
+        _ => (
+            StatusCode::BAD_REQUEST,
+            format!("Failed to parse the request body as JSON: {e}"),
+        ),
+    };
+    Rejection::Other(Box::new(InvalidJsonBody { status, message }))
+}
+
+/// A json body that is not json, or does not match its schema, when it is not parsed by
+/// `axum::Json`.
+#[derive(Debug, Clone)]
+pub struct InvalidJsonBody {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl IntoResponse for InvalidJsonBody {
+    fn into_response(self) -> Response {
+        (self.status, self.message).into_response()
+    }
+}
@@ -1,25 +1,134 @@
 #![doc = include_str!("../../README.md")]
 
 use axum::{
-    extract::rejection::{
-        BytesRejection, FormRejection, JsonRejection, PathRejection, QueryRejection,
-        StringRejection,
+    extract::{
+        rejection::{
+            BytesRejection, FormRejection, JsonRejection, PathRejection, QueryRejection,
+            StringRejection,
+        },
+        Request,
     },
     handler::Handler,
+    http::{Method, StatusCode},
     response::{IntoResponse, Response},
-    routing::MethodRouter,
-    Router,
+    routing::{MethodFilter, MethodRouter, Route},
+    Extension, Router,
 };
+use idempotency::IdempotencyRejection;
+use nest::OapiNested;
+use security::SecurityRejection;
+use std::convert::Infallible;
+use tower::{Layer, Service};
 
+#[doc(hidden)]
+pub use axum_open_api_codegen::expand_validate_routes;
 pub use axum_open_api_codegen::validate_routes;
 
+pub mod borrowed;
+#[cfg(feature = "spec")]
+pub mod breaking;
+pub mod builder;
+pub mod check;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod conditional;
+#[cfg(feature = "cors")]
+pub mod cors;
+#[cfg(feature = "docs")]
+pub mod docs;
+pub mod extract;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod idempotency;
+pub mod json;
+pub mod map;
+pub mod metrics;
+pub mod nest;
+#[cfg(feature = "pattern")]
+pub mod pattern;
+pub mod range;
+pub mod rate_limit;
+pub mod redirect;
+pub mod security;
+pub mod service;
+#[cfg(feature = "spec")]
+pub mod spec;
+pub mod stream;
+pub mod testing;
+pub mod trace;
+#[cfg(feature = "typed-header")]
+pub mod typed_header;
+pub mod uri;
+#[cfg(feature = "validation")]
+pub mod validation;
+
 pub trait OapiRouter {
     type State: Clone + Send + Sync + 'static;
 
+    /// Routes the handler at the path of its [`OapiPath`] extractor. The request gets a
+    /// [`MatchedOperation`] extension before the handler is invoked.
+    ///
+    /// Operations on the same path, like `GET /users` and `POST /users`, are merged into one
+    /// route, each with its own [`MatchedOperation`]. Routing the same operation twice panics.
     fn oapi_route<H, T>(self, handler: H) -> Self
     where
         H: Handler<T, Self::State>,
-        T: 'static + OapiPath;
+        T: 'static + OapiPath + OperationMeta;
+
+    /// Like [`OapiRouter::oapi_route`], but fails to compile if the handler does not return
+    /// one of the responses documented in the spec, see [`ResponseFor`]. The handler must be a
+    /// function, which returns a concrete type rather than `impl IntoResponse` or `Response`.
+    fn oapi_route_checked<H, T>(self, handler: H) -> Self
+    where
+        Self: Sized,
+        H: Handler<T, Self::State> + HandlerOutput<T>,
+        H::Output: ResponseFor<H::Extractor>,
+        T: 'static + OapiPath + OperationMeta,
+    {
+        self.oapi_route(handler)
+    }
+
+    /// Like [`OapiRouter::oapi_route`], with a layer around the handler, like a timeout or a
+    /// body limit for this operation only. The layer sees the [`MatchedOperation`] extension.
+    fn oapi_route_with_layer<H, T, L>(self, handler: H, layer: L) -> Self
+    where
+        H: Handler<T, Self::State>,
+        T: 'static + OapiPath + OperationMeta,
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static;
+
+    /// Routes a service at the path and method of the operation `T`, like
+    /// `router.oapi_route_service::<GetUser, _>(service)`. Unlike handlers, the responses of
+    /// the service are not checked against the spec.
+    fn oapi_route_service<T, Svc>(self, service: Svc) -> Self
+    where
+        T: 'static + OapiPath + OperationMeta,
+        Svc: Service<Request, Error = Infallible> + Clone + Send + 'static,
+        Svc::Response: IntoResponse + 'static,
+        Svc::Future: Send + 'static;
+
+    /// Routes the handler of a webhook at the given path. The request gets a
+    /// [`MatchedOperation`] extension before the handler is invoked.
+    fn oapi_webhook<H, T>(self, path: &str, handler: H) -> Self
+    where
+        H: Handler<T, Self::State>,
+        T: 'static + OapiWebhook + OperationMeta;
+
+    /// Nests the operations routed by `f` at the prefix, so a large api can be split into
+    /// routers per domain:
+    /// ```ignore
+    /// router.oapi_nest("/api/feed", |feed| feed.oapi_route(get_posts).oapi_route(add_tag))
+    /// ```
+    /// The operations are routed at their path without the prefix. Panics if the path of an
+    /// operation does not start with the prefix.
+    fn oapi_nest(
+        self,
+        prefix: &str,
+        f: impl FnOnce(OapiNested<Self::State>) -> OapiNested<Self::State>,
+    ) -> Self;
 }
 
 impl<S: Send + Sync + Clone + 'static> OapiRouter for Router<S> {
@@ -28,19 +137,366 @@ impl<S: Send + Sync + Clone + 'static> OapiRouter for Router<S> {
     fn oapi_route<H, T>(self, handler: H) -> Self
     where
         H: Handler<T, S>,
-        T: 'static + OapiPath,
+        T: 'static + OapiPath + OperationMeta,
     {
-        self.route(T::path(), T::method_router(handler))
+        // `route` merges the method router with the method routers already at the path
+        let operation = MatchedOperation::of::<T>();
+        self.route(
+            T::path(),
+            operation_layers(operation, T::method_router(handler)),
+        )
     }
+
+    fn oapi_route_with_layer<H, T, L>(self, handler: H, layer: L) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + OapiPath + OperationMeta,
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        let method_router = T::method_router(handler).layer(layer);
+        let operation = MatchedOperation::of::<T>();
+        self.route(T::path(), operation_layers(operation, method_router))
+    }
+
+    fn oapi_route_service<T, Svc>(self, service: Svc) -> Self
+    where
+        T: 'static + OapiPath + OperationMeta,
+        Svc: Service<Request, Error = Infallible> + Clone + Send + 'static,
+        Svc::Response: IntoResponse + 'static,
+        Svc::Future: Send + 'static,
+    {
+        let filter = MethodFilter::try_from(T::method()).expect("the method should be routable");
+        let method_router = axum::routing::on_service(filter, service);
+        let operation = MatchedOperation::of::<T>();
+        self.route(T::path(), operation_layers(operation, method_router))
+    }
+
+    fn oapi_webhook<H, T>(self, path: &str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + OapiWebhook + OperationMeta,
+    {
+        let operation = MatchedOperation::of::<T>();
+        self.route(path, T::method_router(handler).layer(Extension(operation)))
+    }
+
+    fn oapi_nest(self, prefix: &str, f: impl FnOnce(OapiNested<S>) -> OapiNested<S>) -> Self {
+        self.nest(prefix, f(OapiNested::new(prefix)).router)
+    }
+}
+
+/// Adds the [`MatchedOperation`] extension of the operation, and records it in the current
+/// span with the `tracing` feature. This is not generic over the operation, so it is compiled
+/// once per state rather than once per operation.
+pub(crate) fn operation_layers<S>(
+    operation: MatchedOperation,
+    method_router: MethodRouter<S>,
+) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    #[cfg(feature = "tracing")]
+    let method_router: MethodRouter<S> =
+        method_router.layer(axum::middleware::map_request(|req: Request| async move {
+            if let Some(operation) = req.extensions().get::<MatchedOperation>() {
+                trace::record_matched_operation(operation);
+            }
+            req
+        }));
+    method_router.layer(Extension(operation))
+}
+
+/// Routes the handler of the operation `O`, like [`OapiRouter::oapi_route`], but fails to
+/// compile if the last extractor of the handler is not `O`. Used by the `build_router` macro.
+#[doc(hidden)]
+pub fn route_operation<O, R, H, T>(router: R, handler: H) -> R
+where
+    R: OapiRouter,
+    H: Handler<T, R::State> + HandlerOutput<T, Extractor = O>,
+    T: 'static + OapiPath + OperationMeta,
+{
+    router.oapi_route(handler)
+}
+
+/// Whether the route path can match the same requests as any of the paths, like `/users/me` and
+/// `/users/{user_id}`. Parameters can be written as `:name` or `{name}`, and match any segment.
+/// Used by the `validated_router` macro, at compile time.
+#[doc(hidden)]
+pub const fn route_conflicts(path: &str, paths: &[&str]) -> bool {
+    let mut i = 0;
+    while i < paths.len() {
+        if paths_overlap(path.as_bytes(), paths[i].as_bytes()) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn paths_overlap(a: &[u8], b: &[u8]) -> bool {
+    // Skip the leading `/`
+    let (mut i, mut j) = (1, 1);
+    while i < a.len() && j < b.len() {
+        // A wildcard matches the rest of the path
+        if a[i] == b'*' || b[j] == b'*' {
+            return true;
+        }
+        let (a_end, b_end) = (segment_end(a, i), segment_end(b, j));
+        let a_segment = a.split_at(a_end).0.split_at(i).1;
+        let b_segment = b.split_at(b_end).0.split_at(j).1;
+        if !is_param(a[i]) && !is_param(b[j]) && !bytes_eq(a_segment, b_segment) {
+            return false;
+        }
+        i = a_end + 1;
+        j = b_end + 1;
+    }
+    i >= a.len() && j >= b.len()
+}
+
+const fn is_param(first: u8) -> bool {
+    first == b':' || first == b'{'
+}
+
+const fn segment_end(path: &[u8], mut i: usize) -> usize {
+    while i < path.len() && path[i] != b'/' {
+        i += 1;
+    }
+    i
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
 }
 
 pub trait OapiPath {
     fn path() -> &'static str;
+    fn method_filter() -> MethodFilter;
+
+    /// Routes the handler at the method of the operation. The handler is boxed right away, so
+    /// only this is instantiated per handler, and the generated code has no generic functions.
     fn method_router<H, T, S>(handler: H) -> MethodRouter<S>
     where
         H: Handler<T, S>,
         T: 'static,
-        S: Clone + Send + Sync + 'static;
+        S: Clone + Send + Sync + 'static,
+    {
+        MethodRouter::new().on(Self::method_filter(), handler)
+    }
+}
+
+/// Like [`OapiPath`], but for webhooks, which have no path in the spec.
+pub trait OapiWebhook {
+    fn method_filter() -> MethodFilter;
+
+    fn method_router<H, T, S>(handler: H) -> MethodRouter<S>
+    where
+        H: Handler<T, S>,
+        T: 'static,
+        S: Clone + Send + Sync + 'static,
+    {
+        MethodRouter::new().on(Self::method_filter(), handler)
+    }
+}
+
+/// Implemented by the types that a handler of the operation `T` may return, which are generated
+/// from the responses in the spec. For a json response with schema `User`, these are
+/// `Json<User>` and `(StatusCode, Json<User>)`.
+///
+/// A `Result` is a valid response if its `Ok` type is, so handlers can still return errors.
+pub trait ResponseFor<T> {}
+
+impl<T, R: ResponseFor<T>, E: IntoResponse> ResponseFor<T> for Result<R, E> {}
+
+/// Expands to the request builders that are generated for tests with feature `test-helpers`, and
+/// to nothing without it, so they are only compiled into the crates that test with them.
+#[cfg(feature = "test-helpers")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! test_helpers {
+    ($($item:item)*) => {
+        $($item)*
+    };
+}
+
+#[cfg(not(feature = "test-helpers"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! test_helpers {
+    ($($item:item)*) => {};
+}
+
+/// Fails the compilation of the code generated for patterns without feature `pattern`, with a
+/// clearer error than the missing items of the `pattern` module.
+#[cfg(feature = "pattern")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! require_pattern_feature {
+    () => {};
+}
+
+#[cfg(not(feature = "pattern"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! require_pattern_feature {
+    () => {
+        compile_error!("The `pattern` checks of `invalid_path_params` require feature `pattern`")
+    };
+}
+
+/// Fails the compilation of the code generated for `typed_headers = true;` without feature
+/// `typed-header`.
+#[cfg(feature = "typed-header")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! require_typed_header_feature {
+    () => {};
+}
+
+#[cfg(not(feature = "typed-header"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! require_typed_header_feature {
+    () => {
+        compile_error!("`typed_headers = true;` requires feature `typed-header` of axum-open-api")
+    };
+}
+
+/// Fails the compilation of the code generated for `cors_layer = ...;` without feature `cors`.
+#[cfg(feature = "cors")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! require_cors_feature {
+    () => {};
+}
+
+#[cfg(not(feature = "cors"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! require_cors_feature {
+    () => {
+        compile_error!("`cors_layer` requires feature `cors` of axum-open-api")
+    };
+}
+
+/// The response of an operation with a documented `204 No Content`. It can't carry a body, so
+/// a handler can't send one by accident.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoContent;
+
+impl IntoResponse for NoContent {
+    fn into_response(self) -> Response {
+        StatusCode::NO_CONTENT.into_response()
+    }
+}
+
+/// The output and the last extractor of a handler, for handlers that are functions.
+pub trait HandlerOutput<T> {
+    type Output;
+    /// The last extractor, which is the generated struct of the operation
+    type Extractor;
+}
+
+macro_rules! impl_handler_output {
+    ($($ty:ident),*; $last:ident) => {
+        impl<F, Fut, M, $($ty,)* $last> HandlerOutput<(M, $($ty,)* $last,)> for F
+        where
+            F: FnOnce($($ty,)* $last) -> Fut,
+            Fut: std::future::Future,
+        {
+            type Output = Fut::Output;
+            type Extractor = $last;
+        }
+    };
+}
+
+impl_handler_output!(; T1);
+impl_handler_output!(T1; T2);
+impl_handler_output!(T1, T2; T3);
+impl_handler_output!(T1, T2, T3; T4);
+impl_handler_output!(T1, T2, T3, T4; T5);
+impl_handler_output!(T1, T2, T3, T4, T5; T6);
+impl_handler_output!(T1, T2, T3, T4, T5, T6; T7);
+impl_handler_output!(T1, T2, T3, T4, T5, T6, T7; T8);
+impl_handler_output!(T1, T2, T3, T4, T5, T6, T7, T8; T9);
+impl_handler_output!(T1, T2, T3, T4, T5, T6, T7, T8, T9; T10);
+impl_handler_output!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10; T11);
+
+/// The metadata of an operation in the OpenAPI spec, implemented by every generated struct.
+pub trait OperationMeta {
+    /// The http method, like `GET`.
+    fn method() -> Method;
+    /// The path as written in the spec, like `/users/{user_id}`, or the name of the webhook.
+    fn path_template() -> &'static str;
+    /// The `operationId`, if the operation has one.
+    fn operation_id() -> Option<&'static str>;
+    /// The `tags` of the operation.
+    fn tags() -> &'static [&'static str];
+}
+
+/// The operation that matched the request, inserted as a request extension by
+/// [`OapiRouter::oapi_route`]. Layers can use this to tag requests by operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedOperation {
+    pub method: Method,
+    pub path_template: &'static str,
+    pub operation_id: Option<&'static str>,
+    pub tags: &'static [&'static str],
+}
+
+impl MatchedOperation {
+    /// Returns the matched operation of the generated struct `T`.
+    pub fn of<T: OperationMeta>() -> Self {
+        Self {
+            method: T::method(),
+            path_template: T::path_template(),
+            operation_id: T::operation_id(),
+            tags: T::tags(),
+        }
+    }
+}
+
+/// The error of the `FromStr` implementations of the enums generated from string schemas with an
+/// `enum`, for a value that is not one of the values in the spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownVariant {
+    pub value: String,
+    pub expected: &'static [&'static str],
+}
+
+impl std::fmt::Display for UnknownVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown value {:?}, expected one of: {}",
+            self.value,
+            self.expected.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownVariant {}
+
+/// The name of the operation, used in spans and metrics: the operationId, or `METHOD /path`.
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+pub(crate) fn operation_name<T: OperationMeta>() -> String {
+    match T::operation_id() {
+        Some(operation_id) => operation_id.to_owned(),
+        None => format!("{} {}", T::method(), T::path_template()),
+    }
 }
 
 macro_rules! impl_oapi_path_for {
@@ -50,13 +506,32 @@ macro_rules! impl_oapi_path_for {
                 P::path()
             }
 
-            fn method_router<H, T, S>(handler: H) -> MethodRouter<S>
-            where
-                H: Handler<T, S>,
-                T: 'static,
-                S: Clone + Send + Sync + 'static,
-            {
-                P::method_router(handler)
+            fn method_filter() -> MethodFilter {
+                P::method_filter()
+            }
+        }
+
+        impl<P: OapiWebhook, $($os),*> OapiWebhook for ($($os,)* P,) {
+            fn method_filter() -> MethodFilter {
+                P::method_filter()
+            }
+        }
+
+        impl<P: OperationMeta, $($os),*> OperationMeta for ($($os,)* P,) {
+            fn method() -> Method {
+                P::method()
+            }
+
+            fn path_template() -> &'static str {
+                P::path_template()
+            }
+
+            fn operation_id() -> Option<&'static str> {
+                P::operation_id()
+            }
+
+            fn tags() -> &'static [&'static str] {
+                P::tags()
             }
         }
     };
@@ -82,6 +557,8 @@ pub enum Rejection {
     Form(FormRejection),
     String(StringRejection),
     Bytes(BytesRejection),
+    Security(SecurityRejection),
+    Idempotency(IdempotencyRejection),
     Other(Box<dyn DynRejection>),
 }
 
@@ -101,6 +578,8 @@ rejection_from!(JsonRejection, Json);
 rejection_from!(FormRejection, Form);
 rejection_from!(StringRejection, String);
 rejection_from!(BytesRejection, Bytes);
+rejection_from!(SecurityRejection, Security);
+rejection_from!(IdempotencyRejection, Idempotency);
 rejection_from!(Box<dyn DynRejection>, Other);
 
 pub trait DynRejection: IntoResponse + std::fmt::Debug + Send + Sync + 'static {
@@ -115,6 +594,23 @@ where
     }
 }
 
+impl Rejection {
+    /// The name of the variant, like `"Json"`.
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            Rejection::Query(_) => "Query",
+            Rejection::Path(_) => "Path",
+            Rejection::Json(_) => "Json",
+            Rejection::Form(_) => "Form",
+            Rejection::String(_) => "String",
+            Rejection::Bytes(_) => "Bytes",
+            Rejection::Security(_) => "Security",
+            Rejection::Idempotency(_) => "Idempotency",
+            Rejection::Other(_) => "Other",
+        }
+    }
+}
+
 impl IntoResponse for Rejection {
     fn into_response(self) -> Response {
         match self {
@@ -124,6 +620,8 @@ impl IntoResponse for Rejection {
             Rejection::Form(e) => e.into_response(),
             Rejection::String(e) => e.into_response(),
             Rejection::Bytes(e) => e.into_response(),
+            Rejection::Security(e) => e.into_response(),
+            Rejection::Idempotency(e) => e.into_response(),
             Rejection::Other(e) => e.boxed_into_response(),
         }
     }
@@ -0,0 +1,108 @@
+//! Maps with a bounded amount of entries, generated for objects with `patternProperties` and
+//! `minProperties` or `maxProperties`.
+
+use serde::{
+    de::{Error, MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{collections::HashMap, fmt, marker::PhantomData, ops::Deref};
+
+/// A map of at least `MIN` and at most `MAX` entries. Deserializing fails as soon as the map has
+/// more than `MAX` entries, so huge payloads are rejected before they are fully read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundedMap<V, const MIN: usize, const MAX: usize>(HashMap<String, V>);
+
+impl<V, const MIN: usize, const MAX: usize> BoundedMap<V, MIN, MAX> {
+    pub fn into_inner(self) -> HashMap<String, V> {
+        self.0
+    }
+}
+
+impl<V, const MIN: usize, const MAX: usize> Deref for BoundedMap<V, MIN, MAX> {
+    type Target = HashMap<String, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<V, const MIN: usize, const MAX: usize> TryFrom<HashMap<String, V>>
+    for BoundedMap<V, MIN, MAX>
+{
+    type Error = OutOfBounds;
+
+    fn try_from(map: HashMap<String, V>) -> Result<Self, Self::Error> {
+        if (MIN..=MAX).contains(&map.len()) {
+            Ok(Self(map))
+        } else {
+            Err(OutOfBounds {
+                len: map.len(),
+                min: MIN,
+                max: MAX,
+            })
+        }
+    }
+}
+
+impl<V: Serialize, const MIN: usize, const MAX: usize> Serialize for BoundedMap<V, MIN, MAX> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, V, const MIN: usize, const MAX: usize> Deserialize<'de> for BoundedMap<V, MIN, MAX>
+where
+    V: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BoundedVisitor<V, const MIN: usize, const MAX: usize>(PhantomData<V>);
+
+        impl<'de, V, const MIN: usize, const MAX: usize> Visitor<'de> for BoundedVisitor<V, MIN, MAX>
+        where
+            V: Deserialize<'de>,
+        {
+            type Value = BoundedMap<V, MIN, MAX>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a map of {MIN} to {MAX} entries")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+                let capacity = access.size_hint().unwrap_or(0).min(MAX);
+                let mut map = HashMap::with_capacity(capacity);
+                while let Some((key, value)) = access.next_entry()? {
+                    if map.len() == MAX {
+                        return Err(A::Error::custom(format!("more than {MAX} entries")));
+                    }
+                    map.insert(key, value);
+                }
+                if map.len() < MIN {
+                    return Err(A::Error::custom(format!("less than {MIN} entries")));
+                }
+                Ok(BoundedMap(map))
+            }
+        }
+
+        deserializer.deserialize_map(BoundedVisitor(PhantomData))
+    }
+}
+
+/// The error of converting a map with too few or too many entries into a [`BoundedMap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub len: usize,
+    pub min: usize,
+    pub max: usize,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} entries, expected {} to {}",
+            self.len, self.min, self.max
+        )
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
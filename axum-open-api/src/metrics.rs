@@ -0,0 +1,40 @@
+//! Metrics of the generated extractors, enabled with feature `metrics`. Without the feature,
+//! everything in this module is a no-op.
+//!
+//! The following metrics are recorded, labeled with the `operation`:
+//! - `oapi_requests_total`: counter of extracted requests.
+//! - `oapi_rejections_total`: counter of rejected requests, also labeled with the `rejection`.
+//! - `oapi_extraction_duration_seconds`: histogram of the extraction latency.
+
+use crate::{OperationMeta, Rejection};
+use std::future::Future;
+
+/// Measures the extraction of the generated struct `T`.
+pub async fn measure_extraction<T, F>(extraction: F) -> Result<T, Rejection>
+where
+    T: OperationMeta,
+    F: Future<Output = Result<T, Rejection>>,
+{
+    #[cfg(feature = "metrics")]
+    {
+        let operation = crate::operation_name::<T>();
+        let started = std::time::Instant::now();
+        let result = extraction.await;
+
+        ::metrics::histogram!("oapi_extraction_duration_seconds", "operation" => operation.clone())
+            .record(started.elapsed().as_secs_f64());
+        ::metrics::counter!("oapi_requests_total", "operation" => operation.clone()).increment(1);
+        if let Err(rejection) = &result {
+            ::metrics::counter!(
+                "oapi_rejections_total",
+                "operation" => operation,
+                "rejection" => rejection.variant_name(),
+            )
+            .increment(1);
+        }
+
+        result
+    }
+    #[cfg(not(feature = "metrics"))]
+    extraction.await
+}
@@ -0,0 +1,135 @@
+//! Splitting the routes of an api into nested routers, see [`OapiRouter::oapi_nest`].
+
+use crate::{operation_layers, MatchedOperation, OapiPath, OapiRouter, OapiWebhook, OperationMeta};
+use axum::{
+    extract::Request,
+    handler::Handler,
+    response::IntoResponse,
+    routing::{MethodFilter, Route},
+    Extension, Router,
+};
+use std::convert::Infallible;
+use tower::{Layer, Service};
+
+/// The router nested at a prefix by [`OapiRouter::oapi_nest`]. Operations are routed at their
+/// path without the prefix, and must have a path that starts with it.
+#[derive(Debug)]
+pub struct OapiNested<S = ()> {
+    prefix: String,
+    pub(crate) router: Router<S>,
+    pub(crate) operations: Vec<MatchedOperation>,
+    /// The webhooks, which are not in the `paths` of the spec
+    pub(crate) webhooks: Vec<MatchedOperation>,
+}
+
+impl<S: Clone + Send + Sync + 'static> OapiNested<S> {
+    pub(crate) fn new(prefix: &str) -> Self {
+        assert!(
+            prefix.starts_with('/') && !prefix.ends_with('/'),
+            "the prefix `{prefix}` should start with a `/` and not end with one"
+        );
+        Self {
+            prefix: prefix.to_owned(),
+            router: Router::new(),
+            operations: Vec::new(),
+            webhooks: Vec::new(),
+        }
+    }
+
+    /// Applies `f` to the nested router, for layers that apply to its operations only.
+    pub fn map_router(mut self, f: impl FnOnce(Router<S>) -> Router<S>) -> Self {
+        self.router = f(self.router);
+        self
+    }
+
+    /// The path of the operation `T` without the prefix. Panics if the path does not start
+    /// with the prefix, as the operation would not be served at its path in the spec.
+    fn path_of<T: OapiPath>(&self) -> &'static str {
+        match T::path().strip_prefix(self.prefix.as_str()) {
+            Some("") => "/",
+            Some(path) if path.starts_with('/') => path,
+            _ => panic!(
+                "the path `{}` does not start with the prefix `{}`",
+                T::path(),
+                self.prefix
+            ),
+        }
+    }
+}
+
+impl<S: Clone + Send + Sync + 'static> OapiRouter for OapiNested<S> {
+    type State = S;
+
+    fn oapi_route<H, T>(mut self, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + OapiPath + OperationMeta,
+    {
+        let path = self.path_of::<T>();
+        let operation = MatchedOperation::of::<T>();
+        self.operations.push(operation.clone());
+        let method_router = operation_layers(operation, T::method_router(handler));
+        self.router = self.router.route(path, method_router);
+        self
+    }
+
+    fn oapi_route_with_layer<H, T, L>(mut self, handler: H, layer: L) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + OapiPath + OperationMeta,
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        let path = self.path_of::<T>();
+        let method_router = T::method_router(handler).layer(layer);
+        let operation = MatchedOperation::of::<T>();
+        self.operations.push(operation.clone());
+        self.router = self
+            .router
+            .route(path, operation_layers(operation, method_router));
+        self
+    }
+
+    fn oapi_route_service<T, Svc>(mut self, service: Svc) -> Self
+    where
+        T: 'static + OapiPath + OperationMeta,
+        Svc: Service<Request, Error = Infallible> + Clone + Send + 'static,
+        Svc::Response: IntoResponse + 'static,
+        Svc::Future: Send + 'static,
+    {
+        let path = self.path_of::<T>();
+        let filter = MethodFilter::try_from(T::method()).expect("the method should be routable");
+        let method_router = axum::routing::on_service(filter, service);
+        let operation = MatchedOperation::of::<T>();
+        self.operations.push(operation.clone());
+        self.router = self
+            .router
+            .route(path, operation_layers(operation, method_router));
+        self
+    }
+
+    /// Webhooks have no path in the spec, so the path is relative to the prefix.
+    fn oapi_webhook<H, T>(mut self, path: &str, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + OapiWebhook + OperationMeta,
+    {
+        let operation = MatchedOperation::of::<T>();
+        self.webhooks.push(operation.clone());
+        self.router = self
+            .router
+            .route(path, T::method_router(handler).layer(Extension(operation)));
+        self
+    }
+
+    fn oapi_nest(mut self, prefix: &str, f: impl FnOnce(OapiNested<S>) -> OapiNested<S>) -> Self {
+        let nested = f(OapiNested::new(&format!("{}{prefix}", self.prefix)));
+        self.operations.extend(nested.operations);
+        self.webhooks.extend(nested.webhooks);
+        self.router = self.router.nest(prefix, nested.router);
+        self
+    }
+}
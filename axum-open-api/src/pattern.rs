@@ -0,0 +1,46 @@
+//! Runtime support for the `pattern` checks of path parameters, generated with the
+//! `invalid_path_params` option of [`validate_routes!`](crate::validate_routes), enabled with
+//! feature `pattern`.
+
+use crate::{extract::InvalidPathParam, Rejection};
+use axum::http::StatusCode;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// The `pattern` of a schema, which is compiled on its first use.
+#[derive(Debug)]
+pub struct Pattern {
+    source: &'static str,
+    regex: OnceLock<Regex>,
+}
+
+impl Pattern {
+    pub const fn new(source: &'static str) -> Self {
+        Self {
+            source,
+            regex: OnceLock::new(),
+        }
+    }
+
+    /// Whether the pattern matches anywhere in the value, as in json schema.
+    pub fn is_match(&self, value: &str) -> bool {
+        let regex = self
+            .regex
+            .get_or_init(|| Regex::new(self.source).expect("patterns are checked by the macro"));
+        regex.is_match(value)
+    }
+
+    /// Checks the value of a path parameter, which is rejected with `status` if it does not match.
+    pub fn check_path_param(
+        &self,
+        value: &str,
+        name: &'static str,
+        status: StatusCode,
+    ) -> Result<(), Rejection> {
+        if self.is_match(value) {
+            return Ok(());
+        }
+        let reason = format!("does not match the pattern `{}`", self.source);
+        Err(InvalidPathParam::rejection(name, reason, status))
+    }
+}
@@ -0,0 +1,198 @@
+//! Runtime support for binary downloads with a `Range` header parameter or a `206` response,
+//! whose generated structs get a `range` field and a `partial_content` response helper:
+//! ```ignore
+//! async fn get_file(req: GetFile) -> PartialContent {
+//!     let file = load_file(&req.file_id).await;
+//!     req.partial_content(file)
+//! }
+//! ```
+
+use axum::{
+    body::Bytes,
+    http::{header, request::Parts, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::ops::Range;
+
+/// A single range of bytes of a `Range` header, like `bytes=0-499`, `bytes=500-` or
+/// `bytes=-500`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// The bytes from `start`, up to and including `end`, or up to the end without one
+    FromTo { start: u64, end: Option<u64> },
+    /// The last bytes
+    Suffix(u64),
+}
+
+impl ByteRange {
+    /// Parses a `Range` header. Other units, multiple ranges and invalid ranges are `None`, so
+    /// the header is ignored and the full content is sent, as allowed by RFC 9110.
+    pub fn parse(value: &str) -> Option<Self> {
+        let range = value.trim().strip_prefix("bytes=")?;
+        if range.contains(',') {
+            return None;
+        }
+        let (start, end) = range.trim().split_once('-')?;
+        match (start, end) {
+            ("", suffix) => suffix.parse().ok().map(ByteRange::Suffix),
+            (start, "") => Some(ByteRange::FromTo {
+                start: start.parse().ok()?,
+                end: None,
+            }),
+            (start, end) => {
+                let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+                (start <= end).then_some(ByteRange::FromTo {
+                    start,
+                    end: Some(end),
+                })
+            }
+        }
+    }
+
+    /// The range of the bytes of content of `len` bytes, or `None` if none of them are in it.
+    pub fn resolve(&self, len: u64) -> Option<Range<u64>> {
+        match *self {
+            ByteRange::FromTo { start, .. } if start >= len => None,
+            ByteRange::FromTo { start, end } => {
+                let end = end.map_or(len, |end| end.saturating_add(1).min(len));
+                Some(start..end)
+            }
+            // An empty body has no last bytes
+            ByteRange::Suffix(suffix) if suffix == 0 || len == 0 => None,
+            ByteRange::Suffix(suffix) => Some(len.saturating_sub(suffix)..len),
+        }
+    }
+}
+
+/// Extracts the `Range` header, if the request has a single range of bytes.
+pub fn byte_range(parts: &Parts) -> Option<ByteRange> {
+    ByteRange::parse(parts.headers.get(header::RANGE)?.to_str().ok()?)
+}
+
+/// A response with the requested range of the body: `206 Partial Content` with its
+/// `Content-Range`, `416 Range Not Satisfiable` if the range is outside of the body, or
+/// `200 OK` with the full body without a range.
+#[derive(Debug, Clone)]
+pub struct PartialContent {
+    pub range: Option<ByteRange>,
+    pub body: Bytes,
+    /// The media type of the response in the spec
+    pub content_type: Option<&'static str>,
+}
+
+impl IntoResponse for PartialContent {
+    fn into_response(self) -> Response {
+        let len = self.body.len() as u64;
+        let (status, content_range, body) = match self.range.map(|range| range.resolve(len)) {
+            None => (StatusCode::OK, None, self.body),
+            Some(Some(range)) => (
+                StatusCode::PARTIAL_CONTENT,
+                Some(format!("bytes {}-{}/{len}", range.start, range.end - 1)),
+                self.body.slice(range.start as usize..range.end as usize),
+            ),
+            Some(None) => (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                Some(format!("bytes */{len}")),
+                Bytes::new(),
+            ),
+        };
+
+        let mut response = (status, body).into_response();
+        let headers = response.headers_mut();
+        headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        if let Some(content_range) = content_range.and_then(|range| range.parse().ok()) {
+            headers.insert(header::CONTENT_RANGE, content_range);
+        }
+        if let Some(content_type) = self.content_type {
+            headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[test]
+    fn parse() {
+        let from_to = |start, end| Some(ByteRange::FromTo { start, end });
+        assert_eq!(ByteRange::parse("bytes=0-499"), from_to(0, Some(499)));
+        assert_eq!(ByteRange::parse("bytes=0-0"), from_to(0, Some(0)));
+        assert_eq!(ByteRange::parse("bytes=500-"), from_to(500, None));
+        assert_eq!(ByteRange::parse("bytes=-500"), Some(ByteRange::Suffix(500)));
+        assert_eq!(ByteRange::parse(" bytes=1-2 "), from_to(1, Some(2)));
+        // Invalid ranges, multiple ranges and other units are ignored
+        assert_eq!(ByteRange::parse("bytes=5-1"), None);
+        assert_eq!(ByteRange::parse("bytes=0-1,5-6"), None);
+        assert_eq!(ByteRange::parse("items=0-1"), None);
+        assert_eq!(ByteRange::parse("bytes=a-1"), None);
+        assert_eq!(ByteRange::parse("bytes=-"), None);
+        assert_eq!(ByteRange::parse("bytes=1"), None);
+    }
+
+    #[test]
+    fn resolve() {
+        let from_to = |start, end| ByteRange::FromTo { start, end };
+        assert_eq!(from_to(0, Some(0)).resolve(10), Some(0..1));
+        assert_eq!(from_to(2, Some(4)).resolve(10), Some(2..5));
+        assert_eq!(from_to(2, Some(100)).resolve(10), Some(2..10));
+        assert_eq!(from_to(2, None).resolve(10), Some(2..10));
+        assert_eq!(from_to(9, None).resolve(10), Some(9..10));
+        assert_eq!(from_to(10, None).resolve(10), None);
+        assert_eq!(from_to(10, Some(20)).resolve(10), None);
+        assert_eq!(ByteRange::Suffix(3).resolve(10), Some(7..10));
+        assert_eq!(ByteRange::Suffix(100).resolve(10), Some(0..10));
+        assert_eq!(ByteRange::Suffix(0).resolve(10), None);
+        assert_eq!(ByteRange::Suffix(3).resolve(0), None);
+    }
+
+    async fn respond(
+        range: Option<ByteRange>,
+        body: &'static str,
+    ) -> (StatusCode, Option<String>, Bytes) {
+        let response = PartialContent {
+            range,
+            body: Bytes::from_static(body.as_bytes()),
+            content_type: None,
+        }
+        .into_response();
+        let content_range = response
+            .headers()
+            .get(header::CONTENT_RANGE)
+            .map(|value| value.to_str().unwrap().to_owned());
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, content_range, body)
+    }
+
+    #[tokio::test]
+    async fn partial_content() {
+        let (status, content_range, body) = respond(ByteRange::parse("bytes=0-0"), "hello").await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(content_range.as_deref(), Some("bytes 0-0/5"));
+        assert_eq!(body, "h");
+
+        let (status, content_range, body) = respond(ByteRange::parse("bytes=-10"), "hello").await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(content_range.as_deref(), Some("bytes 0-4/5"));
+        assert_eq!(body, "hello");
+
+        let (status, content_range, body) = respond(ByteRange::parse("bytes=5-"), "hello").await;
+        assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(content_range.as_deref(), Some("bytes */5"));
+        assert!(body.is_empty());
+
+        let (status, content_range, body) = respond(ByteRange::parse("bytes=-1"), "").await;
+        assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(content_range.as_deref(), Some("bytes */0"));
+        assert!(body.is_empty());
+
+        let (status, content_range, body) =
+            respond(ByteRange::parse("bytes=0-1,3-4"), "hello").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_range, None);
+        assert_eq!(body, "hello");
+    }
+}
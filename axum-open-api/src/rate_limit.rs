@@ -0,0 +1,46 @@
+//! The rate limits of operations, from their `x-rate-limit` extension like
+//! `x-rate-limit: { requests: 100, period: 60 }`. They are generated as the `RATE_LIMIT` constant
+//! of the operation, like `GetPosts::RATE_LIMIT`, and can be enforced with feature `governor`:
+//! ```ignore
+//! let posts = Router::new()
+//!     .oapi_route(get_posts)
+//!     .route_layer(GetPosts::RATE_LIMIT.governor_layer());
+//! ```
+
+use std::time::Duration;
+
+/// The number of requests that are allowed per period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub period: Duration,
+    /// The number of requests that may be made at once, which is `requests` unless the
+    /// extension has a `burst`
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// The time after which one more request is allowed.
+    pub fn replenish_interval(&self) -> Duration {
+        self.period / self.requests.max(1)
+    }
+
+    /// A `tower_governor` layer enforcing the rate limit per ip address of the clients. The
+    /// router must be served with `into_make_service_with_connect_info::<SocketAddr>()`.
+    #[cfg(feature = "governor")]
+    pub fn governor_layer(
+        &self,
+    ) -> tower_governor::GovernorLayer<
+        tower_governor::key_extractor::PeerIpKeyExtractor,
+        governor::middleware::NoOpMiddleware,
+    > {
+        let config = tower_governor::governor::GovernorConfigBuilder::default()
+            .per_millisecond(self.replenish_interval().as_millis().max(1) as u64)
+            .burst_size(self.burst.max(1))
+            .finish()
+            .expect("the rate limit is valid");
+        tower_governor::GovernorLayer {
+            config: std::sync::Arc::new(config),
+        }
+    }
+}
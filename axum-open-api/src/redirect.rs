@@ -0,0 +1,54 @@
+//! The `Location` of documented redirects, which are generated as variants of the response enum
+//! like `CreatePostResponse::Status303(location)`. A location of another operation is built with
+//! its generated `location` helper, like `GetPost::location(&post_id)`.
+
+use axum::{
+    http::{header, uri::InvalidUri, HeaderMap, HeaderValue, Uri},
+    response::{IntoResponseParts, ResponseParts},
+};
+use std::{convert::Infallible, fmt};
+
+/// The target of a redirect, which is always a valid uri.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location(Uri);
+
+impl Location {
+    /// Parses the uri of the location, which may be relative, like `/posts/42`.
+    pub fn new(uri: &str) -> Result<Self, InvalidUri> {
+        uri.parse().map(Location)
+    }
+
+    pub fn uri(&self) -> &Uri {
+        &self.0
+    }
+
+    /// The `Location` header of a response, if it has a valid one.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        Location::new(headers.get(header::LOCATION)?.to_str().ok()?).ok()
+    }
+}
+
+impl From<Uri> for Location {
+    fn from(uri: Uri) -> Self {
+        Location(uri)
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Sets the `Location` header of the response, like `(StatusCode::SEE_OTHER, location, ())`.
+impl IntoResponseParts for Location {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        // A uri only consists of visible ascii, so it is always a valid header
+        if let Ok(value) = HeaderValue::from_str(&self.0.to_string()) {
+            res.headers_mut().insert(header::LOCATION, value);
+        }
+        Ok(res)
+    }
+}
@@ -0,0 +1,256 @@
+//! Runtime support for the extractors generated from `components/securitySchemes`.
+
+use crate::uri::percent_decode;
+use axum::{
+    extract::Query,
+    http::{
+        header::{AUTHORIZATION, COOKIE, WWW_AUTHENTICATE},
+        request::Parts,
+        StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::collections::HashMap;
+
+/// Verifies the keys extracted by the generated `apiKey` security extractors.
+///
+/// This trait must be implemented by the router state.
+#[axum::async_trait]
+pub trait ApiKeyVerifier {
+    /// Returns whether `key` is valid for the security scheme named `scheme`.
+    async fn verify_api_key(&self, scheme: &'static str, key: &str) -> bool;
+}
+
+/// Verifies the credentials extracted by the generated http `basic` security extractors.
+///
+/// This trait must be implemented by the router state.
+#[axum::async_trait]
+pub trait BasicVerifier {
+    /// Returns whether the credentials are valid for the security scheme named `scheme`.
+    async fn verify_basic(&self, scheme: &'static str, username: &str, password: &str) -> bool;
+}
+
+/// Verifies the tokens extracted by the generated http `bearer` security extractors, and
+/// turns them into claims of type `C`.
+///
+/// This trait must be implemented by the router state. With feature `jwt` enabled,
+/// [`JwtVerifier`] can be used to implement it.
+#[axum::async_trait]
+pub trait TokenVerifier<C> {
+    /// Returns the claims of `token` if it is valid for the security scheme named `scheme`.
+    async fn verify_token(&self, scheme: &'static str, token: &str) -> Option<C>;
+}
+
+/// The claims of a token whose security scheme has no `x-claims` schema, which can hold any
+/// json object.
+pub type JsonClaims = serde_json::Map<String, serde_json::Value>;
+
+/// Provides the scopes granted to the caller, for the generated `oauth2` security extractors.
+///
+/// This trait must be implemented by the router state.
+#[axum::async_trait]
+pub trait ScopeSource {
+    /// Returns the scopes granted to the caller for the security scheme named `scheme`, or
+    /// `None` if the caller is not authenticated.
+    async fn scopes(&self, scheme: &'static str, parts: &Parts) -> Option<Vec<String>>;
+}
+
+/// Validates the ID tokens extracted by the generated `openIdConnect` security extractors, and
+/// turns them into claims of type `C`.
+///
+/// This trait must be implemented by the router state.
+#[axum::async_trait]
+pub trait OidcValidator<C> {
+    /// Returns the claims of `token` if it is valid for the security scheme named `scheme`.
+    /// The `discovery_url` is the scheme's `openIdConnectUrl`.
+    async fn validate(
+        &self,
+        scheme: &'static str,
+        discovery_url: &'static str,
+        token: &str,
+    ) -> Option<C>;
+}
+
+/// A [`TokenVerifier`] that decodes and validates JWTs using [`jsonwebtoken`].
+#[cfg(feature = "jwt")]
+#[derive(Clone)]
+pub struct JwtVerifier {
+    pub key: jsonwebtoken::DecodingKey,
+    pub validation: jsonwebtoken::Validation,
+}
+
+#[cfg(feature = "jwt")]
+#[axum::async_trait]
+impl<C> TokenVerifier<C> for JwtVerifier
+where
+    C: serde::de::DeserializeOwned + Send + 'static,
+{
+    async fn verify_token(&self, _scheme: &'static str, token: &str) -> Option<C> {
+        jsonwebtoken::decode(token, &self.key, &self.validation)
+            .ok()
+            .map(|data| data.claims)
+    }
+}
+
+/// Information about the client certificate of a mutual-TLS connection, for the generated
+/// `mutualTLS` security extractors.
+///
+/// The TLS acceptor must insert this as a request extension once the client certificate has
+/// been verified.
+#[derive(Debug, Clone)]
+pub struct ClientCertInfo {
+    /// The DER-encoded certificate chain, starting with the client's certificate.
+    pub chain: Vec<Vec<u8>>,
+}
+
+/// The location of an api-key, as defined by the `in` field of the security scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKeyLocation {
+    Header,
+    Query,
+    Cookie,
+}
+
+/// Extracts the api-key called `name` from the request. The value of a cookie is unquoted and
+/// percent-decoded.
+pub fn extract_api_key(parts: &Parts, location: ApiKeyLocation, name: &str) -> Option<String> {
+    match location {
+        ApiKeyLocation::Header => parts.headers.get(name)?.to_str().ok().map(str::to_owned),
+        ApiKeyLocation::Query => {
+            let Query(mut query) =
+                Query::<HashMap<String, String>>::try_from_uri(&parts.uri).ok()?;
+            query.remove(name)
+        }
+        ApiKeyLocation::Cookie => parts
+            .headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|header| header.to_str().ok())
+            .flat_map(|header| header.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(cookie_name, _)| *cookie_name == name)
+            .map(|(_, value)| {
+                // A cookie value may be wrapped in quotes, and is commonly percent-encoded
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .unwrap_or(value);
+                percent_decode(value).into_owned()
+            }),
+    }
+}
+
+/// Extracts the username and password from an `Authorization: Basic ...` header.
+pub fn extract_basic_credentials(parts: &Parts) -> Option<(String, String)> {
+    let encoded = authorization_credentials(parts, "Basic")?;
+    let decoded = String::from_utf8(STANDARD.decode(encoded).ok()?).ok()?;
+    let (username, password) = decoded.split_once(':')?;
+    Some((username.to_owned(), password.to_owned()))
+}
+
+/// Extracts the token from an `Authorization: Bearer ...` header.
+pub fn extract_bearer_token(parts: &Parts) -> Option<String> {
+    authorization_credentials(parts, "Bearer").map(str::to_owned)
+}
+
+/// Checks that all `required` scopes of the security scheme have been `granted`.
+pub fn check_scopes(
+    scheme: &'static str,
+    granted: &[String],
+    required: &[&'static str],
+) -> Result<(), SecurityRejection> {
+    let missing: Vec<&'static str> = required
+        .iter()
+        .filter(|scope| !granted.iter().any(|granted| granted == *scope))
+        .copied()
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(SecurityRejection::Forbidden { scheme, missing })
+    }
+}
+
+/// Returns the credentials of the `Authorization` header, if it uses the given auth-scheme.
+fn authorization_credentials<'a>(parts: &'a Parts, auth_scheme: &str) -> Option<&'a str> {
+    let header = parts.headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, credentials) = header.split_once(' ')?;
+    scheme
+        .eq_ignore_ascii_case(auth_scheme)
+        .then_some(credentials.trim())
+}
+
+/// A rejection of a security extractor. The `&'static str` is the name of the security scheme.
+#[derive(Debug)]
+pub enum SecurityRejection {
+    /// The credentials were not present in the request.
+    Missing(&'static str),
+    /// The credentials were present, but were not accepted by the verifier.
+    Invalid(&'static str),
+    /// The credentials of an http security scheme were missing or invalid. The response
+    /// includes the `challenge` in its `WWW-Authenticate` header.
+    Challenge {
+        scheme: &'static str,
+        challenge: &'static str,
+    },
+    /// The caller is authenticated, but lacks the `missing` scopes.
+    Forbidden {
+        scheme: &'static str,
+        missing: Vec<&'static str>,
+    },
+}
+
+impl IntoResponse for SecurityRejection {
+    fn into_response(self) -> Response {
+        match self {
+            SecurityRejection::Missing(scheme) => (
+                StatusCode::UNAUTHORIZED,
+                format!("Missing credentials for security scheme `{scheme}`"),
+            )
+                .into_response(),
+            SecurityRejection::Invalid(scheme) => (
+                StatusCode::UNAUTHORIZED,
+                format!("Invalid credentials for security scheme `{scheme}`"),
+            )
+                .into_response(),
+            SecurityRejection::Challenge { scheme, challenge } => (
+                StatusCode::UNAUTHORIZED,
+                [(WWW_AUTHENTICATE, challenge)],
+                format!("Unauthorized for security scheme `{scheme}`"),
+            )
+                .into_response(),
+            SecurityRejection::Forbidden { scheme, missing } => (
+                StatusCode::FORBIDDEN,
+                format!(
+                    "Missing scopes for security scheme `{scheme}`: {}",
+                    missing.join(", ")
+                ),
+            )
+                .into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    fn parts(cookie: &str) -> Parts {
+        let request = Request::builder().header(COOKIE, cookie).body(()).unwrap();
+        request.into_parts().0
+    }
+
+    #[test]
+    fn api_key_cookies() {
+        let key = |cookie| extract_api_key(&parts(cookie), ApiKeyLocation::Cookie, "key");
+        assert_eq!(key("key=abc"), Some("abc".to_owned()));
+        assert_eq!(key("other=1; key=abc"), Some("abc".to_owned()));
+        assert_eq!(key(r#"key="abc""#), Some("abc".to_owned()));
+        assert_eq!(key("key=a%2Bb%3D%3D"), Some("a+b==".to_owned()));
+        assert_eq!(key(r#"key="a%20b""#), Some("a b".to_owned()));
+        assert_eq!(key("keys=abc"), None);
+    }
+}
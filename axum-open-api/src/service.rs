@@ -0,0 +1,92 @@
+//! Operations as standalone `tower::Service`s, which are generated as `GetPostsService` for
+//! `services = true;`. They can be wrapped in tower middleware like any service, or served
+//! without an axum router:
+//! ```ignore
+//! let service = ServiceBuilder::new()
+//!     .timeout(Duration::from_secs(5))
+//!     .service(GetPostsService::new(get_posts));
+//! ```
+
+use crate::{operation_layers, MatchedOperation, OapiPath, OperationMeta};
+use axum::{
+    extract::Request, handler::Handler, response::Response, routing::future::RouteFuture, Router,
+};
+use std::{
+    convert::Infallible,
+    fmt,
+    marker::PhantomData,
+    task::{Context, Poll},
+};
+use tower::Service;
+
+/// The operation `T` as a `tower::Service`, which extracts `T` from the request and calls the
+/// handler with it. It routes the request at the path of the operation like
+/// [`OapiRouter::oapi_route`](crate::OapiRouter::oapi_route), so the path parameters are matched,
+/// other paths are `404 Not Found`, and other methods are `405 Method Not Allowed`.
+///
+/// The handler is boxed when the service is created, so the type of the service only depends on
+/// the operation.
+pub struct OperationService<T> {
+    router: Router,
+    _operation: PhantomData<fn() -> T>,
+}
+
+impl<T: OapiPath + OperationMeta> OperationService<T> {
+    /// Creates the service of the handler.
+    pub fn new<H, X>(handler: H) -> Self
+    where
+        H: Handler<X, ()>,
+        X: 'static,
+    {
+        Self::with_state(handler, ())
+    }
+
+    /// Creates the service of a handler that needs the state, like the handlers of a router
+    /// with this state.
+    pub fn with_state<H, X, S>(handler: H, state: S) -> Self
+    where
+        H: Handler<X, S>,
+        X: 'static,
+        S: Clone + Send + Sync + 'static,
+    {
+        let operation = MatchedOperation::of::<T>();
+        let method_router = operation_layers(operation, T::method_router(handler));
+        Self {
+            router: Router::new()
+                .route(T::path(), method_router)
+                .with_state(state),
+            _operation: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for OperationService<T> {
+    fn clone(&self) -> Self {
+        Self {
+            router: self.router.clone(),
+            _operation: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for OperationService<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OperationService")
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T> Service<Request> for OperationService<T> {
+    type Response = Response;
+    type Error = Infallible;
+    type Future = RouteFuture<Infallible>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Service::<Request>::poll_ready(&mut self.router, cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        self.router.call(req)
+    }
+}
@@ -0,0 +1,413 @@
+//! A runtime view of the OpenAPI spec, enabled with feature `spec`. This is used to validate
+//! requests and responses against the spec.
+
+use crate::{uri::percent_decode, MatchedOperation};
+use axum::http::Method;
+use serde_json::Value;
+#[cfg(feature = "pattern")]
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// The http methods of a path item, as they are written in the spec.
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// The maximum amount of `$ref`s followed when resolving a schema, to prevent cycles.
+const MAX_REF_DEPTH: usize = 32;
+
+/// An OpenAPI spec, parsed from yaml or json.
+#[derive(Debug, Clone)]
+pub struct SpecDocument {
+    root: Value,
+    /// The compiled `pattern`s of the schemas, by their source. Patterns that the `regex` crate
+    /// does not support are `None`.
+    #[cfg(feature = "pattern")]
+    patterns: Arc<Mutex<HashMap<String, Option<regex::Regex>>>>,
+}
+
+/// An operation of a [`SpecDocument`].
+#[derive(Debug, Clone, Copy)]
+pub struct Operation<'a> {
+    /// The http method in lowercase, like `get`.
+    pub method: &'a str,
+    /// The path as written in the spec, like `/users/{user_id}`.
+    pub path_template: &'a str,
+    /// The path item containing the operation.
+    pub path_item: &'a Value,
+    /// The operation itself.
+    pub value: &'a Value,
+}
+
+impl SpecDocument {
+    /// Parses the spec from yaml or json.
+    pub fn parse(spec: &str) -> Result<Self, serde_yaml::Error> {
+        Ok(Self::from_root(serde_yaml::from_str(spec)?))
+    }
+
+    fn from_root(root: Value) -> Self {
+        Self {
+            root,
+            #[cfg(feature = "pattern")]
+            patterns: Arc::default(),
+        }
+    }
+
+    /// The spec as json.
+    pub fn root(&self) -> &Value {
+        &self.root
+    }
+
+    /// All operations of the spec.
+    pub fn operations(&self) -> impl Iterator<Item = Operation<'_>> {
+        self.root["paths"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .flat_map(|(path_template, path_item)| {
+                METHODS.into_iter().filter_map(move |method| {
+                    Some(Operation {
+                        method,
+                        path_template,
+                        path_item,
+                        value: path_item.get(method)?,
+                    })
+                })
+            })
+    }
+
+    /// Finds the operation matching the request, together with the values of its path
+    /// parameters.
+    pub fn find_operation(
+        &self,
+        method: &Method,
+        path: &str,
+    ) -> Option<(Operation<'_>, Vec<(&str, String)>)> {
+        let method = method.as_str().to_ascii_lowercase();
+        self.operations()
+            .filter(|operation| operation.method == method)
+            .find_map(|operation| {
+                let params = match_path_template(operation.path_template, path)?;
+                Some((operation, params))
+            })
+    }
+
+    /// Returns a copy of the spec with only the given operations, like those registered on a
+    /// [`RouteTable`](crate::testing::RouteTable). Path items without operations are removed.
+    ///
+    /// This lets a partially implemented service advertise only the operations it serves.
+    pub fn retain_operations<'a>(
+        &self,
+        operations: impl IntoIterator<Item = &'a MatchedOperation>,
+    ) -> Self {
+        let operations: Vec<&MatchedOperation> = operations.into_iter().collect();
+        let mut root = self.root.clone();
+        if let Some(paths) = root.get_mut("paths").and_then(Value::as_object_mut) {
+            for (path_template, path_item) in paths.iter_mut() {
+                let Some(path_item) = path_item.as_object_mut() else {
+                    continue;
+                };
+                for method in METHODS {
+                    let registered = operations.iter().any(|operation| {
+                        operation.method.as_str().eq_ignore_ascii_case(method)
+                            && operation.path_template == *path_template
+                    });
+                    if !registered {
+                        path_item.remove(method);
+                    }
+                }
+            }
+            paths.retain(|_, path_item| {
+                METHODS
+                    .into_iter()
+                    .any(|method| path_item.get(method).is_some())
+            });
+        }
+        Self::from_root(root)
+    }
+
+    /// Follows the local `$ref`s of the value.
+    pub fn resolve<'a>(&'a self, mut value: &'a Value) -> &'a Value {
+        for _ in 0..MAX_REF_DEPTH {
+            let Some(target) = value
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|reference| reference.strip_prefix('#'))
+                .and_then(|pointer| self.root.pointer(pointer))
+            else {
+                break;
+            };
+            value = target;
+        }
+        value
+    }
+
+    /// The resolved parameters of the operation, including those of its path item.
+    pub fn parameters<'a>(&'a self, operation: Operation<'a>) -> Vec<&'a Value> {
+        let mut parameters: Vec<&Value> = Vec::new();
+        for parameter in [operation.value, operation.path_item]
+            .into_iter()
+            .filter_map(|value| value.get("parameters")?.as_array())
+            .flatten()
+            .map(|parameter| self.resolve(parameter))
+        {
+            // Parameters of the operation override those of the path item
+            if !parameters
+                .iter()
+                .any(|p| p["name"] == parameter["name"] && p["in"] == parameter["in"])
+            {
+                parameters.push(parameter);
+            }
+        }
+        parameters
+    }
+
+    /// Validates the value against the schema, returning the violations.
+    pub fn validate(&self, schema: &Value, value: &Value) -> Vec<String> {
+        let mut violations = Vec::new();
+        self.validate_at(schema, value, "$", &mut violations);
+        violations
+    }
+
+    fn validate_at(&self, schema: &Value, value: &Value, at: &str, violations: &mut Vec<String>) {
+        let schema = self.resolve(schema);
+
+        // In 3.1, the type can be a list of types
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::String(ty)) => vec![ty.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let nullable = value.is_null() && schema["nullable"] == true;
+        if !types.is_empty() && !nullable && !types.iter().any(|ty| matches_type(ty, value)) {
+            violations.push(format!(
+                "{at}: expected {}, found {value}",
+                types.join(" or ")
+            ));
+            return;
+        }
+
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            if !values.contains(value) {
+                violations.push(format!("{at}: {value} is not one of the enum values"));
+            }
+        }
+        if let Some(constant) = schema.get("const") {
+            if constant != value {
+                violations.push(format!("{at}: expected constant {constant}, found {value}"));
+            }
+        }
+
+        match value {
+            Value::Number(number) => {
+                let number = number.as_f64().unwrap_or_default();
+                let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+                if bound("minimum").is_some_and(|minimum| number < minimum)
+                    || bound("exclusiveMinimum").is_some_and(|minimum| number <= minimum)
+                {
+                    violations.push(format!("{at}: {number} is below the minimum"));
+                }
+                if bound("maximum").is_some_and(|maximum| number > maximum)
+                    || bound("exclusiveMaximum").is_some_and(|maximum| number >= maximum)
+                {
+                    violations.push(format!("{at}: {number} is above the maximum"));
+                }
+            }
+            Value::String(string) => {
+                let length = string.chars().count();
+                check_count(schema, "minLength", "maxLength", length, at, violations);
+                #[cfg(feature = "pattern")]
+                if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+                    if self.is_match(pattern, string) == Some(false) {
+                        violations.push(format!("{at}: does not match the pattern `{pattern}`"));
+                    }
+                }
+            }
+            Value::Array(items) => {
+                check_count(schema, "minItems", "maxItems", items.len(), at, violations);
+                if let Some(items_schema) = schema.get("items") {
+                    for (i, item) in items.iter().enumerate() {
+                        self.validate_at(items_schema, item, &format!("{at}[{i}]"), violations);
+                    }
+                }
+            }
+            Value::Object(object) => {
+                check_count(
+                    schema,
+                    "minProperties",
+                    "maxProperties",
+                    object.len(),
+                    at,
+                    violations,
+                );
+                for required in schema["required"].as_array().into_iter().flatten() {
+                    if let Some(required) = required.as_str() {
+                        if !object.contains_key(required) {
+                            violations
+                                .push(format!("{at}: missing required property `{required}`"));
+                        }
+                    }
+                }
+                let properties = schema.get("properties").and_then(Value::as_object);
+                for (name, property) in object {
+                    let property_at = format!("{at}.{name}");
+                    match (
+                        properties.and_then(|p| p.get(name)),
+                        schema.get("additionalProperties"),
+                    ) {
+                        (Some(property_schema), _) => {
+                            self.validate_at(property_schema, property, &property_at, violations)
+                        }
+                        (None, Some(Value::Bool(false))) => {
+                            violations.push(format!("{property_at}: unknown property"))
+                        }
+                        (None, Some(additional @ Value::Object(_))) => {
+                            self.validate_at(additional, property, &property_at, violations)
+                        }
+                        (None, _) => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        // Composition
+        let matching = |schemas: &Value| {
+            schemas
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|schema| self.validate(schema, value).is_empty())
+                .count()
+        };
+        for schema in schema["allOf"].as_array().into_iter().flatten() {
+            self.validate_at(schema, value, at, violations);
+        }
+        if schema.get("anyOf").is_some() && matching(&schema["anyOf"]) == 0 {
+            violations.push(format!("{at}: does not match any of the `anyOf` schemas"));
+        }
+        if schema.get("oneOf").is_some() && matching(&schema["oneOf"]) != 1 {
+            violations.push(format!(
+                "{at}: does not match exactly one of the `oneOf` schemas"
+            ));
+        }
+        if let Some(not) = schema.get("not") {
+            if self.validate(not, value).is_empty() {
+                violations.push(format!("{at}: matches the `not` schema"));
+            }
+        }
+    }
+
+    /// Whether the pattern matches anywhere in the value, as in json schema, or `None` if the
+    /// `regex` crate does not support the pattern, like one with lookarounds.
+    #[cfg(feature = "pattern")]
+    fn is_match(&self, pattern: &str, value: &str) -> Option<bool> {
+        let mut patterns = self.patterns.lock().unwrap_or_else(|e| e.into_inner());
+        let regex = patterns
+            .entry(pattern.to_owned())
+            .or_insert_with(|| regex::Regex::new(pattern).ok());
+        regex.as_ref().map(|regex| regex.is_match(value))
+    }
+}
+
+/// Matches the path against a template like `/users/{user_id}`, returning the percent-decoded
+/// path parameters.
+pub fn match_path_template<'a>(template: &'a str, path: &str) -> Option<Vec<(&'a str, String)>> {
+    let mut template_segments = template.trim_end_matches('/').split('/');
+    let mut path_segments = path.trim_end_matches('/').split('/');
+    let mut params = Vec::new();
+    loop {
+        match (template_segments.next(), path_segments.next()) {
+            (None, None) => return Some(params),
+            (Some(template), Some(segment)) => {
+                match template.strip_prefix('{').and_then(|t| t.strip_suffix('}')) {
+                    Some(name) => params.push((name, percent_decode(segment).into_owned())),
+                    None if template == segment => {}
+                    None => return None,
+                }
+            }
+            _ => return None,
+        }
+    }
+}
+
+fn matches_type(ty: &str, value: &Value) -> bool {
+    match ty {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn check_count(
+    schema: &Value,
+    min_keyword: &str,
+    max_keyword: &str,
+    count: usize,
+    at: &str,
+    violations: &mut Vec<String>,
+) {
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_u64);
+    if bound(min_keyword).is_some_and(|min| (count as u64) < min) {
+        violations.push(format!("{at}: violates `{min_keyword}`"));
+    }
+    if bound(max_keyword).is_some_and(|max| (count as u64) > max) {
+        violations.push(format!("{at}: violates `{max_keyword}`"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_templates() {
+        let params = match_path_template("/users/{user_id}/posts/{post_id}", "/users/1/posts/2");
+        assert_eq!(
+            params,
+            Some(vec![
+                ("user_id", "1".to_owned()),
+                ("post_id", "2".to_owned())
+            ])
+        );
+        assert_eq!(
+            match_path_template("/users/{user_id}", "/users/1/"),
+            Some(vec![("user_id", "1".to_owned())])
+        );
+        assert_eq!(match_path_template("/users", "/users"), Some(Vec::new()));
+        assert_eq!(match_path_template("/users/{user_id}", "/users"), None);
+        assert_eq!(match_path_template("/users/{user_id}", "/posts/1"), None);
+        assert_eq!(
+            match_path_template("/users/{user_id}", "/users/1/posts"),
+            None
+        );
+    }
+
+    #[test]
+    fn path_templates_decode_params() {
+        let params = match_path_template("/files/{name}", "/files/a%2Fb%20c");
+        assert_eq!(params, Some(vec![("name", "a/b c".to_owned())]));
+        // A `%` without two hex digits is kept
+        let params = match_path_template("/files/{name}", "/files/100%");
+        assert_eq!(params, Some(vec![("name", "100%".to_owned())]));
+    }
+
+    #[test]
+    #[cfg(feature = "pattern")]
+    fn patterns() {
+        let spec = SpecDocument::parse("{}").unwrap();
+        let schema = serde_json::json!({ "type": "string", "pattern": "^[a-z]+$" });
+        assert!(spec.validate(&schema, &Value::from("abc")).is_empty());
+        assert_eq!(spec.validate(&schema, &Value::from("ABC")).len(), 1);
+        // Patterns that the `regex` crate does not support are not checked
+        let schema = serde_json::json!({ "type": "string", "pattern": "^(?!admin)" });
+        assert!(spec.validate(&schema, &Value::from("admin")).is_empty());
+    }
+}
@@ -0,0 +1,388 @@
+//! Runtime support for the array bodies of operations declared with `#[stream]`, whose items are
+//! parsed as the body is received instead of into a `Vec`, so bulk ingest operations only hold
+//! one item and one chunk of the body at a time:
+//! ```ignore
+//! async fn import_users(mut req: ImportUsers) -> Result<StatusCode, JsonArrayError> {
+//!     while let Some(user) = req.body.next().await {
+//!         store_user(user?).await;
+//!     }
+//!     Ok(StatusCode::NO_CONTENT)
+//! }
+//! ```
+
+use crate::{
+    json::{is_json, missing_json_content_type},
+    Rejection,
+};
+use axum::{
+    body::BodyDataStream,
+    extract::{FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use futures_core::Stream;
+use serde::de::DeserializeOwned;
+use std::{
+    fmt,
+    marker::PhantomData,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/// The largest item of a [`JsonArray`], in bytes. A larger item is a
+/// [`JsonArrayError::ItemTooLarge`], so a body can't be buffered whole by leaving an item open.
+pub const MAX_ITEM_LEN: usize = 2 * 1024 * 1024;
+
+/// An array body type, for the item type of its [`JsonArray`]. The generated structs name the
+/// items as `<Users as ArrayBody>::Item`, since array schemas with a title are type aliases.
+pub trait ArrayBody {
+    type Item;
+}
+
+impl<T> ArrayBody for Vec<T> {
+    type Item = T;
+}
+
+/// A json array body, which yields its items as they are received, with [`JsonArray::next`] or
+/// as a `Stream`. It ends after the first error.
+///
+/// Only the content type is checked when it is extracted, so an invalid body is an error of the
+/// stream instead of a rejection.
+pub struct JsonArray<T> {
+    body: BodyDataStream,
+    /// The received bytes that are not parsed yet, from `pos`
+    buffer: Vec<u8>,
+    pos: usize,
+    /// Whether the whole body is received
+    received: bool,
+    state: State,
+    _item: PhantomData<fn() -> T>,
+}
+
+/// Where the parser is in the array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Before the `[`
+    Start,
+    /// After the `[`, before the first item or the `]`
+    First,
+    /// After a `,`, before an item
+    Item,
+    /// After an item, before a `,` or the `]`
+    Next,
+    /// After the `]` or an error
+    Done,
+}
+
+/// What the parser needs to do next.
+enum Step<T> {
+    Item(T),
+    End,
+    Receive,
+    Error(JsonArrayError),
+}
+
+impl<T: DeserializeOwned> JsonArray<T> {
+    /// The next item, or `None` at the end of the array or after an error.
+    pub async fn next(&mut self) -> Option<Result<T, JsonArrayError>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Parses the next token of the received bytes.
+    fn step(&mut self) -> Step<T> {
+        loop {
+            let rest = &self.buffer[self.pos..];
+            let Some(start) = rest.iter().position(|byte| !byte.is_ascii_whitespace()) else {
+                self.pos = self.buffer.len();
+                // The rest of the body is received after the `]`, so trailing characters in a
+                // later chunk are an error as well
+                return match self.state {
+                    State::Done if self.received => Step::End,
+                    _ => Step::Receive,
+                };
+            };
+            self.pos += start;
+            let rest = &self.buffer[self.pos..];
+            match (self.state, rest[0]) {
+                (State::Start, b'[') => self.state = State::First,
+                (State::Start, _) => return Step::Error(JsonArrayError::Syntax("expected `[`")),
+                (State::First | State::Next, b']') => self.state = State::Done,
+                (State::Next, b',') => self.state = State::Item,
+                (State::Next, _) => {
+                    return Step::Error(JsonArrayError::Syntax("expected `,` or `]`"));
+                }
+                (State::First | State::Item, _) => {
+                    // The item is only complete once something follows it, since a number at
+                    // the end of the received bytes could go on in the next chunk
+                    let mut items = serde_json::Deserializer::from_slice(rest).into_iter::<T>();
+                    return match items.next() {
+                        Some(Ok(item)) if items.byte_offset() < rest.len() || self.received => {
+                            self.pos += items.byte_offset();
+                            self.state = State::Next;
+                            Step::Item(item)
+                        }
+                        Some(Err(e)) if !e.is_eof() || self.received => {
+                            Step::Error(JsonArrayError::Json(e))
+                        }
+                        _ => Step::Receive,
+                    };
+                }
+                (State::Done, _) => {
+                    return Step::Error(JsonArrayError::Syntax("trailing characters after `]`"));
+                }
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Stream for JsonArray<T> {
+    type Item = Result<T, JsonArrayError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            let error = match this.step() {
+                Step::Item(item) => return Poll::Ready(Some(Ok(item))),
+                Step::End => return Poll::Ready(None),
+                Step::Error(e) => e,
+                Step::Receive if this.received => {
+                    JsonArrayError::Syntax("unexpected end of the body")
+                }
+                Step::Receive if this.buffer.len() - this.pos > MAX_ITEM_LEN => {
+                    JsonArrayError::ItemTooLarge
+                }
+                Step::Receive => match ready!(Pin::new(&mut this.body).poll_next(cx)) {
+                    Some(Ok(chunk)) => {
+                        this.buffer.drain(..this.pos);
+                        this.pos = 0;
+                        this.buffer.extend_from_slice(&chunk);
+                        continue;
+                    }
+                    Some(Err(e)) => JsonArrayError::Body(e),
+                    None => {
+                        this.received = true;
+                        continue;
+                    }
+                },
+            };
+            this.state = State::Done;
+            this.buffer = Vec::new();
+            this.pos = 0;
+            this.received = true;
+            return Poll::Ready(Some(Err(error)));
+        }
+    }
+}
+
+impl<T> fmt::Debug for JsonArray<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonArray")
+            .field("state", &self.state)
+            .field("buffered", &(self.buffer.len() - self.pos))
+            .finish_non_exhaustive()
+    }
+}
+
+#[axum::async_trait]
+impl<T, S: Send + Sync> FromRequest<S> for JsonArray<T> {
+    type Rejection = Rejection;
+
+    async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        if !is_json(req.headers()) {
+            return Err(missing_json_content_type());
+        }
+        Ok(Self {
+            body: req.into_body().into_data_stream(),
+            buffer: Vec::new(),
+            pos: 0,
+            received: false,
+            state: State::Start,
+            _item: PhantomData,
+        })
+    }
+}
+
+/// An error of a [`JsonArray`], which is also its response.
+#[derive(Debug)]
+pub enum JsonArrayError {
+    /// The body could not be received
+    Body(axum::Error),
+    /// An item is not json, or does not match its schema
+    Json(serde_json::Error),
+    /// The body is not a json array
+    Syntax(&'static str),
+    /// An item is larger than [`MAX_ITEM_LEN`]
+    ItemTooLarge,
+}
+
+impl fmt::Display for JsonArrayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Body(e) => write!(f, "Failed to receive the body: {e}"),
+            Self::Json(e) => write!(f, "Failed to deserialize an item of the JSON array: {e}"),
+            Self::Syntax(message) => write!(f, "Failed to parse the JSON array: {message}"),
+            Self::ItemTooLarge => write!(f, "An item of the JSON array is too large"),
+        }
+    }
+}
+
+impl std::error::Error for JsonArrayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Body(e) => Some(e),
+            Self::Json(e) => Some(e),
+            Self::Syntax(_) | Self::ItemTooLarge => None,
+        }
+    }
+}
+
+impl IntoResponse for JsonArrayError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::Json(e) if e.classify() == serde_json::error::Category::Data => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            Self::ItemTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::header::CONTENT_TYPE};
+    use std::{collections::VecDeque, convert::Infallible};
+
+    /// A body that is received in the given chunks.
+    struct Chunks(VecDeque<Vec<u8>>);
+
+    impl Stream for Chunks {
+        type Item = Result<Vec<u8>, Infallible>;
+
+        fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            Poll::Ready(self.0.pop_front().map(Ok))
+        }
+    }
+
+    async fn collect<T: DeserializeOwned>(chunks: &[&str]) -> Vec<Result<T, JsonArrayError>> {
+        let chunks = chunks
+            .iter()
+            .map(|chunk| chunk.as_bytes().to_vec())
+            .collect();
+        let request = Request::builder()
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from_stream(Chunks(chunks)))
+            .unwrap();
+        let mut array = JsonArray::<T>::from_request(request, &()).await.unwrap();
+        let mut items = Vec::new();
+        while let Some(item) = array.next().await {
+            items.push(item);
+        }
+        items
+    }
+
+    async fn items<T: DeserializeOwned + fmt::Debug>(chunks: &[&str]) -> Vec<T> {
+        collect(chunks)
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    async fn error<T: DeserializeOwned + fmt::Debug>(chunks: &[&str]) -> JsonArrayError {
+        let mut items = collect::<T>(chunks).await;
+        items.pop().unwrap().unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn items_split_across_chunks() {
+        assert_eq!(items::<u32>(&["[1, 2, 3]"]).await, [1, 2, 3]);
+        assert_eq!(items::<u32>(&["[1", "2", "3, 4", "5]"]).await, [123, 45]);
+        assert_eq!(
+            items::<u32>(&[" [", "1 ", ",", " 2", "] ", " "]).await,
+            [1, 2]
+        );
+        assert_eq!(
+            items::<String>(&["[\"a", "b\", \"", "c\"]"]).await,
+            ["ab", "c"]
+        );
+        assert_eq!(items::<u32>(&["[]"]).await, Vec::<u32>::new());
+        assert_eq!(items::<u32>(&["[", "", "]"]).await, Vec::<u32>::new());
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        assert!(matches!(
+            error::<u32>(&["[1,]"]).await,
+            JsonArrayError::Json(_)
+        ));
+        assert!(matches!(
+            error::<u32>(&["[1 2]"]).await,
+            JsonArrayError::Syntax(_)
+        ));
+        assert!(matches!(
+            error::<u32>(&["{}"]).await,
+            JsonArrayError::Syntax(_)
+        ));
+        assert!(matches!(
+            error::<u32>(&["[1] x"]).await,
+            JsonArrayError::Syntax(_)
+        ));
+        assert!(matches!(
+            error::<u32>(&["[1]", " x"]).await,
+            JsonArrayError::Syntax(_)
+        ));
+        assert!(matches!(
+            error::<u32>(&["[\"a\"]"]).await,
+            JsonArrayError::Json(_)
+        ));
+
+        // The items before the error are still yielded, and nothing after it
+        let items = collect::<u32>(&["[1, 2", ", x, 3]"]).await;
+        assert!(matches!(
+            items[..],
+            [Ok(1), Ok(2), Err(JsonArrayError::Json(_))]
+        ));
+    }
+
+    #[tokio::test]
+    async fn body_ends_early() {
+        for chunks in [&[""][..], &["["], &["[1, 2"], &["[1, "], &["[1, \"a"]] {
+            let error = error::<serde_json::Value>(chunks).await;
+            assert!(
+                matches!(error, JsonArrayError::Syntax(_) | JsonArrayError::Json(_)),
+                "{chunks:?}: {error}"
+            );
+        }
+        let items = collect::<u32>(&["[1, 2"]).await;
+        assert!(matches!(
+            items[..],
+            [Ok(1), Ok(2), Err(JsonArrayError::Syntax(_))]
+        ));
+    }
+
+    #[tokio::test]
+    async fn item_too_large() {
+        let large = "a".repeat(MAX_ITEM_LEN);
+        let error = error::<String>(&["[\"", &large, "a", "\"]"]).await;
+        assert!(matches!(error, JsonArrayError::ItemTooLarge));
+        assert_eq!(
+            error.into_response().status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        // Items up to the limit are fine, however they are split
+        let item = "a".repeat(MAX_ITEM_LEN - 2);
+        let body = ["[\"", &item[..10], &item[10..], "\"]"];
+        assert_eq!(items::<String>(&body).await, [item]);
+    }
+
+    #[tokio::test]
+    async fn rejects_other_content_types() {
+        let request = Request::builder().body(Body::from("[]")).unwrap();
+        assert!(JsonArray::<u32>::from_request(request, &()).await.is_err());
+    }
+}
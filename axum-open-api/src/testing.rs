@@ -0,0 +1,311 @@
+//! Helpers for testing a router against the spec. These are used by the contract tests that
+//! are generated with the `contract_tests` option of [`validate_routes!`](crate::validate_routes).
+
+use crate::{nest::OapiNested, MatchedOperation, OapiRouter, OperationMeta};
+use axum::{
+    body::Body,
+    handler::Handler,
+    http::{header::CONTENT_TYPE, Method, Request},
+    response::IntoResponse,
+    routing::Route,
+    Router,
+};
+use std::{convert::Infallible, marker::PhantomData};
+use tower::{Layer, Service, ServiceExt};
+
+/// Records the operations that are registered with [`OapiRouter::oapi_route`], without
+/// building a router.
+///
+/// By registering the routes in a function that is generic over the [`OapiRouter`], the same
+/// function can build both the `Router` and the `RouteTable`:
+/// ```ignore
+/// fn routes<R: OapiRouter<State = AppState>>(router: R) -> R {
+///     router.oapi_route(GetUser::handle)
+/// }
+///
+/// #[test]
+/// fn covers_spec() {
+///     let table = routes(RouteTable::new());
+///     assert_covers_spec(&table, include_bytes!("../my-api.yaml"));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RouteTable<S = ()> {
+    operations: Vec<MatchedOperation>,
+    _state: PhantomData<fn() -> S>,
+}
+
+impl<S> RouteTable<S> {
+    pub fn new() -> Self {
+        Self {
+            operations: Vec::new(),
+            _state: PhantomData,
+        }
+    }
+
+    /// The registered operations, in order of registration.
+    pub fn operations(&self) -> &[MatchedOperation] {
+        &self.operations
+    }
+}
+
+impl<S> Default for RouteTable<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Clone + Send + Sync + 'static> OapiRouter for RouteTable<S> {
+    type State = S;
+
+    fn oapi_route<H, T>(mut self, _handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + crate::OapiPath + OperationMeta,
+    {
+        self.operations.push(MatchedOperation::of::<T>());
+        self
+    }
+
+    fn oapi_route_with_layer<H, T, L>(self, handler: H, _layer: L) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + crate::OapiPath + OperationMeta,
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<axum::extract::Request> + Clone + Send + 'static,
+        <L::Service as Service<axum::extract::Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<axum::extract::Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<axum::extract::Request>>::Future: Send + 'static,
+    {
+        self.oapi_route(handler)
+    }
+
+    fn oapi_route_service<T, Svc>(mut self, _service: Svc) -> Self
+    where
+        T: 'static + crate::OapiPath + OperationMeta,
+        Svc: Service<axum::extract::Request, Error = Infallible> + Clone + Send + 'static,
+        Svc::Response: IntoResponse + 'static,
+        Svc::Future: Send + 'static,
+    {
+        self.operations.push(MatchedOperation::of::<T>());
+        self
+    }
+
+    /// Webhooks are not in the `paths` of the spec, so they are not recorded.
+    fn oapi_webhook<H, T>(self, _path: &str, _handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static + crate::OapiWebhook + OperationMeta,
+    {
+        self
+    }
+
+    /// Webhooks are not recorded, like with [`OapiRouter::oapi_webhook`].
+    fn oapi_nest(mut self, prefix: &str, f: impl FnOnce(OapiNested<S>) -> OapiNested<S>) -> Self {
+        self.operations
+            .extend(f(OapiNested::new(prefix)).operations);
+        self
+    }
+}
+
+/// Panics if the registered operations differ from the operations of the spec, listing the
+/// operations of the spec that were never registered, and the registered operations that are
+/// not in the spec.
+#[cfg(feature = "spec")]
+pub fn assert_covers_spec<S>(table: &RouteTable<S>, spec: &[u8]) {
+    let coverage = spec_coverage_report(&table.operations, spec);
+    if !coverage.is_complete() {
+        panic!(
+            "the routes do not cover the spec\n\
+             declared in the spec but never registered: {:?}\n\
+             registered but not declared in the spec: {:?}",
+            coverage.missing, coverage.extra
+        );
+    }
+}
+
+/// The operations of the spec that are not implemented, and the implemented operations that are
+/// not in the spec, like `GET /users/{user_id}`.
+#[cfg(feature = "spec")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpecCoverage {
+    pub missing: Vec<String>,
+    pub extra: Vec<String>,
+}
+
+#[cfg(feature = "spec")]
+impl SpecCoverage {
+    /// Whether the implemented operations are exactly the operations of the spec.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty()
+    }
+
+    /// Panics with the missing and extra operations, unless the coverage is complete.
+    #[track_caller]
+    pub fn assert_complete(&self) {
+        if !self.is_complete() {
+            panic!("the implemented operations do not cover the spec\n{self}");
+        }
+    }
+}
+
+#[cfg(feature = "spec")]
+impl std::fmt::Display for SpecCoverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.missing.is_empty() {
+            writeln!(f, "Operations of the spec that are not implemented:")?;
+            for operation in &self.missing {
+                writeln!(f, "    {operation}")?;
+            }
+        }
+        if !self.extra.is_empty() {
+            writeln!(f, "Implemented operations that are not in the spec:")?;
+            for operation in &self.extra {
+                writeln!(f, "    {operation}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares the implemented operations with the operations of the spec, without failing, so a
+/// service can report its progress before it covers the whole spec. The operations can be the
+/// generated `IMPLEMENTED_OPERATIONS` of the declared operations, or the operations registered
+/// on a [`RouteTable`]:
+/// ```ignore
+/// let coverage = spec_coverage_report(IMPLEMENTED_OPERATIONS, include_bytes!("../my-api.yaml"));
+/// println!("{coverage}");
+/// coverage.assert_complete();
+/// ```
+#[cfg(feature = "spec")]
+pub fn spec_coverage_report(implemented: &[MatchedOperation], spec: &[u8]) -> SpecCoverage {
+    let spec = std::str::from_utf8(spec).expect("the spec should be utf-8");
+    let spec =
+        crate::spec::SpecDocument::parse(spec).expect("the spec should be valid yaml or json");
+
+    let is_implemented = |method: &str, path_template: &str| {
+        implemented.iter().any(|operation| {
+            operation.method.as_str().eq_ignore_ascii_case(method)
+                && operation.path_template == path_template
+        })
+    };
+    let missing = spec
+        .operations()
+        .filter(|operation| !is_implemented(operation.method, operation.path_template))
+        .map(|operation| {
+            format!(
+                "{} {}",
+                operation.method.to_ascii_uppercase(),
+                operation.path_template
+            )
+        })
+        .collect();
+    let extra = implemented
+        .iter()
+        .filter(|operation| {
+            !spec.operations().any(|declared| {
+                operation
+                    .method
+                    .as_str()
+                    .eq_ignore_ascii_case(declared.method)
+                    && operation.path_template == declared.path_template
+            })
+        })
+        .map(|operation| format!("{} {}", operation.method, operation.path_template))
+        .collect();
+    SpecCoverage { missing, extra }
+}
+
+/// A request built from the examples of an operation, together with its documented responses.
+#[derive(Debug, Clone)]
+pub struct ContractCase {
+    pub method: Method,
+    /// The path and query, like `/users/42?description=true`.
+    pub uri: &'static str,
+    pub content_type: Option<&'static str>,
+    pub body: Option<&'static str>,
+    /// The keys of the operation's `responses`, like `200`, `4XX` or `default`.
+    pub statuses: &'static [&'static str],
+}
+
+/// Sends the request of the case to the router, and panics if the status code of the response
+/// is not documented.
+pub async fn check_contract_case(router: Router, case: ContractCase) {
+    let mut request = Request::builder().method(case.method.clone()).uri(case.uri);
+    if let Some(content_type) = case.content_type {
+        request = request.header(CONTENT_TYPE, content_type);
+    }
+    let request = request
+        .body(case.body.map(Body::from).unwrap_or_else(Body::empty))
+        .expect("the contract case should be a valid request");
+
+    let response = router
+        .oneshot(request)
+        .await
+        .expect("the router should be infallible");
+
+    let status = response.status();
+    let code = status.as_str();
+    let documented = case.statuses.iter().any(|documented| {
+        *documented == code
+            || *documented == "default"
+            || (documented.len() == 3
+                && documented.ends_with("XX")
+                && documented.as_bytes()[0] == code.as_bytes()[0])
+    });
+    assert!(
+        documented,
+        "{} {} responded with undocumented status {status}, expected one of {:?}",
+        case.method, case.uri, case.statuses
+    );
+}
+
+/// A request to an operation for tests, which is filled in by the generated request builders
+/// with feature `test-helpers`, like `GetPosts::request().id(3).query_limit(10).build()`.
+#[cfg(feature = "test-helpers")]
+#[derive(Debug, Default)]
+pub struct TestRequest {
+    headers: axum::http::HeaderMap,
+    body: Body,
+}
+
+#[cfg(feature = "test-helpers")]
+impl TestRequest {
+    /// Adds a header, which panics if it is invalid.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = axum::http::HeaderName::try_from(name);
+        let name = name.expect("the header name should be valid");
+        let value = value.parse().expect("the header value should be valid");
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets the body, with its content type.
+    pub fn body(self, content_type: &str, body: impl Into<Body>) -> Self {
+        let mut this = self.header(CONTENT_TYPE.as_str(), content_type);
+        this.body = body.into();
+        this
+    }
+
+    /// Sets the body to the json of the value.
+    pub fn json_body<T: serde::Serialize>(self, body: &T) -> Self {
+        let body = serde_json::to_vec(body).expect("the body should be serializable");
+        self.body("application/json", body)
+    }
+
+    /// Sets the body to the form of the value.
+    pub fn form_body<T: serde::Serialize>(self, body: &T) -> Self {
+        let body = serde_urlencoded::to_string(body).expect("the body should be serializable");
+        self.body("application/x-www-form-urlencoded", body)
+    }
+
+    pub fn build(self, method: Method, uri: &str) -> Request<Body> {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(self.body)
+            .expect("the uri should be valid");
+        *request.headers_mut() = self.headers;
+        request
+    }
+}
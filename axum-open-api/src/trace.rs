@@ -0,0 +1,143 @@
+//! Tracing of the generated extractors, enabled with feature `tracing`. Without the feature,
+//! everything in this module is a no-op.
+
+use crate::{MatchedOperation, OperationMeta};
+#[cfg(feature = "tracing")]
+use std::task::{Context, Poll};
+use std::{fmt::Debug, future::Future, sync::OnceLock};
+#[cfg(feature = "tracing")]
+use tracing::{field::Empty, instrument::Instrumented, Instrument, Span};
+
+static REDACTION_HOOK: OnceLock<fn(&str) -> bool> = OnceLock::new();
+
+/// Sets the hook that decides which parameters are redacted when they are recorded, by name.
+///
+/// The hook can only be set once; returns `false` if it was already set.
+pub fn set_redaction_hook(hook: fn(&str) -> bool) -> bool {
+    REDACTION_HOOK.set(hook).is_ok()
+}
+
+/// The span of a generated extractor, named after the operationId (`otel.name`).
+#[derive(Debug, Clone)]
+pub struct ExtractionSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl ExtractionSpan {
+    /// Creates the span for the generated struct `T`.
+    pub fn new<T: OperationMeta>() -> Self {
+        #[cfg(feature = "tracing")]
+        {
+            let span = tracing::info_span!(
+                "oapi_extract",
+                otel.name = %crate::operation_name::<T>(),
+                http.request.method = %T::method(),
+                http.route = T::path_template(),
+                params = tracing::field::Empty,
+            );
+            Self { span }
+        }
+        #[cfg(not(feature = "tracing"))]
+        Self {}
+    }
+
+    /// Records the path- and query-parameters on the span, redacting them according to the
+    /// hook set with [`set_redaction_hook`].
+    pub fn record_params(&self, params: &[(&str, &dyn Debug)]) {
+        #[cfg(feature = "tracing")]
+        {
+            let redact = REDACTION_HOOK.get();
+            let params = params
+                .iter()
+                .map(|(name, value)| match redact {
+                    Some(redact) if redact(name) => format!("{name}=[redacted]"),
+                    _ => format!("{name}={value:?}"),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.span.record("params", params.as_str());
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = params;
+    }
+
+    /// Runs the future inside of the span.
+    pub async fn instrument<F: Future>(self, future: F) -> F::Output {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::Instrument::instrument(future, self.span).await
+        }
+        #[cfg(not(feature = "tracing"))]
+        future.await
+    }
+}
+
+/// Records the matched operation on the current span, following the OpenTelemetry HTTP
+/// semantic conventions. This is done by [`OapiRouter::oapi_route`](crate::OapiRouter), and
+/// is picked up by the span of the [`OtelLayer`].
+pub fn record_matched_operation(operation: &MatchedOperation) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = Span::current();
+        span.record(
+            "otel.name",
+            format!("{} {}", operation.method, operation.path_template).as_str(),
+        );
+        span.record("http.route", operation.path_template);
+        span.record("url.template", operation.path_template);
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = operation;
+}
+
+/// A layer that runs every request in a span with the OpenTelemetry HTTP attributes
+/// `http.request.method`, `http.route` and `url.template`. The route and template are the path
+/// templates of the spec, recorded for routes added with
+/// [`OapiRouter::oapi_route`](crate::OapiRouter).
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OtelLayer;
+
+#[cfg(feature = "tracing")]
+impl<S> tower::Layer<S> for OtelLayer {
+    type Service = OtelService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        OtelService { inner }
+    }
+}
+
+/// The service of the [`OtelLayer`].
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone)]
+pub struct OtelService<S> {
+    inner: S,
+}
+
+#[cfg(feature = "tracing")]
+impl<S, B> tower::Service<axum::http::Request<B>> for OtelService<S>
+where
+    S: tower::Service<axum::http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Instrumented<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: axum::http::Request<B>) -> Self::Future {
+        let span = tracing::info_span!(
+            "http_request",
+            otel.name = %req.method(),
+            otel.kind = "server",
+            http.request.method = %req.method(),
+            http.route = Empty,
+            url.path = req.uri().path(),
+            url.template = Empty,
+        );
+        self.inner.call(req).instrument(span)
+    }
+}
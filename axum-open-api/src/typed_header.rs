@@ -0,0 +1,33 @@
+//! Runtime support for `typed_headers = true;`, which extracts the standard header parameters
+//! of the spec, like `User-Agent` or `If-Modified-Since`, as the `TypedHeader` of their type in
+//! `headers` instead of a plain string.
+
+use crate::Rejection;
+use axum::{extract::FromRequestParts, http::request::Parts};
+pub use axum_extra::{headers, TypedHeader};
+use headers::Header;
+
+/// Extracts a required header, rejecting the request if it is missing or invalid.
+pub async fn typed_header<H>(parts: &mut Parts) -> Result<TypedHeader<H>, Rejection>
+where
+    H: Header + Send + 'static,
+{
+    TypedHeader::from_request_parts(parts, &())
+        .await
+        .map_err(|e| Rejection::Other(Box::new(e)))
+}
+
+/// Extracts an optional header, which is `None` if it is missing, but rejects the request if it
+/// is invalid.
+pub async fn optional_typed_header<H>(
+    parts: &mut Parts,
+) -> Result<Option<TypedHeader<H>>, Rejection>
+where
+    H: Header + Send + 'static,
+{
+    match TypedHeader::from_request_parts(parts, &()).await {
+        Ok(header) => Ok(Some(header)),
+        Err(e) if e.is_missing() => Ok(None),
+        Err(e) => Err(Rejection::Other(Box::new(e))),
+    }
+}
@@ -0,0 +1,94 @@
+//! Helpers for the uris built by the generated code.
+
+use serde::Serialize;
+use std::{borrow::Cow, fmt::Display};
+
+/// Percent-encodes a path parameter, so it stays a single segment of the path.
+pub fn encode_path_param(param: &dyn Display) -> String {
+    let mut encoded = String::new();
+    for byte in param.to_string().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Encodes the query parameters, skipping those that are `None`.
+pub fn encode_query<T: Serialize>(query: &T) -> String {
+    serde_urlencoded::to_string(query).expect("query parameters should be serializable")
+}
+
+/// The reserved characters that are sent as they are in parameters with `allowReserved`. The
+/// `&`, `#` and `+` are still encoded, since they would end the value, the query, or be read as a
+/// space.
+const RESERVED: &[u8] = b":/?[]@!$'()*,;=";
+
+/// Encodes the query parameters like [`encode_query`], without encoding the reserved characters
+/// in the values of the `reserved` parameters, which have `allowReserved`.
+pub fn encode_query_reserved<T: Serialize>(query: &T, reserved: &[&str]) -> String {
+    let query = encode_query(query);
+    let pairs = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            if !reserved.contains(&name) {
+                return pair.to_owned();
+            }
+            format!("{name}={}", decode_reserved(value))
+        });
+    pairs.collect::<Vec<_>>().join("&")
+}
+
+/// Decodes the reserved characters of an encoded value, and encodes its spaces as `%20`, since a
+/// `+` is a plus in the values of parameters with `allowReserved`.
+fn decode_reserved(value: &str) -> String {
+    // The encoded value is ascii, with every `%` followed by two hex digits
+    let mut decoded = String::new();
+    let mut i = 0;
+    while let Some(&byte) = value.as_bytes().get(i) {
+        match byte {
+            b'+' => decoded.push_str("%20"),
+            b'%' => {
+                let encoded = &value[i..i + 3];
+                match u8::from_str_radix(&encoded[1..], 16) {
+                    Ok(byte) if RESERVED.contains(&byte) => decoded.push(byte as char),
+                    _ => decoded.push_str(encoded),
+                }
+                i += 2;
+            }
+            byte => decoded.push(byte as char),
+        }
+        i += 1;
+    }
+    decoded
+}
+
+/// Percent-decodes a value from a uri or a cookie. A `%` that is not followed by two hex digits
+/// is kept as it is, and decoded bytes that are not utf-8 are replaced.
+pub(crate) fn percent_decode(value: &str) -> Cow<'_, str> {
+    if !value.contains('%') {
+        return Cow::Borrowed(value);
+    }
+    let bytes = value.as_bytes();
+    let hex_digit = |i: usize| bytes.get(i).and_then(|&byte| (byte as char).to_digit(16));
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while let Some(&byte) = bytes.get(i) {
+        match (byte, hex_digit(i + 1), hex_digit(i + 2)) {
+            (b'%', Some(high), Some(low)) => {
+                decoded.push((high * 16 + low) as u8);
+                i += 3;
+            }
+            _ => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Cow::Owned(String::from_utf8_lossy(&decoded).into_owned())
+}
@@ -0,0 +1,619 @@
+//! Runtime validation of requests and responses against the spec, enabled with feature
+//! `validation`.
+//!
+//! The generated extractors do not enforce everything in the spec, like `minLength` or
+//! `pattern`. The [`SpecValidationLayer`] re-validates requests at runtime, which is useful in
+//! staging to catch drift between the generated code and the spec. The `pattern` of strings is
+//! only checked with feature `pattern`.
+//!
+//! The [`ResponseValidationLayer`] checks that responses conform to the spec, which is meant for
+//! tests, so that contract violations are caught before they reach consumers. It only panics in
+//! debug builds, and reports the violations to stderr in release builds.
+
+use crate::spec::{Operation, SpecDocument};
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Query, Request},
+    http::{header::CONTENT_TYPE, request::Parts, response, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// The default limit of the buffered request body.
+const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// What to do with requests that violate the spec.
+#[derive(Debug, Clone, Copy)]
+pub enum ValidationMode {
+    /// Reject the request with `400 Bad Request`.
+    Reject,
+    /// Report the violations, and pass the request on.
+    Report(fn(&[String])),
+}
+
+/// A layer that validates the parameters, content type and body of requests against the spec.
+#[derive(Debug, Clone)]
+pub struct SpecValidationLayer {
+    spec: Arc<SpecDocument>,
+    mode: ValidationMode,
+    body_limit: usize,
+}
+
+impl SpecValidationLayer {
+    /// Creates a layer that rejects requests violating the spec.
+    pub fn new(spec: SpecDocument) -> Self {
+        Self {
+            spec: Arc::new(spec),
+            mode: ValidationMode::Reject,
+            body_limit: DEFAULT_BODY_LIMIT,
+        }
+    }
+
+    /// Sets what to do with requests that violate the spec.
+    pub fn mode(mut self, mode: ValidationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the limit of the buffered request body.
+    pub fn body_limit(mut self, body_limit: usize) -> Self {
+        self.body_limit = body_limit;
+        self
+    }
+}
+
+impl<S> tower::Layer<S> for SpecValidationLayer {
+    type Service = SpecValidationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SpecValidationService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The service of the [`SpecValidationLayer`].
+#[derive(Debug, Clone)]
+pub struct SpecValidationService<S> {
+    inner: S,
+    layer: SpecValidationLayer,
+}
+
+impl<S> tower::Service<Request> for SpecValidationService<S>
+where
+    S: tower::Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // The service that was polled ready must be used
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let Ok(body) = to_bytes(body, layer.body_limit).await else {
+                return Ok(StatusCode::PAYLOAD_TOO_LARGE.into_response());
+            };
+
+            let violations = validate_request(&layer.spec, &parts, &body);
+            if !violations.is_empty() {
+                match layer.mode {
+                    ValidationMode::Reject => {
+                        return Ok((StatusCode::BAD_REQUEST, violations.join("\n")).into_response())
+                    }
+                    ValidationMode::Report(report) => report(&violations),
+                }
+            }
+
+            inner
+                .call(Request::from_parts(parts, Body::from(body)))
+                .await
+        })
+    }
+}
+
+/// What to do with responses that violate the spec.
+#[derive(Debug, Clone, Copy)]
+pub enum ResponseValidationMode {
+    /// Panic, failing the test. Only available in debug builds, so a release build never panics
+    /// on a response.
+    #[cfg(any(test, debug_assertions))]
+    Panic,
+    /// Report the violations, and return the response.
+    Report(fn(&[String])),
+}
+
+/// A layer that validates the status code and json body of responses against the `responses`
+/// of the operation.
+#[derive(Debug, Clone)]
+pub struct ResponseValidationLayer {
+    spec: Arc<SpecDocument>,
+    mode: ResponseValidationMode,
+    body_limit: usize,
+}
+
+/// Responses violating the spec fail the tests of debug builds.
+#[cfg(any(test, debug_assertions))]
+const DEFAULT_RESPONSE_MODE: ResponseValidationMode = ResponseValidationMode::Panic;
+#[cfg(not(any(test, debug_assertions)))]
+const DEFAULT_RESPONSE_MODE: ResponseValidationMode =
+    ResponseValidationMode::Report(print_violations);
+
+#[cfg(not(any(test, debug_assertions)))]
+fn print_violations(violations: &[String]) {
+    eprintln!("Response violates the spec:\n{}", violations.join("\n"));
+}
+
+impl ResponseValidationLayer {
+    /// Creates a layer that panics on responses violating the spec in debug builds, and reports
+    /// them to stderr in release builds.
+    pub fn new(spec: SpecDocument) -> Self {
+        Self {
+            spec: Arc::new(spec),
+            mode: DEFAULT_RESPONSE_MODE,
+            body_limit: DEFAULT_BODY_LIMIT,
+        }
+    }
+
+    /// Sets what to do with responses that violate the spec.
+    pub fn mode(mut self, mode: ResponseValidationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the limit of the buffered response body.
+    pub fn body_limit(mut self, body_limit: usize) -> Self {
+        self.body_limit = body_limit;
+        self
+    }
+}
+
+impl<S> tower::Layer<S> for ResponseValidationLayer {
+    type Service = ResponseValidationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ResponseValidationService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// The service of the [`ResponseValidationLayer`].
+#[derive(Debug, Clone)]
+pub struct ResponseValidationService<S> {
+    inner: S,
+    layer: ResponseValidationLayer,
+}
+
+impl<S> tower::Service<Request> for ResponseValidationService<S>
+where
+    S: tower::Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // The service that was polled ready must be used
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+        let method = req.method().clone();
+        let path = req.uri().path().to_owned();
+
+        Box::pin(async move {
+            let (parts, body) = inner.call(req).await?.into_parts();
+            let Ok(body) = to_bytes(body, layer.body_limit).await else {
+                return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+            };
+
+            let violations = validate_response(&layer.spec, &method, &path, &parts, &body);
+            if !violations.is_empty() {
+                match layer.mode {
+                    #[cfg(any(test, debug_assertions))]
+                    ResponseValidationMode::Panic => {
+                        panic!("Response violates the spec:\n{}", violations.join("\n"))
+                    }
+                    ResponseValidationMode::Report(report) => report(&violations),
+                }
+            }
+
+            Ok(Response::from_parts(parts, Body::from(body)))
+        })
+    }
+}
+
+/// Validates the response to a request against the spec, returning the violations. Responses
+/// to requests that do not match an operation of the spec are not validated.
+pub fn validate_response(
+    spec: &SpecDocument,
+    method: &Method,
+    path: &str,
+    parts: &response::Parts,
+    body: &Bytes,
+) -> Vec<String> {
+    let Some((operation, _)) = spec.find_operation(method, path) else {
+        return Vec::new();
+    };
+    let prefix = format!("{method} {}", operation.path_template);
+    let status = parts.status.as_u16();
+
+    // The exact status code takes precedence over a range like `2XX`, which takes precedence
+    // over `default`
+    let responses = &operation.value["responses"];
+    let range = format!("{}XX", status / 100);
+    let Some(response) = [status.to_string(), range, "default".to_owned()]
+        .iter()
+        .find_map(|key| responses.get(key))
+        .map(|response| spec.resolve(response))
+    else {
+        return vec![format!("{prefix}: undocumented status code {status}")];
+    };
+
+    let content_type = parts
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .unwrap_or_default()
+        .trim();
+    let Some(content) = response["content"].as_object() else {
+        if body.is_empty() {
+            return Vec::new();
+        }
+        return vec![format!(
+            "{prefix}: undocumented body for status code {status}"
+        )];
+    };
+    let Some((media_type_name, media_type)) = content
+        .iter()
+        .find(|(media_type_name, _)| media_type_matches(media_type_name, content_type))
+    else {
+        return vec![format!(
+            "{prefix}: undocumented content type `{content_type}` for status code {status}"
+        )];
+    };
+
+    if !media_type_name.ends_with("json") {
+        return Vec::new();
+    }
+    match serde_json::from_slice::<Value>(body) {
+        Ok(value) => spec
+            .validate(&media_type["schema"], &value)
+            .into_iter()
+            .map(|violation| format!("{prefix}: response body: {violation}"))
+            .collect(),
+        Err(e) => vec![format!("{prefix}: invalid json response body: {e}")],
+    }
+}
+
+/// Validates the request against the spec, returning the violations. Requests that do not
+/// match an operation of the spec are not validated.
+pub fn validate_request(spec: &SpecDocument, parts: &Parts, body: &Bytes) -> Vec<String> {
+    let Some((operation, path_params)) = spec.find_operation(&parts.method, parts.uri.path())
+    else {
+        return Vec::new();
+    };
+    let prefix = format!("{} {}", parts.method, operation.path_template);
+
+    let mut violations = validate_params(spec, operation, parts, &path_params);
+    violations.extend(validate_body(spec, operation, parts, body));
+    violations
+        .into_iter()
+        .map(|violation| format!("{prefix}: {violation}"))
+        .collect()
+}
+
+fn validate_params(
+    spec: &SpecDocument,
+    operation: Operation<'_>,
+    parts: &Parts,
+    path_params: &[(&str, String)],
+) -> Vec<String> {
+    let query: Vec<(String, String)> = Query::try_from_uri(&parts.uri)
+        .map(|Query(query)| query)
+        .unwrap_or_default();
+
+    let mut violations = Vec::new();
+    for param in spec.parameters(operation) {
+        let Some(name) = param["name"].as_str() else {
+            continue;
+        };
+        let location = param["in"].as_str().unwrap_or_default();
+        let value = match location {
+            "path" => path_params
+                .iter()
+                .find(|(param_name, _)| *param_name == name)
+                .map(|(_, value)| value.as_str()),
+            "query" => query
+                .iter()
+                .find(|(param_name, _)| param_name == name)
+                .map(|(_, value)| value.as_str()),
+            "header" => parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok()),
+            _ => continue,
+        };
+
+        match value {
+            None if param["required"] == true => {
+                violations.push(format!("missing required {location} parameter `{name}`"))
+            }
+            None => {}
+            Some(value) => {
+                let schema = &param["schema"];
+                let value = coerce_param(spec, schema, value);
+                violations.extend(
+                    spec.validate(schema, &value)
+                        .into_iter()
+                        .map(|violation| format!("{location} parameter `{name}`: {violation}")),
+                );
+            }
+        }
+    }
+    violations
+}
+
+fn validate_body(
+    spec: &SpecDocument,
+    operation: Operation<'_>,
+    parts: &Parts,
+    body: &Bytes,
+) -> Vec<String> {
+    let Some(request_body) = operation.value.get("requestBody").map(|b| spec.resolve(b)) else {
+        return Vec::new();
+    };
+    if body.is_empty() {
+        if request_body["required"] == true {
+            return vec!["missing required request body".to_owned()];
+        }
+        return Vec::new();
+    }
+
+    let content_type = parts
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .unwrap_or_default()
+        .trim();
+    let Some((media_type_name, media_type)) = request_body["content"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .find(|(media_type_name, _)| media_type_matches(media_type_name, content_type))
+    else {
+        return vec![format!("unsupported content type `{content_type}`")];
+    };
+
+    if !media_type_name.ends_with("json") {
+        return Vec::new();
+    }
+    match serde_json::from_slice::<Value>(body) {
+        Ok(value) => spec
+            .validate(&media_type["schema"], &value)
+            .into_iter()
+            .map(|violation| format!("request body: {violation}"))
+            .collect(),
+        Err(e) => vec![format!("invalid json request body: {e}")],
+    }
+}
+
+/// Returns whether the content type matches a media type like `text/*` or `*/*`.
+fn media_type_matches(media_type: &str, content_type: &str) -> bool {
+    match media_type.split_once('/') {
+        Some(("*", "*")) => true,
+        Some((ty, "*")) => content_type.split('/').next() == Some(ty),
+        _ => media_type.eq_ignore_ascii_case(content_type),
+    }
+}
+
+/// Converts the string value of a parameter to json, according to its schema.
+fn coerce_param(spec: &SpecDocument, schema: &Value, value: &str) -> Value {
+    let schema = spec.resolve(schema);
+    let coerced = match schema["type"].as_str() {
+        Some("integer") => value.parse::<i64>().ok().map(Value::from),
+        Some("number") => value.parse::<f64>().ok().map(Value::from),
+        Some("boolean") => value.parse::<bool>().ok().map(Value::from),
+        Some("array") => Some(Value::Array(
+            value
+                .split(',')
+                .map(|item| coerce_param(spec, &schema["items"], item))
+                .collect(),
+        )),
+        _ => None,
+    };
+    coerced.unwrap_or_else(|| Value::from(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info: { title: Test, version: 0.0.1 }
+paths:
+  /users/{user_id}:
+    parameters:
+      - { in: path, name: user_id, required: true, schema: { type: integer } }
+    put:
+      parameters:
+        - { in: query, name: notify, schema: { type: boolean } }
+        - { in: query, name: tags, schema: { type: array, items: { type: integer } } }
+        - { in: header, name: X-Request-Id, required: true, schema: { type: string } }
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              type: object
+              required: [name]
+              properties:
+                name: { type: string, minLength: 1 }
+      responses:
+        "204": { description: Updated }
+"#;
+
+    fn validate(method: &str, uri: &str, request_id: Option<&str>, body: &str) -> Vec<String> {
+        let spec = SpecDocument::parse(SPEC).unwrap();
+        let mut req = Request::builder().method(method).uri(uri);
+        if let Some(request_id) = request_id {
+            req = req.header("x-request-id", request_id);
+        }
+        if !body.is_empty() {
+            req = req.header(CONTENT_TYPE, "application/json");
+        }
+        let (parts, ()) = req.body(()).unwrap().into_parts();
+        validate_request(&spec, &parts, &Bytes::copy_from_slice(body.as_bytes()))
+    }
+
+    #[test]
+    fn valid_request() {
+        let violations = validate(
+            "PUT",
+            "/users/1?notify=true&tags=1,2",
+            Some("a"),
+            r#"{"name":"a"}"#,
+        );
+        assert_eq!(violations, Vec::<String>::new());
+    }
+
+    #[test]
+    fn unknown_operations_are_not_validated() {
+        assert!(validate("GET", "/users/1", None, "").is_empty());
+        assert!(validate("PUT", "/posts/1", None, "").is_empty());
+    }
+
+    #[test]
+    fn invalid_params() {
+        let violations = validate(
+            "PUT",
+            "/users/a?notify=yes&tags=1,b",
+            None,
+            r#"{"name":"a"}"#,
+        );
+        assert_eq!(violations.len(), 4, "{violations:?}");
+        assert!(violations
+            .iter()
+            .all(|v| v.starts_with("PUT /users/{user_id}: ")));
+        assert!(violations
+            .iter()
+            .any(|v| v.contains("path parameter `user_id`")));
+        assert!(violations
+            .iter()
+            .any(|v| v.contains("query parameter `notify`")));
+        assert!(violations
+            .iter()
+            .any(|v| v.contains("query parameter `tags`: $[1]")));
+        assert!(violations
+            .iter()
+            .any(|v| v.contains("missing required header parameter")));
+    }
+
+    #[test]
+    fn invalid_bodies() {
+        let violations = validate("PUT", "/users/1", Some("a"), "");
+        assert_eq!(
+            violations,
+            ["PUT /users/{user_id}: missing required request body"]
+        );
+        let violations = validate("PUT", "/users/1", Some("a"), r#"{"name":""}"#);
+        assert_eq!(
+            violations,
+            ["PUT /users/{user_id}: request body: $.name: violates `minLength`"]
+        );
+        let violations = validate("PUT", "/users/1", Some("a"), "{");
+        assert!(
+            violations[0].contains("invalid json request body"),
+            "{violations:?}"
+        );
+    }
+
+    #[test]
+    fn param_coercion() {
+        let spec = SpecDocument::parse("{}").unwrap();
+        let coerce = |schema: Value, value| coerce_param(&spec, &schema, value);
+        let integer = serde_json::json!({ "type": "integer" });
+        assert_eq!(coerce(integer.clone(), "42"), Value::from(42));
+        // Values that can't be coerced stay strings, so they fail the validation of their type
+        assert_eq!(coerce(integer, "4.2"), Value::from("4.2"));
+        assert_eq!(
+            coerce(serde_json::json!({ "type": "number" }), "4.2"),
+            Value::from(4.2)
+        );
+        assert_eq!(
+            coerce(serde_json::json!({ "type": "boolean" }), "false"),
+            Value::from(false)
+        );
+        assert_eq!(
+            coerce(serde_json::json!({ "type": "string" }), "42"),
+            Value::from("42")
+        );
+        let array = serde_json::json!({ "type": "array", "items": { "type": "integer" } });
+        assert_eq!(coerce(array, "1,2,a"), serde_json::json!([1, 2, "a"]));
+    }
+
+    /// Responds to `PUT /users/1` with a body, which the spec does not document.
+    async fn undocumented_response(mode: Option<ResponseValidationMode>) -> Response {
+        use tower::ServiceExt;
+
+        let mut layer = ResponseValidationLayer::new(SpecDocument::parse(SPEC).unwrap());
+        if let Some(mode) = mode {
+            layer = layer.mode(mode);
+        }
+        let router = axum::Router::new()
+            .route("/users/:user_id", axum::routing::put(|| async { "body" }))
+            .layer(layer);
+        let request = Request::put("/users/1").body(Body::empty()).unwrap();
+        router.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Response violates the spec")]
+    async fn invalid_responses_panic_in_debug_builds() {
+        undocumented_response(None).await;
+    }
+
+    #[tokio::test]
+    async fn invalid_responses_can_be_reported() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static REPORTED: AtomicUsize = AtomicUsize::new(0);
+        fn report(violations: &[String]) {
+            assert_eq!(
+                violations,
+                ["PUT /users/{user_id}: undocumented status code 200"]
+            );
+            REPORTED.fetch_add(1, Ordering::SeqCst);
+        }
+        let response = undocumented_response(Some(ResponseValidationMode::Report(report))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(REPORTED.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,100 @@
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    Router,
+};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use schemas::User;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+    api_trait = Api;
+
+    GET     /users              as pub ListUsers;
+    POST    /users              as pub CreateUser;
+    GET     /users/{user_id}    as pub GetUser;
+    DELETE  /users/{user_id}    as pub DeleteUser;
+    GET     /health             as pub GetHealth;
+);
+
+#[derive(Clone)]
+struct TestApi;
+
+#[axum::async_trait]
+impl Api for TestApi {
+    async fn list_users(&self, _req: ListUsers) -> ListUsersResponse {
+        ListUsersResponse::Status200(Vec::new())
+    }
+
+    async fn create_user(&self, req: CreateUser) -> CreateUserResponse {
+        CreateUserResponse::Status201(req.body)
+    }
+
+    async fn get_user(&self, req: GetUser) -> GetUserResponse {
+        match req.user_id {
+            1 => GetUserResponse::Status200(User {
+                id: 1,
+                name: "Ann".to_owned(),
+            }),
+            _ => GetUserResponse::Status404,
+        }
+    }
+
+    async fn delete_user(&self, _req: DeleteUser) -> DeleteUserResponse {
+        DeleteUserResponse::Status204
+    }
+
+    async fn get_health(&self, _req: GetHealth) -> GetHealthResponse {
+        GetHealthResponse::Status200("ok".to_owned())
+    }
+}
+
+async fn send(router: Router, request: Request<Body>) -> (StatusCode, Option<String>, String) {
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response.headers().get(CONTENT_TYPE);
+    let content_type = content_type.map(|value| value.to_str().unwrap().to_owned());
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        content_type,
+        String::from_utf8(bytes.to_vec()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn routed_operations() {
+    let router = into_router(TestApi);
+
+    let request = Request::get("/users/1").body(Body::empty()).unwrap();
+    let (status, content_type, body) = send(router.clone(), request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/json"));
+    assert_eq!(body, r#"{"id":1,"name":"Ann"}"#);
+
+    let request = Request::get("/users/2").body(Body::empty()).unwrap();
+    assert_eq!(send(router.clone(), request).await.0, StatusCode::NOT_FOUND);
+
+    let request = Request::post("/users")
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"id":2,"name":"Bob"}"#))
+        .unwrap();
+    let (status, _, body) = send(router.clone(), request).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body, r#"{"id":2,"name":"Bob"}"#);
+
+    let request = Request::delete("/users/2").body(Body::empty()).unwrap();
+    assert_eq!(
+        send(router.clone(), request).await.0,
+        StatusCode::NO_CONTENT
+    );
+
+    // Text responses have the media type of the spec
+    let request = Request::get("/health").body(Body::empty()).unwrap();
+    let (status, content_type, body) = send(router, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/plain"));
+    assert_eq!(body, "ok");
+}
@@ -0,0 +1,28 @@
+use axum::{body::Body, http::Request, Router};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/events.yaml";
+
+    POST /subscriptions as pub CreateSubscription;
+);
+
+#[tokio::test]
+async fn inline_body_named_after_the_operation_id() {
+    // The untitled body of `createSubscription`
+    let router = Router::new().oapi_route(|req: CreateSubscription| async move {
+        let body: CreateSubscriptionBody = req.body;
+        body.callback_url
+    });
+
+    let request = Request::post("/subscriptions")
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"callbackUrl":"https://example.com"}"#))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "https://example.com");
+}
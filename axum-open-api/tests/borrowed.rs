@@ -0,0 +1,55 @@
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    Router,
+};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use schemas::{User, UserRef};
+use std::borrow::Cow;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    #[borrowed]
+    POST /users as pub CreateUser;
+);
+
+async fn create_user(req: CreateUser) -> String {
+    let user: UserRef<'_> = req.body.get();
+    let borrowed = matches!(user.name, Cow::Borrowed(_));
+    let user: User = user.into_owned();
+    format!("{} {} {borrowed}", user.id, user.name)
+}
+
+async fn send(body: &'static str) -> (StatusCode, String) {
+    let request = Request::post("/users")
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = Router::new()
+        .oapi_route(create_user)
+        .oneshot(request)
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn borrowed_bodies() {
+    let (status, body) = send(r#"{"id":2,"name":"Bob"}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "2 Bob true");
+    // Escaped strings can't be borrowed
+    assert_eq!(send(r#"{"id":2,"name":"B\u006fb"}"#).await.1, "2 Bob false");
+}
+
+#[tokio::test]
+async fn invalid_bodies() {
+    let (status, _) = send(r#"{"id":"two","name":"Bob"}"#).await;
+    assert!(status.is_client_error(), "{status}");
+}
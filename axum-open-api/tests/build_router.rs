@@ -0,0 +1,53 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::NoContent;
+use axum_open_api_codegen::validate_routes;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+    build_router = build_router;
+
+    GET     /users/{user_id}    as pub GetUser;
+    DELETE  /users/{user_id}    as pub DeleteUser;
+    GET     /health             as pub GetHealth;
+);
+
+async fn get_user(req: GetUser) -> String {
+    req.user_id.to_string()
+}
+
+async fn delete_user(_req: DeleteUser) -> NoContent {
+    NoContent
+}
+
+async fn get_health(_req: GetHealth) -> &'static str {
+    "ok"
+}
+
+async fn status(router: Router, request: Request<Body>) -> StatusCode {
+    router.oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn built_router() {
+    // The handlers can be in any order
+    let router: Router = build_router!(
+        GetHealth => get_health,
+        GetUser => get_user,
+        DeleteUser => delete_user,
+    );
+
+    let request = Request::get("/users/1").body(Body::empty()).unwrap();
+    assert_eq!(status(router.clone(), request).await, StatusCode::OK);
+    let request = Request::delete("/users/1").body(Body::empty()).unwrap();
+    assert_eq!(
+        status(router.clone(), request).await,
+        StatusCode::NO_CONTENT
+    );
+    let request = Request::get("/health").body(Body::empty()).unwrap();
+    assert_eq!(status(router, request).await, StatusCode::OK);
+}
@@ -0,0 +1,28 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::{ApiVersion, Status, User};
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+);
+
+#[test]
+fn struct_builders() {
+    // Optional fields can be left out
+    let user = User::builder()
+        .id(1)
+        .name("Ann")
+        .version(ApiVersion)
+        .build();
+    assert_eq!((user.id, user.name.as_str(), user.email), (1, "Ann", None));
+
+    // The fields can be set in any order
+    let user = User::builder()
+        .status(Status::Active)
+        .version(ApiVersion)
+        .name("Bob")
+        .email("bob@example.com")
+        .id(2)
+        .build();
+    assert_eq!(user.email.as_deref(), Some("bob@example.com"));
+    assert_eq!(user.status, Some(Status::Active));
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "client")]
+
+use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use axum_open_api::client::reqwest::Client;
+use axum_open_api_codegen::validate_routes;
+use schemas::Event;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+validate_routes!(
+    path = "axum-open-api/tests/events.yaml";
+    client = ApiClient;
+
+    POST /subscriptions as pub CreateSubscription;
+);
+
+/// The kinds of the received events.
+type Received = Arc<Mutex<Vec<String>>>;
+
+async fn receive_event(State(received): State<Received>, Json(event): Json<Event>) -> StatusCode {
+    received.lock().unwrap().push(event.kind);
+    StatusCode::NO_CONTENT
+}
+
+#[tokio::test]
+async fn callback_senders() {
+    // The subscriber of the callbacks
+    let received = Received::default();
+    let router = Router::new()
+        .route("/hooks/events", post(receive_event))
+        .with_state(received.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    // The runtime expression of the url is an argument of the sender
+    let event = Event {
+        kind: "created".to_owned(),
+    };
+    let callback_url = format!("http://{address}/hooks");
+    let response = CreateSubscription::send_on_event_post(&Client::new(), &callback_url, &event)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(*received.lock().unwrap(), ["created"]);
+}
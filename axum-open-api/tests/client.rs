@@ -0,0 +1,151 @@
+#![cfg(feature = "client")]
+
+use axum::{http::StatusCode, Json, Router};
+use axum_open_api::{NoContent, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use schemas::User;
+use tokio::net::TcpListener;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+    client = ApiClient;
+
+    GET     /users              as pub ListUsers;
+    POST    /users              as pub CreateUser;
+    GET     /users/{user_id}    as pub GetUser;
+    DELETE  /users/{user_id}    as pub DeleteUser;
+    GET     /health             as pub GetHealth;
+);
+
+fn ann() -> User {
+    User {
+        id: 1,
+        name: "Ann".to_owned(),
+    }
+}
+
+async fn list_users(req: ListUsers) -> Json<Vec<User>> {
+    // Only the first page has users
+    Json(match req.page {
+        None | Some(1) => vec![ann()],
+        Some(_) => Vec::new(),
+    })
+}
+
+async fn create_user(req: CreateUser) -> (StatusCode, Json<User>) {
+    (StatusCode::CREATED, Json(req.body))
+}
+
+async fn get_user(req: GetUser) -> Result<Json<User>, StatusCode> {
+    match req.user_id {
+        1 => Ok(Json(ann())),
+        _ => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+async fn delete_user(_req: DeleteUser) -> NoContent {
+    NoContent
+}
+
+async fn get_health(_req: GetHealth) -> StatusCode {
+    StatusCode::SERVICE_UNAVAILABLE
+}
+
+/// Serves the api on a random port, and returns a client for it.
+async fn client() -> ApiClient {
+    let router = Router::new()
+        .oapi_route(list_users)
+        .oapi_route(create_user)
+        .oapi_route(get_user)
+        .oapi_route(delete_user)
+        .oapi_route(get_health);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    ApiClient::new(format!("http://{address}"))
+}
+
+#[tokio::test]
+async fn documented_responses() {
+    let client = client().await;
+    match client.get_user(1).await.unwrap() {
+        GetUserResponse::Status200(user) => assert_eq!(user.name, "Ann"),
+        other => panic!("{other:?}"),
+    }
+    assert!(matches!(
+        client.get_user(2).await.unwrap(),
+        GetUserResponse::Status404
+    ));
+
+    let bob = User {
+        id: 2,
+        name: "Bob".to_owned(),
+    };
+    match client.create_user(bob).await.unwrap() {
+        CreateUserResponse::Status201(user) => assert_eq!(user.name, "Bob"),
+        other => panic!("{other:?}"),
+    }
+    assert!(matches!(
+        client.delete_user(2).await.unwrap(),
+        DeleteUserResponse::Status204
+    ));
+}
+
+#[tokio::test]
+async fn query_params() {
+    let client = client().await;
+    match client.list_users(Some(2)).await.unwrap() {
+        ListUsersResponse::Status200(users) => assert!(users.is_empty()),
+        other => panic!("{other:?}"),
+    }
+    match client.list_users(None).await.unwrap() {
+        ListUsersResponse::Status200(users) => assert_eq!(users[0].name, "Ann"),
+        other => panic!("{other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn undocumented_responses() {
+    match client().await.get_health().await.unwrap() {
+        GetHealthResponse::Other(status, _) => {
+            assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE)
+        }
+        other => panic!("{other:?}"),
+    }
+}
+
+mod search {
+    use axum::{http::StatusCode, Router};
+    use axum_open_api::OapiRouter;
+    use axum_open_api_codegen::validate_routes;
+    use tokio::net::TcpListener;
+
+    validate_routes!(
+        path = "axum-open-api/tests/params.yaml";
+        client = SearchClient;
+
+        GET /search as pub Search;
+    );
+
+    async fn search(req: Search) -> StatusCode {
+        match req.path.as_deref() {
+            Some("a/b?c") => StatusCode::NO_CONTENT,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    #[tokio::test]
+    async fn reserved_query_params() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let router = Router::new().oapi_route(search);
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        let client = SearchClient::new(format!("http://{address}"));
+        let response = client
+            .search(None, None, None, Some("a/b?c".to_owned()))
+            .await
+            .unwrap();
+        assert!(matches!(response, SearchResponse::Status204));
+    }
+}
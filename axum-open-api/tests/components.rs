@@ -0,0 +1,22 @@
+use axum_open_api_codegen::validate_routes;
+
+// The unreferenced schema would fail the compilation
+validate_routes!(
+    path = "axum-open-api/tests/components.yaml";
+    components = referenced;
+
+    GET /pets as pub ListPets;
+);
+
+#[test]
+fn referenced_components() {
+    // Schemas referenced by other referenced schemas are generated too
+    let pet = schemas::Pet {
+        name: "Rex".to_owned(),
+        owner: schemas::Owner {
+            name: "Ann".to_owned(),
+        },
+    };
+    let response = ListPetsOk(vec![pet]);
+    assert_eq!(response.0[0].owner.name, "Ann");
+}
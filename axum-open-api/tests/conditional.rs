@@ -0,0 +1,77 @@
+use axum::{
+    body::Body,
+    http::{header::ETAG, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use axum_open_api::{conditional::EntityTag, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use schemas::Payment;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/http.yaml";
+
+    GET /payments/{payment_id} as pub GetPayment;
+    PUT /payments/{payment_id} as pub UpdatePayment;
+);
+
+fn current() -> EntityTag {
+    EntityTag::strong("v2")
+}
+
+async fn get_payment(req: GetPayment) -> Response {
+    if let Err(failed) = req.check_preconditions(Some(&current())) {
+        return failed.into_response();
+    }
+    let payment = Payment {
+        id: req.payment_id,
+        amount: 100,
+    };
+    (current(), Json(payment)).into_response()
+}
+
+async fn update_payment(req: UpdatePayment) -> Response {
+    match req.check_preconditions(Some(&current())) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(failed) => failed.into_response(),
+    }
+}
+
+async fn send(request: Request<Body>) -> Response {
+    let router = Router::new()
+        .oapi_route(get_payment)
+        .oapi_route(update_payment);
+    router.oneshot(request).await.unwrap()
+}
+
+fn request(method: &str, header: Option<(&str, &str)>) -> Request<Body> {
+    let mut request = Request::builder().method(method).uri("/payments/1");
+    if let Some((name, value)) = header {
+        request = request.header(name, value);
+    }
+    if method == "PUT" {
+        request = request.header("Content-Type", "application/json");
+        return request
+            .body(Body::from(r#"{"id":1,"amount":200}"#))
+            .unwrap();
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn conditional_requests() {
+    let response = send(request("GET", None)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[ETAG], r#""v2""#);
+    let response = send(request("GET", Some(("If-None-Match", r#""v2""#)))).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+    let response = send(request("PUT", Some(("If-Match", r#""v1", "v2""#)))).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = send(request("PUT", Some(("If-Match", r#""v1""#)))).await;
+    assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    // Headers that are not entity tags are rejected
+    let response = send(request("PUT", Some(("If-Match", "v1")))).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
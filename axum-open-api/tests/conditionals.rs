@@ -0,0 +1,19 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::Payment;
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+);
+
+#[test]
+fn dependent_properties() {
+    let payment = r#"{"type":"card","cardNumber":"4111"}"#;
+    let payment = serde_json::from_str::<Payment>(payment).unwrap();
+    assert_eq!(payment.card_number.as_deref(), Some("4111"));
+    let payment = r#"{"type":"transfer","iban":"NL00"}"#;
+    assert!(serde_json::from_str::<Payment>(payment).is_ok());
+
+    // A card needs a `cardNumber`, and other payments an `iban`
+    assert!(serde_json::from_str::<Payment>(r#"{"type":"card","iban":"NL00"}"#).is_err());
+    assert!(serde_json::from_str::<Payment>(r#"{"type":"transfer"}"#).is_err());
+}
@@ -0,0 +1,18 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::ApiVersion;
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+);
+
+#[test]
+fn constant_schemas() {
+    assert_eq!(ApiVersion::VALUE, "v1");
+    assert_eq!(serde_json::to_string(&ApiVersion).unwrap(), r#""v1""#);
+    assert_eq!(
+        serde_json::from_str::<ApiVersion>(r#""v1""#).unwrap(),
+        ApiVersion
+    );
+    // Other values are rejected
+    assert!(serde_json::from_str::<ApiVersion>(r#""v2""#).is_err());
+}
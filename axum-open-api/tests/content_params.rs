@@ -0,0 +1,49 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::{extract::JsonParam, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use schemas::Filter;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/params.yaml";
+
+    GET /items as pub ListItems;
+);
+
+async fn list_items(req: ListItems) -> String {
+    format!("{:?}", req.filter.map(|filter| filter.0.status))
+}
+
+async fn send(uri: &str) -> (StatusCode, String) {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    let response = Router::new()
+        .oapi_route(list_items)
+        .oneshot(request)
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn json_params() {
+    let uri = ListItems::uri(&Some(JsonParam(Filter {
+        status: Some("active".to_owned()),
+    })));
+    assert_eq!(uri, "/items?filter=%7B%22status%22%3A%22active%22%7D");
+    let (status, body) = send(&uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, r#"Some(Some("active"))"#);
+
+    assert_eq!(send("/items").await.1, "None");
+    assert_eq!(
+        send("/items?filter=active").await.0,
+        StatusCode::BAD_REQUEST
+    );
+}
@@ -0,0 +1,51 @@
+use axum::{http::StatusCode, Json, Router};
+use axum_open_api::{NoContent, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use schemas::User;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+    contract_tests = crate::router;
+
+    GET     /users              as pub ListUsers;
+    POST    /users              as pub CreateUser;
+    GET     /users/{user_id}    as pub GetUser;
+    DELETE  /users/{user_id}    as pub DeleteUser;
+    GET     /health             as pub GetHealth;
+);
+
+async fn list_users(_req: ListUsers) -> Json<Vec<User>> {
+    Json(Vec::new())
+}
+
+async fn create_user(req: CreateUser) -> (StatusCode, Json<User>) {
+    (StatusCode::CREATED, Json(req.body))
+}
+
+async fn get_user(req: GetUser) -> Result<Json<User>, StatusCode> {
+    match req.user_id {
+        1 => Ok(Json(User {
+            id: 1,
+            name: "Ann".to_owned(),
+        })),
+        _ => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+async fn delete_user(_req: DeleteUser) -> NoContent {
+    NoContent
+}
+
+async fn get_health(_req: GetHealth) -> &'static str {
+    "ok"
+}
+
+/// The router that the generated contract tests send the examples of the spec to.
+fn router() -> Router {
+    Router::new()
+        .oapi_route(list_users)
+        .oapi_route(create_user)
+        .oapi_route(get_user)
+        .oapi_route(delete_user)
+        .oapi_route(get_health)
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "cors")]
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, Request, StatusCode},
+    Router,
+};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/cors.yaml";
+    cors_layer = cors_layer;
+
+    GET /users as pub ListUsers;
+);
+
+async fn preflight(origin: &str) -> HeaderMap {
+    let router = Router::new()
+        .oapi_route(|_req: ListUsers| async { StatusCode::NO_CONTENT })
+        .layer(cors_layer());
+    let request = Request::options("/users")
+        .header("Origin", origin)
+        .header("Access-Control-Request-Method", "GET")
+        .body(Body::empty())
+        .unwrap();
+    router.oneshot(request).await.unwrap().headers().clone()
+}
+
+#[tokio::test]
+async fn cors_policy() {
+    let headers = preflight("https://example.com").await;
+    let header = |name: &str| headers.get(name).map(|value| value.to_str().unwrap());
+    assert_eq!(
+        header("access-control-allow-origin"),
+        Some("https://example.com")
+    );
+    // Without `allowMethods`, the methods of the spec are allowed
+    assert_eq!(header("access-control-allow-methods"), Some("GET,POST"));
+    assert_eq!(
+        header("access-control-allow-headers"),
+        Some("authorization,content-type")
+    );
+    assert_eq!(header("access-control-allow-credentials"), Some("true"));
+    assert_eq!(header("access-control-max-age"), Some("3600"));
+
+    let headers = preflight("https://other.example.com").await;
+    assert!(headers.get("access-control-allow-origin").is_none());
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "spec")]
+
+use axum_open_api::{
+    spec::SpecDocument,
+    testing::{assert_covers_spec, RouteTable},
+    OapiRouter,
+};
+use axum_open_api_codegen::validate_routes;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users              as pub ListUsers;
+    POST    /users              as pub CreateUser;
+    GET     /users/{user_id}    as pub GetUser;
+    DELETE  /users/{user_id}    as pub DeleteUser;
+    GET     /health             as pub GetHealth;
+);
+
+const SPEC: &[u8] = include_bytes!("operations.yaml");
+
+async fn list_users(_req: ListUsers) {}
+
+async fn create_user(_req: CreateUser) {}
+
+async fn get_user(_req: GetUser) {}
+
+async fn delete_user(_req: DeleteUser) {}
+
+async fn get_health(_req: GetHealth) {}
+
+/// The routes without `GET /health`.
+fn routes() -> RouteTable {
+    RouteTable::new()
+        .oapi_route(list_users)
+        .oapi_route(create_user)
+        .oapi_route(get_user)
+        .oapi_route(delete_user)
+}
+
+#[test]
+fn covered_spec() {
+    assert_covers_spec(&routes().oapi_route(get_health), SPEC);
+}
+
+#[test]
+#[should_panic(expected = "declared in the spec but never registered: [\"GET /health\"]")]
+fn uncovered_spec() {
+    assert_covers_spec(&routes(), SPEC);
+}
+
+#[test]
+fn implemented_spec() {
+    let spec = SpecDocument::parse(include_str!("operations.yaml")).unwrap();
+    let table: RouteTable = RouteTable::new()
+        .oapi_route(get_user)
+        .oapi_route(get_health);
+    let spec = spec.retain_operations(table.operations());
+
+    // Path items without registered operations are removed
+    let paths = &spec.root()["paths"];
+    assert!(paths.get("/users").is_none());
+    assert!(paths["/users/{user_id}"].get("get").is_some());
+    assert!(paths["/users/{user_id}"].get("delete").is_none());
+    assert!(paths["/health"].get("get").is_some());
+    // The rest of the spec is kept
+    assert!(spec.root()["components"]["schemas"].get("User").is_some());
+}
@@ -0,0 +1,38 @@
+use axum_open_api_codegen::{expand_validate_routes, validate_routes};
+
+// The doc comments of declarations compile, including those of webhooks
+validate_routes!(
+    path = "axum-open-api/tests/events.yaml";
+
+    /// Subscribes to the events.
+    ///
+    /// Subscriptions expire after a day.
+    POST /subscriptions as pub CreateSubscription;
+
+    /// Receives an event.
+    POST webhook newEvent as pub NewEvent;
+);
+
+#[test]
+fn declaration_docs() {
+    let expanded = expand_validate_routes!(
+        path = "axum-open-api/tests/events.yaml";
+
+        /// Subscribes to the events.
+        ///
+        /// Subscriptions expire after a day.
+        POST /subscriptions as pub CreateSubscription;
+
+        /// Receives an event.
+        POST webhook newEvent as pub NewEvent;
+    );
+
+    // They come before the docs from the spec
+    let subscription =
+        "/// Subscribes to the events.\n///\n/// Subscriptions expire after a day.\n\
+        /**\nGenerated from OpenAPI spec.\n- Method: `POST /subscriptions`";
+    assert!(expanded.contains(subscription), "{expanded}");
+    let event =
+        "/// Receives an event.\n/**\nGenerated from OpenAPI spec.\n- Method: `POST newEvent`";
+    assert!(expanded.contains(event), "{expanded}");
+}
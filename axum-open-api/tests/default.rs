@@ -0,0 +1,16 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::{Filter, Status};
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+);
+
+#[test]
+fn optional_structs_default() {
+    // Every field of `Filter` is optional
+    let filter = Filter {
+        status: Some(Status::Active),
+        ..Default::default()
+    };
+    assert_eq!((filter.name, filter.status), (None, Some(Status::Active)));
+}
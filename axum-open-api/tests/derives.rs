@@ -0,0 +1,42 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::{NewUser, User};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+validate_routes!(
+    path = "axum-open-api/tests/derives.yaml";
+    derives = usage;
+
+    POST /users as pub CreateUser;
+);
+
+/// Whether `T` implements the serde traits, by the inherent constants that shadow the trait's
+/// constants only if `T` implements them.
+struct Derives<T>(PhantomData<T>);
+
+trait NotDerived {
+    const SERIALIZE: bool = false;
+    const DESERIALIZE: bool = false;
+}
+
+impl<T> NotDerived for Derives<T> {}
+
+impl<T: Serialize> Derives<T> {
+    const SERIALIZE: bool = true;
+}
+
+impl<T: DeserializeOwned> Derives<T> {
+    const DESERIALIZE: bool = true;
+}
+
+#[test]
+fn derives_by_usage() {
+    // The request body is only deserialized, and the response only serialized
+    let new_user = (
+        Derives::<NewUser>::SERIALIZE,
+        Derives::<NewUser>::DESERIALIZE,
+    );
+    assert_eq!(new_user, (false, true));
+    let user = (Derives::<User>::SERIALIZE, Derives::<User>::DESERIALIZE);
+    assert_eq!(user, (true, false));
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "docs")]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::docs::{docs_router, DocsUi};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET /health as pub GetHealth;
+);
+
+async fn get(router: Router, uri: &str) -> (StatusCode, String) {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn swagger_ui() {
+    let router = docs_router(DocsUi::SwaggerUi, OPENAPI_SPEC_JSON);
+    let (status, page) = get(router.clone(), "/docs").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains(r#"url: "docs/openapi.json""#), "{page}");
+
+    let (status, spec) = get(router, "/docs/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(spec, OPENAPI_SPEC_JSON);
+}
+
+#[tokio::test]
+async fn redoc() {
+    // The spec url is relative, so the router can be nested
+    let router = Router::new().nest("/api", docs_router(DocsUi::Redoc, OPENAPI_SPEC_JSON));
+    let (status, page) = get(router.clone(), "/api/docs").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        page.contains(r#"<redoc spec-url="docs/openapi.json">"#),
+        "{page}"
+    );
+    assert_eq!(
+        get(router, "/api/docs/openapi.json").await.0,
+        StatusCode::OK
+    );
+}
@@ -0,0 +1,64 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use errors::ApiError;
+use http_body_util::BodyExt;
+use schemas::{Error, ValidationError};
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/errors.yaml";
+    errors = errors;
+
+    GET /orders/{order_id} as pub GetOrder;
+    PUT /orders/{order_id} as pub UpdateOrder;
+);
+
+/// An error of the application, converted into the documented responses in one place.
+enum OrderError {
+    Missing,
+    InvalidField(&'static str),
+}
+
+impl From<OrderError> for ApiError {
+    fn from(error: OrderError) -> Self {
+        match error {
+            OrderError::Missing => ApiError::NotFound(Error {
+                message: "No such order".to_owned(),
+            }),
+            OrderError::InvalidField(field) => {
+                ApiError::UnprocessableEntityValidationError(ValidationError {
+                    fields: vec![field.to_owned()],
+                })
+            }
+        }
+    }
+}
+
+async fn update_order(_req: UpdateOrder) -> Result<StatusCode, ApiError> {
+    Err(OrderError::InvalidField("total"))?
+}
+
+#[tokio::test]
+async fn error_catalog() {
+    // The status shared by two schemas is named after both
+    let error = ApiError::UnprocessableEntityError(Error {
+        message: "Invalid".to_owned(),
+    });
+    assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        ApiError::from(OrderError::Missing).status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let router = Router::new().oapi_route(update_order);
+    let request = Request::put("/orders/1").body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, r#"{"fields":["total"]}"#);
+}
@@ -0,0 +1,34 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::Invoice;
+
+validate_routes!(
+    path = "axum-open-api/tests/extensions.yaml";
+
+    GET /invoices as pub GetInvoices;
+);
+
+#[test]
+fn operation_extensions() {
+    // Strings, booleans and numbers keep their type
+    let extensions = (GetInvoices::X_RATE_LIMIT_TIER, GetInvoices::X_CACHEABLE);
+    assert_eq!(extensions, ("gold", true));
+}
+
+#[test]
+fn schema_extensions() {
+    let extensions = (Invoice::X_TABLE, Invoice::X_AUDITED, Invoice::X_VERSION);
+    assert_eq!(extensions, ("invoices", true, 2));
+    assert_eq!(Invoice::X_SAMPLE_RATE, 0.5);
+    // Other values are json
+    assert_eq!(Invoice::X_OWNERS, r#"["billing","finance"]"#);
+}
+
+#[test]
+fn schema_modules() {
+    let payment = schemas::billing::payments::Payment {
+        invoice: Invoice { id: 1 },
+    };
+    // The schema is re-exported from `schemas`
+    let payment: schemas::Payment = payment;
+    assert_eq!(payment.invoice.id, 1);
+}
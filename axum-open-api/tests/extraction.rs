@@ -0,0 +1,63 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::{extract::query_params, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use serde::Deserialize;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users              as pub ListUsers;
+    POST    /users              as pub CreateUser;
+    GET     /users/{user_id}    as pub GetUser;
+);
+
+fn router() -> Router {
+    Router::new()
+        .oapi_route(|_req: ListUsers| async {})
+        .oapi_route(|_req: CreateUser| async {})
+        .oapi_route(|_req: GetUser| async {})
+}
+
+async fn status(request: Request<Body>) -> StatusCode {
+    router().oneshot(request).await.unwrap().status()
+}
+
+fn create_user(content_type: &str, body: &'static str) -> Request<Body> {
+    let request = Request::post("/users").header("Content-Type", content_type);
+    request.body(Body::from(body)).unwrap()
+}
+
+#[tokio::test]
+async fn rejections() {
+    let request = Request::get("/users/x").body(Body::empty()).unwrap();
+    assert_eq!(status(request).await, StatusCode::BAD_REQUEST);
+    let request = Request::get("/users?page=x").body(Body::empty()).unwrap();
+    assert_eq!(status(request).await, StatusCode::BAD_REQUEST);
+
+    let request = create_user("application/json", r#"{"id":1,"name":"Ann"}"#);
+    assert_eq!(status(request).await, StatusCode::OK);
+    let request = create_user("application/json", r#"{"id":1}"#);
+    assert_eq!(status(request).await, StatusCode::UNPROCESSABLE_ENTITY);
+    let request = create_user("text/plain", r#"{"id":1,"name":"Ann"}"#);
+    assert_eq!(status(request).await, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[test]
+fn shared_query_helper() {
+    #[derive(Deserialize)]
+    struct Page {
+        page: Option<i64>,
+    }
+
+    let (parts, _) = Request::get("/users?page=3").body(()).unwrap().into_parts();
+    let query = query_params::<Page>(&parts).unwrap();
+    assert_eq!(query.page, Some(3));
+
+    let (parts, _) = Request::get("/users?page=x").body(()).unwrap().into_parts();
+    assert!(query_params::<Page>(&parts).is_err());
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "fuzz")]
+
+use axum::http::StatusCode;
+use axum_open_api::fuzz::extract;
+use axum_open_api_codegen::validate_routes;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users              as pub ListUsers;
+    POST    /users              as pub CreateUser;
+    GET     /users/{user_id}    as pub GetUser;
+);
+
+fn status(response: Option<axum::response::Response>) -> StatusCode {
+    response.expect("the input should be a request").status()
+}
+
+#[test]
+fn fuzzer_inputs() {
+    assert_eq!(status(extract::<GetUser, _>(b"1\n")), StatusCode::OK);
+    assert_eq!(
+        status(extract::<GetUser, _>(b"one\n")),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(status(extract::<ListUsers, _>(b"\npage=2")), StatusCode::OK);
+    assert_eq!(
+        status(extract::<ListUsers, _>(b"\npage=x")),
+        StatusCode::BAD_REQUEST
+    );
+
+    let input = b"\n\ncontent-type: application/json\n\n{\"id\":2,\"name\":\"Bob\"}";
+    assert_eq!(status(extract::<CreateUser, _>(input)), StatusCode::OK);
+    let input = b"\n\ncontent-type: application/json\n\n{\"id\":";
+    assert_eq!(
+        status(extract::<CreateUser, _>(input)),
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[test]
+fn arbitrary_inputs() {
+    for input in [
+        &b""[..],
+        b"\xff\xfe/\n\0?&=\n:\n\x80",
+        b"%\n%%\nx: \xff\n\n\xff",
+    ] {
+        extract::<GetUser, _>(input);
+        extract::<ListUsers, _>(input);
+        extract::<CreateUser, _>(input);
+    }
+}
@@ -0,0 +1,29 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::{Owner, Pet};
+
+// Like a spec that a build script writes to `$OUT_DIR`
+validate_routes!(
+    path = "${CARGO_MANIFEST_DIR}/tests/generated.json";
+    unsupported = { composition: deny, formats: deny };
+
+    GET /pets/{id} as pub GetPet;
+);
+
+#[test]
+fn normalized_quirks() {
+    // Nullable values are optional fields, and a documented reference is the referenced schema
+    let pet = Pet {
+        id: 1,
+        nickname: Some("Rex".to_owned()),
+        owner: None,
+        best_friend: Some(Owner {
+            name: "Ann".to_owned(),
+        }),
+    };
+    let json = serde_json::json!({ "id": 1, "nickname": "Rex", "best_friend": { "name": "Ann" } });
+    assert_eq!(
+        serde_json::from_value::<Pet>(json).unwrap().nickname,
+        pet.nickname
+    );
+    assert_eq!(GetPetOk(pet).0.best_friend.unwrap().name, "Ann");
+}
@@ -0,0 +1,34 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+    headers = true;
+
+    GET /users/{user_id} as pub GetUser;
+);
+
+async fn get_user(req: GetUser) -> String {
+    let request_id = req.headers.get("X-Request-Id");
+    format!("{} {:?}", req.user_id, request_id)
+}
+
+#[tokio::test]
+async fn raw_headers() {
+    let router = Router::new().oapi_route(get_user);
+    let request = Request::get("/users/1")
+        .header("X-Request-Id", "abc")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(bytes, r#"1 Some("abc")"#);
+}
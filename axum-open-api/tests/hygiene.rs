@@ -0,0 +1,20 @@
+use axum_open_api_codegen::validate_routes;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users              as pub ListUsers;
+    GET     /users/{user_id}    as pub GetUser;
+);
+
+/// The helpers of the generated code are scoped to the functions that use them, so they don't
+/// conflict with the items next to the macro.
+#[allow(dead_code)]
+struct __QueryGenerated__;
+
+#[test]
+fn generated_items() {
+    let _: Option<i64> = ListUsers { page: Some(2) }.page;
+    let _: i64 = GetUser { user_id: 1 }.user_id;
+    assert_eq!(ListUsers::uri(&Some(2)), "/users?page=2");
+}
@@ -0,0 +1,76 @@
+use axum::{
+    body::{Body, Bytes},
+    http::{Request, StatusCode},
+    Json, Router,
+};
+use axum_open_api::{
+    idempotency::{IdempotencyStatus, IdempotencyStore},
+    OapiRouter,
+};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use schemas::Payment;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/http.yaml";
+
+    POST /payments as pub CreatePayment;
+);
+
+/// The statuses of the keys, which the handler would complete in a real store.
+#[derive(Clone, Default)]
+struct Store(Arc<Mutex<HashMap<String, IdempotencyStatus>>>);
+
+#[axum::async_trait]
+impl IdempotencyStore for Store {
+    async fn begin(&self, operation: &'static str, key: &str) -> IdempotencyStatus {
+        assert_eq!(operation, "createPayment");
+        let mut keys = self.0.lock().unwrap();
+        let status = keys.get(key).cloned().unwrap_or(IdempotencyStatus::New);
+        keys.entry(key.to_owned())
+            .or_insert(IdempotencyStatus::InProgress);
+        status
+    }
+}
+
+async fn create_payment(req: CreatePayment) -> (StatusCode, Json<Payment>) {
+    assert_eq!(req.idempotency_key.as_deref(), Some("new"));
+    (StatusCode::CREATED, Json(req.body))
+}
+
+#[tokio::test]
+async fn idempotency_keys() {
+    let store = Store::default();
+    let completed = IdempotencyStatus::Completed(StatusCode::CREATED, Bytes::from("replayed"));
+    store.0.lock().unwrap().insert("done".to_owned(), completed);
+    let router = Router::new().oapi_route(create_payment).with_state(store);
+
+    let send = |key: &'static str| {
+        let request = Request::post("/payments")
+            .header("Content-Type", "application/json")
+            .header("Idempotency-Key", key)
+            .body(Body::from(r#"{"id":1,"amount":100}"#))
+            .unwrap();
+        let router = router.clone();
+        async move {
+            let response = router.oneshot(request).await.unwrap();
+            let status = response.status();
+            let bytes = response.into_body().collect().await.unwrap().to_bytes();
+            (status, String::from_utf8(bytes.to_vec()).unwrap())
+        }
+    };
+    let created = (StatusCode::CREATED, r#"{"amount":100,"id":1}"#.to_owned());
+    assert_eq!(send("new").await, created);
+    // The first request with the key is still in progress
+    assert_eq!(send("new").await.0, StatusCode::CONFLICT);
+    // Completed requests are replayed without calling the handler
+    assert_eq!(
+        send("done").await,
+        (StatusCode::CREATED, "replayed".to_owned())
+    );
+}
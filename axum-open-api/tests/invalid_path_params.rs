@@ -0,0 +1,89 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt;
+
+async fn status(router: Router, uri: &str) -> StatusCode {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    router.oneshot(request).await.unwrap().status()
+}
+
+mod default {
+    use axum::{http::StatusCode, Router};
+    use axum_open_api::{NoContent, OapiRouter};
+    use axum_open_api_codegen::validate_routes;
+
+    validate_routes!(
+        path = "axum-open-api/tests/params.yaml";
+
+        GET /colors/{color} as pub GetColor;
+    );
+
+    #[tokio::test]
+    async fn bad_request() {
+        let router = Router::new().oapi_route(|_req: GetColor| async { NoContent });
+        assert_eq!(
+            super::status(router.clone(), "/colors/red").await,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            super::status(router, "/colors/blue").await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
+
+mod not_found {
+    use axum::{http::StatusCode, Router};
+    use axum_open_api::{NoContent, OapiRouter};
+    use axum_open_api_codegen::validate_routes;
+
+    validate_routes!(
+        path = "axum-open-api/tests/params.yaml";
+        invalid_path_params = not_found;
+
+        GET /colors/{color} as pub GetColor;
+    );
+
+    #[tokio::test]
+    async fn enums() {
+        let router = Router::new().oapi_route(|_req: GetColor| async { NoContent });
+        assert_eq!(
+            super::status(router.clone(), "/colors/red").await,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            super::status(router, "/colors/blue").await,
+            StatusCode::NOT_FOUND
+        );
+    }
+}
+
+#[cfg(feature = "pattern")]
+mod unprocessable_entity {
+    use axum::{http::StatusCode, Router};
+    use axum_open_api::{NoContent, OapiRouter};
+    use axum_open_api_codegen::validate_routes;
+
+    validate_routes!(
+        path = "axum-open-api/tests/params.yaml";
+        invalid_path_params = unprocessable_entity;
+
+        GET /tags/{tag} as pub GetTag;
+    );
+
+    #[tokio::test]
+    async fn patterns() {
+        let router = Router::new().oapi_route(|_req: GetTag| async { NoContent });
+        assert_eq!(
+            super::status(router.clone(), "/tags/rust").await,
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            super::status(router, "/tags/Rust").await,
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use axum_open_api::{MatchedOperation, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use std::convert::Infallible;
+use tower::{service_fn, ServiceExt};
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users/{user_id}    as pub GetUser;
+    DELETE  /users/{user_id}    as pub DeleteUser;
+    GET     /health             as pub GetHealth;
+);
+
+/// Adds the operationId of the matched operation as the `X-Operation` header.
+async fn operation_header(request: Request, next: Next) -> Response {
+    let operation = request.extensions().get::<MatchedOperation>().cloned();
+    let mut response = next.run(request).await;
+    let operation_id = operation.and_then(|operation| operation.operation_id);
+    let value = HeaderValue::from_static(operation_id.unwrap_or("-"));
+    response.headers_mut().insert("X-Operation", value);
+    response
+}
+
+async fn get_user(_req: GetUser) {}
+
+async fn delete_user(_req: DeleteUser) {}
+
+async fn send(router: Router, request: Request<Body>) -> Response {
+    router.oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn operation_layers() {
+    let router = Router::new()
+        .oapi_route_with_layer(get_user, middleware::from_fn(operation_header))
+        .oapi_route(delete_user);
+
+    // The layer sees the matched operation
+    let request = Request::get("/users/1").body(Body::empty()).unwrap();
+    let response = send(router.clone(), request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["X-Operation"], "getUser");
+
+    // The layer only wraps its operation
+    let request = Request::delete("/users/1").body(Body::empty()).unwrap();
+    let response = send(router, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("X-Operation").is_none());
+}
+
+#[tokio::test]
+async fn operation_services() {
+    let service = service_fn(|request: Request| async move {
+        let operation = request.extensions().get::<MatchedOperation>().unwrap();
+        Ok::<_, Infallible>(operation.path_template)
+    });
+    let router = Router::new().oapi_route_service::<GetHealth, _>(service);
+
+    let request = Request::get("/health").body(Body::empty()).unwrap();
+    let response = send(router.clone(), request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(bytes, "/health");
+
+    // The service is only routed at the method of the operation
+    let request = Request::post("/health").body(Body::empty()).unwrap();
+    let response = send(router, request).await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
@@ -0,0 +1,25 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::Subscription;
+
+validate_routes!(
+    path = "axum-open-api/tests/events.yaml";
+
+    POST    /subscriptions                          as pub CreateSubscription;
+    GET     /subscriptions/{subscription_id}        as pub GetSubscription;
+    GET     /subscriptions/{subscription_id}/events as pub ListEvents;
+);
+
+#[test]
+fn link_urls() {
+    let subscription = Subscription {
+        id: 5,
+        callback_url: "https://example.com/hooks".to_owned(),
+    };
+    // The parameters of `$response.body` are taken from the fields of the response
+    assert_eq!(subscription.get_subscription_url(), "/subscriptions/5");
+    // Other runtime expressions are arguments
+    assert_eq!(
+        subscription.list_events_url(&"created"),
+        "/subscriptions/5/events?kind=created"
+    );
+}
@@ -0,0 +1,50 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::{Greetings, Labels, Metadata, Product, Scores, Translations};
+use serde_json::json;
+
+validate_routes!(
+    path = "axum-open-api/tests/maps.yaml";
+);
+
+#[test]
+fn pattern_properties() {
+    let translations: Translations = serde_json::from_value(json!({ "en": "Hello" })).unwrap();
+    assert_eq!(translations["en"], "Hello");
+}
+
+#[test]
+fn pattern_properties_next_to_properties() {
+    let greetings: Greetings =
+        serde_json::from_value(json!({ "default": "Hello", "nl": "Hallo" })).unwrap();
+    assert_eq!(greetings.default, "Hello");
+    assert_eq!(greetings.pattern_properties["nl"], "Hallo");
+    assert!(!greetings.pattern_properties.contains_key("default"));
+}
+
+#[test]
+fn additional_properties() {
+    let scores: Scores = serde_json::from_value(json!({ "alice": 3, "bob": 5 })).unwrap();
+    assert_eq!(scores["bob"], 5);
+
+    let metadata: Metadata = serde_json::from_value(json!({ "tags": ["a"] })).unwrap();
+    assert_eq!(metadata["tags"], json!(["a"]));
+}
+
+#[test]
+fn bounded_additional_properties() {
+    assert!(serde_json::from_value::<Labels>(json!({ "a": "x" })).is_ok());
+    assert!(serde_json::from_value::<Labels>(json!({})).is_err());
+    assert!(serde_json::from_value::<Labels>(json!({ "a": "x", "b": "y", "c": "z" })).is_err());
+}
+
+#[test]
+fn additional_properties_next_to_properties() {
+    let product: Product =
+        serde_json::from_value(json!({ "name": "Chair", "weight": 4.5 })).unwrap();
+    assert_eq!(product.name, "Chair");
+    assert_eq!(product.additional_properties["weight"], 4.5);
+    assert_eq!(
+        serde_json::to_value(&product).unwrap(),
+        json!({ "name": "Chair", "weight": 4.5 })
+    );
+}
@@ -0,0 +1,60 @@
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/http.yaml";
+    api_trait = Api;
+
+    GET /reports/{year} as pub GetReport;
+);
+
+#[derive(Clone)]
+struct Reports;
+
+#[axum::async_trait]
+impl Api for Reports {
+    async fn get_report(&self, req: GetReport) -> GetReportResponse {
+        match req.year {
+            2024 => GetReportResponse::csv("month,total\njan,100\n"),
+            _ => GetReportResponse::plain("no report"),
+        }
+    }
+}
+
+async fn send(uri: &str) -> (StatusCode, String, String) {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    let response = into_router(Reports).oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response.headers()[CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        content_type,
+        String::from_utf8(bytes.to_vec()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn media_type_constructors() {
+    // Sent with the media type of the spec, including its parameters
+    let (status, content_type, body) = send("/reports/2024").await;
+    assert_eq!(
+        (status, content_type.as_str()),
+        (StatusCode::OK, "text/csv; charset=utf-8")
+    );
+    assert_eq!(body, "month,total\njan,100\n");
+
+    let (status, content_type, _) = send("/reports/2023").await;
+    assert_eq!(
+        (status, content_type.as_str()),
+        (StatusCode::NOT_FOUND, "text/plain")
+    );
+}
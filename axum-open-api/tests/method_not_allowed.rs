@@ -0,0 +1,47 @@
+use axum::{
+    body::Body,
+    http::{header::ALLOW, Method, Request, StatusCode},
+    response::Response,
+    Router,
+};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+    method_not_allowed = method_not_allowed;
+
+    GET /users/{user_id} as pub GetUser;
+);
+
+async fn send(method: Method, uri: &str) -> Response {
+    let router = method_not_allowed(Router::new().oapi_route(|_req: GetUser| async {}));
+    let request = Request::builder().method(method).uri(uri);
+    router
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn undeclared_methods() {
+    let response = send(Method::PUT, "/users/1").await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers()[ALLOW], "GET, DELETE");
+
+    // The methods of the spec are allowed even if they are not routed yet
+    let response = send(Method::PATCH, "/users").await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers()[ALLOW], "GET, POST");
+}
+
+#[tokio::test]
+async fn routed_methods() {
+    let response = send(Method::GET, "/users/1").await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Paths that are not in the spec are still not found
+    let response = send(Method::GET, "/posts").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,62 @@
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    routing::MethodFilter,
+    Router,
+};
+use axum_open_api::{OapiPath, OapiWebhook};
+use axum_open_api_codegen::validate_routes;
+use tower::ServiceExt;
+
+mod api {
+    use axum_open_api_codegen::validate_routes;
+
+    validate_routes!(
+        path = "axum-open-api/tests/operations.yaml";
+
+        GET     /users/{user_id}    as pub GetUser;
+        DELETE  /users/{user_id}    as pub DeleteUser;
+    );
+}
+
+validate_routes!(
+    path = "axum-open-api/tests/events.yaml";
+
+    POST webhook newEvent as pub NewEvent;
+);
+
+use api::{DeleteUser, GetUser};
+
+async fn status(router: Router, method: Method) -> StatusCode {
+    let request = Request::builder()
+        .method(method)
+        .uri("/users/1")
+        .body(Body::empty())
+        .unwrap();
+    router.oneshot(request).await.unwrap().status()
+}
+
+#[test]
+fn method_filters() {
+    assert_eq!(GetUser::method_filter(), MethodFilter::GET);
+    assert_eq!(DeleteUser::method_filter(), MethodFilter::DELETE);
+    assert_eq!(NewEvent::method_filter(), MethodFilter::POST);
+}
+
+#[tokio::test]
+async fn method_routers() {
+    // Handlers of different types are routed by the same provided function
+    let method_router = GetUser::method_router(|_req: GetUser| async { "user" }).merge(
+        DeleteUser::method_router(|| async { StatusCode::NO_CONTENT }),
+    );
+    let router = Router::new().route(GetUser::path(), method_router);
+    assert_eq!(status(router.clone(), Method::GET).await, StatusCode::OK);
+    assert_eq!(
+        status(router.clone(), Method::DELETE).await,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        status(router, Method::POST).await,
+        StatusCode::METHOD_NOT_ALLOWED
+    );
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "metrics")]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+use std::sync::Mutex;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users/{user_id}    as pub GetUser;
+);
+
+/// The metrics that were recorded, like `oapi_requests_total{operation=getUser}`.
+static METRICS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// A recorder that records the keys of the metrics. The macros of `metrics` register a metric
+/// every time they record it.
+struct KeyRecorder;
+
+impl KeyRecorder {
+    fn register(&self, key: &Key) {
+        let labels = key
+            .labels()
+            .map(|label| format!("{}={}", label.key(), label.value()))
+            .collect::<Vec<_>>();
+        let metric = format!("{}{{{}}}", key.name(), labels.join(","));
+        METRICS.lock().unwrap().push(metric);
+    }
+}
+
+impl Recorder for KeyRecorder {
+    fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        self.register(key);
+        Counter::noop()
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        self.register(key);
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        self.register(key);
+        Histogram::noop()
+    }
+}
+
+async fn get_user(_req: GetUser) {}
+
+#[tokio::test]
+async fn extraction_metrics() {
+    metrics::set_global_recorder(KeyRecorder).unwrap();
+    let router = Router::new().oapi_route(get_user);
+
+    let request = Request::get("/users/1").body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        *METRICS.lock().unwrap(),
+        [
+            "oapi_extraction_duration_seconds{operation=getUser}",
+            "oapi_requests_total{operation=getUser}",
+        ]
+    );
+
+    // Rejections are counted by their variant
+    METRICS.lock().unwrap().clear();
+    let request = Request::get("/users/x").body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        METRICS.lock().unwrap().last().unwrap(),
+        "oapi_rejections_total{operation=getUser,rejection=Path}"
+    );
+}
@@ -0,0 +1,65 @@
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+    mock_router = mock_router;
+
+    POST    /users              as pub CreateUser;
+    GET     /users/{user_id}    as pub GetUser;
+    DELETE  /users/{user_id}    as pub DeleteUser;
+    GET     /health             as pub GetHealth;
+);
+
+async fn send(request: Request<Body>) -> (StatusCode, Option<String>, String) {
+    let response = mock_router().oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response.headers().get(CONTENT_TYPE);
+    let content_type = content_type.map(|value| value.to_str().unwrap().to_owned());
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        content_type,
+        String::from_utf8(bytes.to_vec()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn examples() {
+    let request = Request::get("/users/7").body(Body::empty()).unwrap();
+    let (status, content_type, body) = send(request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/json"));
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+        serde_json::json!({ "id": 1, "name": "Ann" })
+    );
+
+    let request = Request::get("/health").body(Body::empty()).unwrap();
+    let (status, content_type, body) = send(request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/plain"));
+    assert_eq!(body, "ok");
+}
+
+#[tokio::test]
+async fn responses_without_examples() {
+    // The first successful status, without a body
+    let request = Request::post("/users").body(Body::empty()).unwrap();
+    assert_eq!(send(request).await.0, StatusCode::CREATED);
+
+    let request = Request::delete("/users/7").body(Body::empty()).unwrap();
+    assert_eq!(
+        send(request).await,
+        (StatusCode::NO_CONTENT, None, String::new())
+    );
+
+    // Operations that are not declared are not mocked
+    let request = Request::get("/users").body(Body::empty()).unwrap();
+    assert_eq!(send(request).await.0, StatusCode::METHOD_NOT_ALLOWED);
+}
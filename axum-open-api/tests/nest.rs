@@ -0,0 +1,66 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Extension, Router,
+};
+use axum_open_api::{MatchedOperation, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users              as pub ListUsers;
+    GET     /users/{user_id}    as pub GetUser;
+    GET     /health             as pub GetHealth;
+);
+
+async fn list_users(_req: ListUsers) -> &'static str {
+    "users"
+}
+
+async fn get_user(operation: Extension<MatchedOperation>, req: GetUser) -> String {
+    format!("{} {}", operation.path_template, req.user_id)
+}
+
+async fn get_health(_req: GetHealth) -> &'static str {
+    "ok"
+}
+
+async fn get(router: Router, uri: &str) -> (StatusCode, String) {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn nested_operations() {
+    let router = Router::new()
+        .oapi_nest("/users", |users| {
+            users.oapi_route(list_users).oapi_route(get_user)
+        })
+        .oapi_route(get_health);
+
+    // The operations are served at their paths in the spec
+    assert_eq!(
+        get(router.clone(), "/users").await,
+        (StatusCode::OK, "users".to_owned())
+    );
+    assert_eq!(
+        get(router.clone(), "/users/3").await,
+        (StatusCode::OK, "/users/{user_id} 3".to_owned())
+    );
+    assert_eq!(
+        get(router, "/health").await,
+        (StatusCode::OK, "ok".to_owned())
+    );
+}
+
+#[test]
+#[should_panic(expected = "the path `/health` does not start with the prefix `/users`")]
+fn operation_outside_of_the_prefix() {
+    let _: Router = Router::new().oapi_nest("/users", |users| users.oapi_route(get_health));
+}
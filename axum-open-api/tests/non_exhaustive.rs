@@ -0,0 +1,25 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::{ApiVersion, Filter, Status};
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+    non_exhaustive = true;
+);
+
+// `#[non_exhaustive]` only restricts other crates, which use the builders and `Default`
+#[test]
+fn non_exhaustive_types() {
+    let user = schemas::User::builder()
+        .id(1)
+        .name("Ann")
+        .version(ApiVersion)
+        .build();
+    assert_eq!(user.name, "Ann");
+    let filter = Filter::default();
+    assert_eq!(filter.status, None);
+    let status = match Status::Active {
+        Status::Active => "active",
+        Status::InProgress => "in-progress",
+    };
+    assert_eq!(status, "active");
+}
@@ -0,0 +1,15 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::Username;
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+);
+
+#[test]
+fn excluded_values() {
+    let username: Username = serde_json::from_str(r#""ann""#).unwrap();
+    assert_eq!(username.as_str(), "ann");
+    // The values excluded by `not` are rejected when deserializing
+    assert!(serde_json::from_str::<Username>(r#""admin""#).is_err());
+    assert!(Username::new("root".to_owned()).is_err());
+}
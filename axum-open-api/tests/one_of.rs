@@ -0,0 +1,78 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::{
+    Address, AdjacentPet, Cat, Contact, Dog, InternalPet, Letter, Message, Parcel, Pet, UntaggedPet,
+};
+
+validate_routes!(
+    path = "axum-open-api/tests/one_of.yaml";
+);
+
+#[test]
+fn conversions() {
+    let address = Address {
+        street: "Main Street".to_owned(),
+    };
+    let contact = Contact::from(address.clone());
+    assert!(matches!(&contact, Contact::Address(_)));
+    assert_eq!(Address::try_from(contact).unwrap().street, address.street);
+}
+
+#[test]
+fn variants_of_the_same_type() {
+    // Both `Email` and `Phone` are a `String`, so they have no conversions
+    let contact: Contact = serde_json::from_str(r#"{"Email":"me@example.com"}"#).unwrap();
+    assert!(matches!(contact, Contact::Email(email) if email == "me@example.com"));
+    assert!(Address::try_from(Contact::Phone("0123".to_owned())).is_err());
+}
+
+#[test]
+fn boxed_variants() {
+    // `Letter` is much larger than `Email`, and `Parcel` is boxed with `x-rust-box`
+    let message = Message::from(Parcel { weight: 2 });
+    let Message::Parcel(parcel) = message else {
+        panic!("expected a parcel");
+    };
+    let parcel: Box<Parcel> = parcel;
+    assert_eq!(parcel.weight, 2);
+
+    // The conversions of a boxed variant convert from its type
+    let letter = serde_json::from_value::<Letter>(serde_json::json!({
+        "greeting": "Dear", "subject": "", "body": "", "closing": "Regards", "signature": "",
+        "sender": "", "recipient": "", "street": "", "city": "", "country": "",
+    }));
+    let Message::Letter(letter) = Message::from(letter.unwrap()) else {
+        panic!("expected a letter");
+    };
+    let letter: Box<Letter> = letter;
+    assert_eq!(letter.greeting, "Dear");
+    let message = Message::Letter(letter);
+    assert_eq!(Letter::try_from(message).unwrap().closing, "Regards");
+}
+
+#[test]
+fn mapping_variants() {
+    // The variants are named after their key in the mapping, which is their serialized name
+    let pet = Pet::Feline(Cat {
+        name: "Tom".to_owned(),
+    });
+    let json = serde_json::to_value(&pet).unwrap();
+    assert_eq!(json, serde_json::json!({ "feline": { "name": "Tom" } }));
+    let pet: Pet = serde_json::from_str(r#"{"canine":{"name":"Rex"}}"#).unwrap();
+    assert!(matches!(pet, Pet::Canine(Dog { name }) if name == "Rex"));
+}
+
+#[test]
+fn tagging() {
+    let cat = || Cat {
+        name: "Tom".to_owned(),
+    };
+    let json = serde_json::to_value(InternalPet::Cat(cat())).unwrap();
+    assert_eq!(json, serde_json::json!({ "petType": "Cat", "name": "Tom" }));
+    let json = serde_json::to_value(AdjacentPet::Cat(cat())).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "kind": "Cat", "pet": { "name": "Tom" } })
+    );
+    let json = serde_json::to_value(UntaggedPet::Cat(cat())).unwrap();
+    assert_eq!(json, serde_json::json!({ "name": "Tom" }));
+}
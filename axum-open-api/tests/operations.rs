@@ -0,0 +1,145 @@
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Extension, Router,
+};
+use axum_open_api::{MatchedOperation, OapiRouter, OperationMeta};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users              as pub ListUsers;
+    POST    /users              as pub CreateUser;
+    GET     /users/{user_id}    as pub GetUser;
+    DELETE  /users/{user_id}    as pub DeleteUser;
+    GET     /health             as pub GetHealth;
+);
+
+/// Responds with the matched operation, as `method path operation_id tags`.
+async fn matched_operation(Extension(operation): Extension<MatchedOperation>) -> String {
+    let operation_id = operation.operation_id.unwrap_or("-");
+    let tags = operation.tags.join(",");
+    format!(
+        "{} {} {operation_id} {tags}",
+        operation.method, operation.path_template
+    )
+}
+
+async fn get_user(operation: Extension<MatchedOperation>, _req: GetUser) -> String {
+    matched_operation(operation).await
+}
+
+async fn delete_user(operation: Extension<MatchedOperation>, _req: DeleteUser) -> String {
+    matched_operation(operation).await
+}
+
+async fn send(router: Router, request: Request<Body>) -> (StatusCode, String) {
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[test]
+fn operation_meta() {
+    assert_eq!(GetUser::method(), Method::GET);
+    assert_eq!(GetUser::path_template(), "/users/{user_id}");
+    assert_eq!(GetUser::operation_id(), Some("getUser"));
+    assert_eq!(GetUser::tags(), ["users"]);
+
+    assert_eq!(CreateUser::method(), Method::POST);
+    assert_eq!(CreateUser::tags(), ["users", "admin"]);
+
+    // Operations without an operationId or tags
+    assert_eq!(DeleteUser::operation_id(), None);
+    assert!(GetHealth::tags().is_empty());
+}
+
+#[tokio::test]
+async fn matched_operation_extension() {
+    let router = Router::new().oapi_route(get_user).oapi_route(delete_user);
+
+    let request = Request::get("/users/1").body(Body::empty()).unwrap();
+    assert_eq!(
+        send(router.clone(), request).await,
+        (
+            StatusCode::OK,
+            "GET /users/{user_id} getUser users".to_owned()
+        )
+    );
+
+    // Every operation on the same path gets its own extension
+    let request = Request::delete("/users/1").body(Body::empty()).unwrap();
+    assert_eq!(
+        send(router, request).await,
+        (StatusCode::OK, "DELETE /users/{user_id} - ".to_owned())
+    );
+}
+
+#[test]
+fn uri_builders() {
+    assert_eq!(GetUser::path_with(&1), "/users/1");
+    assert_eq!(GetUser::uri(&1), "/users/1");
+    assert_eq!(ListUsers::path_with(), "/users");
+    assert_eq!(ListUsers::uri(&Some(2)), "/users?page=2");
+    // Absent query parameters are left out
+    assert_eq!(ListUsers::uri(&None), "/users");
+}
+
+#[test]
+fn operation_ids() {
+    assert_eq!(OperationId::GetUser.as_str(), "getUser");
+    assert_eq!(OperationId::GetUser.method(), Method::GET);
+    assert_eq!(OperationId::GetUser.path_template(), "/users/{user_id}");
+    // Operations without an operationId are named after their method and path
+    assert_eq!(OperationId::DeleteUser.as_str(), "DELETE /users/{user_id}");
+
+    // The operations are a closed set, in the order of their declaration
+    let names = OperationId::ALL.iter().map(OperationId::as_str);
+    assert_eq!(
+        names.collect::<Vec<_>>(),
+        [
+            "listUsers",
+            "createUser",
+            "getUser",
+            "DELETE /users/{user_id}",
+            "GET /health"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn operations_on_the_same_path() {
+    // Separate `oapi_route` calls are merged into one route
+    let router = Router::new()
+        .oapi_route(|_req: ListUsers| async { "list" })
+        .oapi_route(|_req: CreateUser| async { "create" });
+
+    let request = Request::get("/users").body(Body::empty()).unwrap();
+    assert_eq!(
+        send(router.clone(), request).await,
+        (StatusCode::OK, "list".to_owned())
+    );
+    let request = Request::post("/users")
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"id":1,"name":"Ann"}"#))
+        .unwrap();
+    assert_eq!(
+        send(router.clone(), request).await,
+        (StatusCode::OK, "create".to_owned())
+    );
+    let request = Request::put("/users").body(Body::empty()).unwrap();
+    assert_eq!(
+        send(router, request).await.0,
+        StatusCode::METHOD_NOT_ALLOWED
+    );
+}
+
+#[test]
+#[should_panic(expected = "Overlapping method route")]
+fn operation_routed_twice() {
+    let _: Router = Router::new().oapi_route(get_user).oapi_route(get_user);
+}
@@ -0,0 +1,16 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::Status;
+use std::collections::BTreeMap;
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+    ord = true;
+);
+
+#[test]
+fn ordered_enums() {
+    // Ordered like the values of the spec, so the enums can be map keys
+    let counts = BTreeMap::from([(Status::InProgress, 2), (Status::Active, 1)]);
+    let keys = counts.into_keys().collect::<Vec<_>>();
+    assert_eq!(keys, [Status::Active, Status::InProgress]);
+}
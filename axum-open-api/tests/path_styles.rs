@@ -0,0 +1,43 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/params.yaml";
+
+    GET /posts/{post_id} as pub GetPost;
+    GET /comments/{comment_id} as pub GetComment;
+);
+
+async fn send(uri: &str) -> (StatusCode, String) {
+    let router = Router::new()
+        .oapi_route(|req: GetPost| async move { req.post_id.to_string() })
+        .oapi_route(|req: GetComment| async move { req.comment_id.to_string() });
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn label_and_matrix_styles() {
+    assert_eq!(send("/posts/.5").await, (StatusCode::OK, "5".to_owned()));
+    assert_eq!(
+        send("/comments/;comment_id=7").await,
+        (StatusCode::OK, "7".to_owned())
+    );
+    // The prefix is required
+    assert_eq!(send("/posts/5").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(send("/comments/7").await.0, StatusCode::BAD_REQUEST);
+
+    // The paths of the operations have the prefix
+    assert_eq!(GetPost::path_with(&5), "/posts/.5");
+    assert_eq!(GetComment::path_with(&7), "/comments/;comment_id=7");
+}
@@ -0,0 +1,36 @@
+use axum::{body::Body, http::Request, Router};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/queries.yaml";
+
+    GET /users as pub ListUsers;
+    GET /posts as pub ListPosts;
+);
+
+#[test]
+fn shared_query_struct() {
+    // The operations with the same query parameters share a struct
+    let query = PagePerPageQuery {
+        page: Some(2),
+        per_page: None,
+    };
+    let query = serde_json::to_value(query).unwrap();
+    assert_eq!(query, serde_json::json!({ "page": 2, "per_page": null }));
+}
+
+#[tokio::test]
+async fn shared_query_extraction() {
+    let router = Router::new()
+        .oapi_route(|req: ListUsers| async move { format!("{:?}", req.page) })
+        .oapi_route(|req: ListPosts| async move { format!("{:?}", req.per_page) });
+
+    for (uri, expected) in [("/users?page=2", "Some(2)"), ("/posts?page=2", "None")] {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX);
+        assert_eq!(body.await.unwrap(), expected);
+    }
+}
@@ -0,0 +1,44 @@
+use axum::{body::Body, http::Request, Router};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/params.yaml";
+
+    GET /search as pub Search;
+);
+
+async fn search(req: Search) -> String {
+    format!("{:?} {:?} {:?} {:?}", req.q, req.exact, req.limit, req.path)
+}
+
+async fn send(uri: &str) -> String {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    let response = Router::new()
+        .oapi_route(search)
+        .oneshot(request)
+        .await
+        .unwrap();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn empty_values() {
+    // An empty string, `true`, and an absent value
+    let expected = r#"Some("") Some(true) None None"#;
+    assert_eq!(send("/search?q&exact&limit").await, expected);
+    assert_eq!(send("/search?q=&exact=&limit=").await, expected);
+}
+
+#[tokio::test]
+async fn reserved_values() {
+    assert_eq!(
+        send("/search?path=a+b/c").await,
+        r#"None None None Some("a+b/c")"#
+    );
+    let uri = Search::uri(&None, &None, &None, &Some("a/b?c".to_owned()));
+    assert_eq!(uri, "/search?path=a/b?c");
+}
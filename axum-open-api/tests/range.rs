@@ -0,0 +1,61 @@
+use axum::{
+    body::Body,
+    http::{
+        header::{CONTENT_RANGE, CONTENT_TYPE},
+        Request, StatusCode,
+    },
+    Router,
+};
+use axum_open_api::{range::PartialContent, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/http.yaml";
+
+    GET /payments/{payment_id}/receipt as pub GetReceipt;
+);
+
+async fn get_receipt(req: GetReceipt) -> PartialContent {
+    req.partial_content("%PDF-receipt")
+}
+
+async fn send(range: Option<&str>) -> (StatusCode, Option<String>, String) {
+    let mut request = Request::get("/payments/1/receipt");
+    if let Some(range) = range {
+        request = request.header("Range", range);
+    }
+    let request = request.body(Body::empty()).unwrap();
+    let response = Router::new()
+        .oapi_route(get_receipt)
+        .oneshot(request)
+        .await
+        .unwrap();
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/pdf");
+    let status = response.status();
+    let content_range = response.headers().get(CONTENT_RANGE);
+    let content_range = content_range.map(|value| value.to_str().unwrap().to_owned());
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        content_range,
+        String::from_utf8(bytes.to_vec()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn byte_ranges() {
+    // A `206` response gives the operation a range, without a `Range` parameter
+    let (status, content_range, body) = send(Some("bytes=0-3")).await;
+    assert_eq!(
+        (status, body.as_str()),
+        (StatusCode::PARTIAL_CONTENT, "%PDF")
+    );
+    assert_eq!(content_range.as_deref(), Some("bytes 0-3/12"));
+
+    let (status, _, body) = send(None).await;
+    assert_eq!((status, body.as_str()), (StatusCode::OK, "%PDF-receipt"));
+    let (status, _, _) = send(Some("bytes=100-")).await;
+    assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+}
@@ -0,0 +1,46 @@
+use axum_open_api::rate_limit::RateLimit;
+use axum_open_api_codegen::validate_routes;
+use std::time::Duration;
+
+validate_routes!(
+    path = "axum-open-api/tests/extensions.yaml";
+
+    GET /invoices as pub GetInvoices;
+);
+
+#[test]
+fn rate_limit_constant() {
+    let rate_limit = RateLimit {
+        requests: 1,
+        period: Duration::from_secs(60),
+        burst: 1,
+    };
+    assert_eq!(GetInvoices::RATE_LIMIT, rate_limit);
+}
+
+#[cfg(feature = "governor")]
+#[tokio::test]
+async fn governor_layer() {
+    use axum::{
+        body::Body,
+        extract::ConnectInfo,
+        http::{Request, StatusCode},
+        Router,
+    };
+    use axum_open_api::OapiRouter;
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    let router = Router::new()
+        .oapi_route(|_req: GetInvoices| async { StatusCode::OK })
+        .route_layer(GetInvoices::RATE_LIMIT.governor_layer());
+    let client = ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 1234)));
+    let mut statuses = Vec::new();
+    for _ in 0..2 {
+        let request = Request::get("/invoices").extension(client);
+        let request = request.body(Body::empty()).unwrap();
+        statuses.push(router.clone().oneshot(request).await.unwrap().status());
+    }
+    // One request per minute is allowed
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::TOO_MANY_REQUESTS]);
+}
@@ -0,0 +1,28 @@
+use axum::{body::Body, http::Request, Router};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/params.yaml";
+
+    #[raw_path]
+    GET /objects/{key} as pub GetObject;
+);
+
+async fn get_object(req: GetObject) -> String {
+    format!("{} was requested as {}", req.key, req.raw_key)
+}
+
+#[tokio::test]
+async fn raw_path_params() {
+    let request = Request::get("/objects/a%2Fb").body(Body::empty()).unwrap();
+    let response = Router::new()
+        .oapi_route(get_object)
+        .oneshot(request)
+        .await
+        .unwrap();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(bytes, "a/b was requested as a%2Fb");
+}
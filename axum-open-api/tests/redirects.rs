@@ -0,0 +1,41 @@
+use axum::{
+    body::Body,
+    http::{header::LOCATION, Request, StatusCode},
+};
+use axum_open_api::redirect::Location;
+use axum_open_api_codegen::validate_routes;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/http.yaml";
+    api_trait = Api;
+
+    GET /receipts/{payment_id} as pub FindReceipt;
+    GET /payments/{payment_id}/receipt as pub GetReceipt;
+);
+
+#[derive(Clone)]
+struct Receipts;
+
+#[axum::async_trait]
+impl Api for Receipts {
+    async fn find_receipt(&self, req: FindReceipt) -> FindReceiptResponse {
+        FindReceiptResponse::Status303(GetReceipt::location(&req.payment_id))
+    }
+
+    async fn get_receipt(&self, _req: GetReceipt) -> GetReceiptResponse {
+        GetReceiptResponse::pdf_200("%PDF")
+    }
+}
+
+#[tokio::test]
+async fn redirects() {
+    let request = Request::get("/receipts/1").body(Body::empty()).unwrap();
+    let response = into_router(Receipts).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()[LOCATION], "/payments/1/receipt");
+
+    let location = Location::from_headers(response.headers()).unwrap();
+    assert_eq!(location.uri().path(), "/payments/1/receipt");
+    assert!(Location::new("not a uri").is_err());
+}
@@ -0,0 +1,20 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::Profile;
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+    rename_all = "camelCase";
+);
+
+#[test]
+fn renamed_fields() {
+    // `userID` is not what the rule names `user_id`, so it is renamed explicitly
+    let json = serde_json::json!({ "createdAt": "2024-01-01", "userID": "1", "type": "admin" });
+    let profile: Profile = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(
+        (profile.created_at.as_str(), profile.user_id.as_str()),
+        ("2024-01-01", "1")
+    );
+    assert_eq!(profile.r#type, "admin");
+    assert_eq!(serde_json::to_value(profile).unwrap(), json);
+}
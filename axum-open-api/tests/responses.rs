@@ -0,0 +1,82 @@
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    response::IntoResponse,
+    Router,
+};
+use axum_open_api::{NoContent, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use schemas::Post;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/responses.yaml";
+
+    GET     /posts      as pub GetPosts;
+    DELETE  /posts      as pub DeletePosts;
+    GET     /status     as pub GetStatus;
+);
+
+async fn get_posts(_req: GetPosts) -> Result<GetPostsOk, StatusCode> {
+    Ok(GetPostsOk(vec![Post { id: 1 }, Post { id: 2 }]))
+}
+
+async fn delete_posts(_req: DeletePosts) -> NoContent {
+    NoContent
+}
+
+/// Returns `impl IntoResponse`, which can't be checked against the spec.
+async fn get_status(_req: GetStatus) -> impl IntoResponse {
+    "ok"
+}
+
+async fn send(router: Router, request: Request<Body>) -> (StatusCode, String) {
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn checked_responses() {
+    let router = Router::new()
+        .oapi_route_checked(get_posts)
+        .oapi_route_checked(delete_posts);
+
+    let request = Request::get("/posts").body(Body::empty()).unwrap();
+    let (status, body) = send(router.clone(), request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, r#"[{"id":1},{"id":2}]"#);
+
+    // `NoContent` responds without a body
+    let request = Request::delete("/posts").body(Body::empty()).unwrap();
+    let (status, body) = send(router, request).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn statuses_with_the_same_body() {
+    let response = GetPostsPartialContent(vec![Post { id: 1 }]).into_response();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+}
+
+#[tokio::test]
+async fn media_types_of_wrappers() {
+    let response = GetPostsOk(Vec::new()).into_response();
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+    let response = GetStatusOk("ok".to_owned()).into_response();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "text/plain");
+}
+
+#[tokio::test]
+async fn unchecked_responses() {
+    let router = Router::new().oapi_route(get_status);
+    let request = Request::get("/status").body(Body::empty()).unwrap();
+    assert_eq!(
+        send(router, request).await,
+        (StatusCode::OK, "ok".to_owned())
+    );
+}
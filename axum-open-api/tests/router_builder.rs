@@ -0,0 +1,67 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+};
+use axum_open_api::{
+    builder::{MissingOperations, OapiRouterBuilder},
+    OapiRouter,
+};
+use axum_open_api_codegen::validate_routes;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users/{user_id}    as pub GetUser;
+    DELETE  /users/{user_id}    as pub DeleteUser;
+    GET     /health             as pub GetHealth;
+);
+
+async fn get_user(_req: GetUser) {}
+
+async fn delete_user(_req: DeleteUser) {}
+
+async fn get_health(_req: GetHealth) {}
+
+fn builder() -> OapiRouterBuilder {
+    OapiRouterBuilder::new(OperationId::ALL.iter().map(OperationId::as_str))
+}
+
+#[tokio::test]
+async fn complete_router() {
+    let router = builder()
+        .oapi_route(get_user)
+        .oapi_route(delete_user)
+        .oapi_route(get_health)
+        .map_router(|router| router.route("/version", get(|| async { "1" })))
+        .build()
+        .unwrap();
+
+    for request in [
+        Request::get("/users/1"),
+        Request::delete("/users/1"),
+        Request::get("/health"),
+        Request::get("/version"),
+    ] {
+        let request = request.body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
+
+#[test]
+fn missing_operations() {
+    let builder = builder().oapi_route(get_user);
+    assert_eq!(builder.registered()[0].operation_id, Some("getUser"));
+
+    let missing = builder.build().unwrap_err();
+    assert_eq!(
+        missing,
+        MissingOperations(vec!["DELETE /users/{user_id}", "GET /health"])
+    );
+    assert_eq!(
+        missing.to_string(),
+        "operations without a handler: DELETE /users/{user_id}, GET /health"
+    );
+}
@@ -0,0 +1,25 @@
+mod api {
+    axum_open_api_codegen::validate_routes!(
+        path = "axum-open-api/tests/test-api.yaml";
+
+        GET     /account    as pub GetAccount;
+    );
+}
+
+mod users {
+    axum_open_api_codegen::validate_routes!(
+        path = "axum-open-api/tests/test-api.yaml";
+        schemas_from = crate::api::schemas;
+
+        GET     /profile    as pub GetProfile;
+    );
+}
+
+#[test]
+fn shared_security_schemes() {
+    let auth = users::security::ApiKeyAuth {
+        key: "secret".to_owned(),
+    };
+    let auth: api::security::ApiKeyAuth = auth;
+    assert_eq!(auth.key, "secret");
+}
@@ -0,0 +1,19 @@
+mod api {
+    axum_open_api_codegen::validate_routes!(
+        path = "axum-open-api/tests/operations.yaml";
+        schemas as pub(crate) models;
+
+        GET /users/{user_id} as pub GetUser;
+    );
+}
+
+#[test]
+fn renamed_schemas_module() {
+    let user = api::models::User {
+        id: 1,
+        name: "Ann".to_owned(),
+    };
+    // The generated code refers to the renamed module
+    let response = api::GetUserOk(user);
+    assert_eq!(response.0.name, "Ann");
+}
@@ -0,0 +1,50 @@
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+    serve_spec = spec_router;
+
+    GET /health as pub GetHealth;
+);
+
+async fn get(uri: &str) -> (StatusCode, String, String) {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    let response = spec_router().oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response.headers()[CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        content_type,
+        String::from_utf8(bytes.to_vec()).unwrap(),
+    )
+}
+
+#[test]
+fn embedded_spec() {
+    assert_eq!(OPENAPI_SPEC, include_str!("operations.yaml"));
+    let json: serde_json::Value = serde_json::from_str(OPENAPI_SPEC_JSON).unwrap();
+    assert_eq!(json["info"]["title"], "Operations");
+}
+
+#[tokio::test]
+async fn served_spec() {
+    let (status, content_type, body) = get("/openapi.yaml").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/yaml");
+    assert_eq!(body, OPENAPI_SPEC);
+
+    let (status, content_type, body) = get("/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/json");
+    assert_eq!(body, OPENAPI_SPEC_JSON);
+}
@@ -0,0 +1,62 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::OapiRouter;
+use tower::ServiceExt;
+
+mod prefixed {
+    axum_open_api_codegen::validate_routes!(
+        path = "axum-open-api/tests/servers.yaml";
+        base_path = prefix;
+
+        GET /users/{user_id} as pub GetUser;
+    );
+}
+
+mod nested {
+    axum_open_api_codegen::validate_routes!(
+        path = "axum-open-api/tests/servers.yaml";
+        base_path = nest;
+
+        GET /users/{user_id} as pub GetUser;
+    );
+}
+
+async fn status(router: Router, uri: &str) -> StatusCode {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+    router.oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn prefixed_paths() {
+    let router = Router::new().oapi_route(|_req: prefixed::GetUser| async {});
+    assert_eq!(
+        status(router.clone(), "/api/v1/users/1").await,
+        StatusCode::OK
+    );
+    assert_eq!(status(router, "/users/1").await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn nested_router() {
+    assert_eq!(nested::BASE_PATH, "/api/v1");
+    let router = nested::nest(Router::new().oapi_route(|_req: nested::GetUser| async {}));
+    assert_eq!(
+        status(router.clone(), "/api/v1/users/1").await,
+        StatusCode::OK
+    );
+    assert_eq!(status(router, "/users/1").await, StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn server_variables() {
+    let variables = nested::ServerVariables::default();
+    assert_eq!(variables.region, "eu");
+    assert_eq!(variables.url(), "https://eu.example.com/api/v1");
+
+    let region = "us".to_owned();
+    let variables = nested::ServerVariables { region };
+    assert_eq!(variables.url(), "https://us.example.com/api/v1");
+}
@@ -0,0 +1,70 @@
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Request, StatusCode},
+    response::Response,
+    Extension,
+};
+use axum_open_api::{service::OperationService, MatchedOperation};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::{ServiceBuilder, ServiceExt};
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+    services = true;
+
+    GET     /users/{user_id}    as pub GetUser;
+    GET     /health             as pub GetHealth;
+);
+
+async fn get_user(operation: Extension<MatchedOperation>, req: GetUser) -> String {
+    format!("{} {}", operation.path_template, req.user_id)
+}
+
+async fn get_health(State(status): State<StatusCode>, _req: GetHealth) -> StatusCode {
+    status
+}
+
+async fn text(response: Response) -> String {
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn operation_services() {
+    let service: OperationService<GetUser> = GetUserService::new(get_user);
+    let request = Request::get("/users/1").body(Body::empty()).unwrap();
+    let response = service.clone().oneshot(request).await.unwrap();
+    assert_eq!(text(response).await, "/users/{user_id} 1");
+
+    let statuses = [
+        (Request::get("/users/one"), StatusCode::BAD_REQUEST),
+        (Request::post("/users/1"), StatusCode::METHOD_NOT_ALLOWED),
+        (Request::get("/health"), StatusCode::NOT_FOUND),
+    ];
+    for (request, status) in statuses {
+        let request = request.body(Body::empty()).unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), status);
+    }
+}
+
+#[tokio::test]
+async fn middleware() {
+    let service = ServiceBuilder::new()
+        .map_response(|mut response: Response| {
+            response
+                .headers_mut()
+                .insert("x-service", "health".parse().unwrap());
+            response
+        })
+        .service(GetHealthService::with_state(
+            get_health,
+            StatusCode::ACCEPTED,
+        ));
+    let request = Request::get("/health").body(Body::empty()).unwrap();
+    let response = service.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    assert_eq!(response.headers()["x-service"], "health");
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "simd-json")]
+
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    Router,
+};
+use axum_open_api::{json::SIMD_JSON_MIN_LEN, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    POST /users as pub CreateUser;
+);
+
+async fn send(content_type: &str, body: String) -> (StatusCode, String) {
+    let request = Request::post("/users")
+        .header(CONTENT_TYPE, content_type)
+        .body(Body::from(body))
+        .unwrap();
+    let response = Router::new()
+        .oapi_route(|req: CreateUser| async move { req.body.name.len().to_string() })
+        .oneshot(request)
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+fn user(name: &str) -> String {
+    format!(r#"{{"id":1,"name":"{name}"}}"#)
+}
+
+#[tokio::test]
+async fn large_bodies() {
+    let name = "a".repeat(SIMD_JSON_MIN_LEN);
+    let (status, body) = send("application/json", user(&name)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, SIMD_JSON_MIN_LEN.to_string());
+}
+
+#[tokio::test]
+async fn small_bodies() {
+    let (status, body) = send("application/json", user("Ann")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "3");
+}
+
+#[tokio::test]
+async fn rejections() {
+    // Invalid bodies are rejected by `axum::Json`, whatever their length
+    let padding = " ".repeat(SIMD_JSON_MIN_LEN);
+    let (status, body) = send("application/json", format!(r#"{{"id":"one"{padding}}}"#)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(
+        body.starts_with("Failed to deserialize the JSON body"),
+        "{body}"
+    );
+
+    let (status, _) = send("text/plain", user("Ann")).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "spec")]
+
+use axum_open_api::testing::spec_coverage_report;
+use axum_open_api_codegen::validate_routes;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users              as pub ListUsers;
+    GET     /users/{user_id}    as pub GetUser;
+);
+
+const SPEC: &[u8] = include_bytes!("operations.yaml");
+
+#[test]
+fn missing_operations() {
+    let coverage = spec_coverage_report(IMPLEMENTED_OPERATIONS, SPEC);
+    assert_eq!(
+        coverage.missing,
+        ["GET /health", "POST /users", "DELETE /users/{user_id}"]
+    );
+    assert!(coverage.extra.is_empty());
+    assert!(!coverage.is_complete());
+}
+
+#[test]
+fn extra_operations() {
+    let coverage = spec_coverage_report(IMPLEMENTED_OPERATIONS, include_bytes!("params.yaml"));
+    assert_eq!(coverage.extra, ["GET /users", "GET /users/{user_id}"]);
+}
+
+#[test]
+#[should_panic(expected = "Operations of the spec that are not implemented:\n    GET /health")]
+fn incomplete_coverage() {
+    spec_coverage_report(IMPLEMENTED_OPERATIONS, SPEC).assert_complete();
+}
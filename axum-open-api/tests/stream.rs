@@ -0,0 +1,66 @@
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    Router,
+};
+use axum_open_api::{stream::JsonArrayError, OapiRouter};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/http.yaml";
+
+    #[stream]
+    POST /payments/import as pub ImportPayments;
+);
+
+async fn import_payments(mut req: ImportPayments) -> Result<String, JsonArrayError> {
+    let mut total = 0;
+    while let Some(payment) = req.body.next().await {
+        total += payment?.amount;
+    }
+    Ok(total.to_string())
+}
+
+async fn send(content_type: &str, body: &'static str) -> (StatusCode, String) {
+    let request = Request::post("/payments/import")
+        .header(CONTENT_TYPE, content_type)
+        .body(Body::from(body))
+        .unwrap();
+    let response = Router::new()
+        .oapi_route(import_payments)
+        .oneshot(request)
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn streamed_items() {
+    let body = r#"[{"id":1,"amount":100}, {"id":2,"amount":50}]"#;
+    assert_eq!(
+        send("application/json", body).await,
+        (StatusCode::OK, "150".to_owned())
+    );
+    assert_eq!(send("application/json", "[]").await.1, "0");
+}
+
+#[tokio::test]
+async fn invalid_bodies() {
+    // Errors of the items are errors of the stream
+    let body = r#"[{"id":1,"amount":100}, {"id":2}]"#;
+    assert_eq!(
+        send("application/json", body).await.0,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(
+        send("application/json", "{}").await.0,
+        StatusCode::BAD_REQUEST
+    );
+    // The content type is checked at extraction
+    let status = send("text/plain", "[]").await.0;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
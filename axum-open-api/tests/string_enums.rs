@@ -0,0 +1,25 @@
+use axum_open_api::UnknownVariant;
+use axum_open_api_codegen::validate_routes;
+use schemas::Status;
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+);
+
+#[test]
+fn display_and_from_str() {
+    // The values of the spec are used, not the names of the variants
+    assert_eq!(Status::InProgress.to_string(), "in-progress");
+    assert_eq!("in-progress".parse::<Status>(), Ok(Status::InProgress));
+
+    let error = "done".parse::<Status>().unwrap_err();
+    let expected = UnknownVariant {
+        value: "done".to_owned(),
+        expected: &["active", "in-progress"],
+    };
+    assert_eq!(error, expected);
+    assert_eq!(
+        error.to_string(),
+        r#"unknown value "done", expected one of: active, in-progress"#
+    );
+}
@@ -0,0 +1,14 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::Status;
+use strum::IntoEnumIterator;
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+    strum = true;
+);
+
+#[test]
+fn strum_derives() {
+    let values = Status::iter().map(<&'static str>::from).collect::<Vec<_>>();
+    assert_eq!(values, ["active", "in-progress"]);
+}
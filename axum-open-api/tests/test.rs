@@ -1,20 +1,169 @@
+use axum::{
+    body::Body,
+    http::{
+        header::{AUTHORIZATION, WWW_AUTHENTICATE},
+        request::Parts,
+        Request, StatusCode,
+    },
+    response::Response,
+    Json, Router,
+};
+use axum_open_api::{
+    security::{
+        ApiKeyVerifier, BasicVerifier, ClientCertInfo, JsonClaims, OidcValidator, ScopeSource,
+        TokenVerifier,
+    },
+    OapiRouter,
+};
 use axum_open_api_codegen::validate_routes;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use http_body_util::BodyExt;
 use schemas::NestedInlineObject;
+use tower::ServiceExt;
+
 validate_routes!(
     path = "axum-open-api/tests/test-api.yaml";
 
-    // pub mod feed {
-    //     GET     /api/feed/get_posts         as pub GetPosts;
-    //     GET     /api/feed/get_tags          as pub GetTags;
-    //     POST    /api/feed/add_tag           as pub AddTag;
-    //     DELETE  /api/feed/remove_tag        as pub RemoveTag;
-    // }
-
-    // pub mod account {
-    //     POST    /api/account/create         as pub CreateAccount;
-    // }
+    GET     /account                                    as pub GetAccount;
+    GET     /certificate                                as pub GetCertificate;
+    GET     /claims                                     as pub GetClaims;
+    POST    /comments                                   as pub CreateComment;
+    GET     /profile                                    as pub GetProfile;
+    GET     /status                                     as pub GetStatus;
+    GET     /users/{user_id}                            as pub GetUser;
+    GET     /users/{user_id}/posts/{post_id}            as pub GetPost;
+    POST    /users/{user_id}/posts/{post_id}/comment    as pub CreatePostComment;
 );
 
+/// The state of the test router, which accepts the api-key `secret`, the basic credentials
+/// `user:pass:word`, the bearer token `token`, the ID token `id-token`, and the scopes of the
+/// `X-Scopes` header.
+#[derive(Clone)]
+struct TestState;
+
+#[axum::async_trait]
+impl ApiKeyVerifier for TestState {
+    async fn verify_api_key(&self, _scheme: &'static str, key: &str) -> bool {
+        key == "secret"
+    }
+}
+
+#[axum::async_trait]
+impl BasicVerifier for TestState {
+    async fn verify_basic(&self, _scheme: &'static str, username: &str, password: &str) -> bool {
+        username == "user" && password == "pass:word"
+    }
+}
+
+#[axum::async_trait]
+impl TokenVerifier<JsonClaims> for TestState {
+    async fn verify_token(&self, _scheme: &'static str, token: &str) -> Option<JsonClaims> {
+        let claims = serde_json::json!({ "sub": "user" });
+        (token == "token").then(|| claims.as_object().unwrap().clone())
+    }
+}
+
+#[axum::async_trait]
+impl OidcValidator<security::OidcClaims> for TestState {
+    async fn validate(
+        &self,
+        _scheme: &'static str,
+        discovery_url: &'static str,
+        token: &str,
+    ) -> Option<security::OidcClaims> {
+        assert_eq!(discovery_url, security::Oidc::DISCOVERY_URL);
+        let sub = "user".to_owned();
+        (token == "id-token").then_some(security::OidcClaims { sub })
+    }
+}
+
+#[axum::async_trait]
+impl ScopeSource for TestState {
+    async fn scopes(&self, _scheme: &'static str, parts: &Parts) -> Option<Vec<String>> {
+        let scopes = parts.headers.get("X-Scopes")?.to_str().ok()?;
+        Some(scopes.split(' ').map(str::to_owned).collect())
+    }
+}
+
+async fn get_account(req: GetAccount) -> Json<Vec<String>> {
+    Json(vec![req.basic_auth.username, req.basic_auth.password])
+}
+
+async fn get_certificate(req: GetCertificate) -> Json<Vec<String>> {
+    Json(vec![req.mutual_tls.cert.chain.len().to_string()])
+}
+
+async fn get_claims(req: GetClaims) -> Json<Vec<String>> {
+    let sub = &req.bearer_auth.claims["sub"];
+    Json(vec![sub.as_str().unwrap().to_owned()])
+}
+
+async fn get_profile(req: GetProfile) -> Json<Vec<String>> {
+    Json(vec![req.oidc.claims.sub])
+}
+
+async fn get_status(_req: GetStatus) -> Json<Vec<String>> {
+    Json(vec!["ok".to_owned()])
+}
+
+async fn get_user(req: GetUser) -> Json<Vec<String>> {
+    Json(vec![req.user_id, req.api_key_auth.key])
+}
+
+async fn get_post(req: GetPost) -> Json<Vec<String>> {
+    let post_ids = req.post_id.iter().map(i64::to_string);
+    Json([req.user_id].into_iter().chain(post_ids).collect())
+}
+
+async fn create_post_comment(req: CreatePostComment) -> Json<Vec<String>> {
+    Json(vec![req.user_id])
+}
+
+async fn create_comment(req: CreateComment) -> Json<Vec<String>> {
+    // The scheme that satisfied the requirements
+    match (req.o_auth_2, req.basic_auth) {
+        (Some(_), _) => Json(vec!["OAuth2".to_owned()]),
+        (None, Some(basic_auth)) => Json(vec![basic_auth.username]),
+        (None, None) => unreachable!(),
+    }
+}
+
+fn router() -> Router {
+    Router::new()
+        .oapi_route(get_account)
+        .oapi_route(get_certificate)
+        .oapi_route(get_claims)
+        .oapi_route(create_comment)
+        .oapi_route(get_profile)
+        .oapi_route(get_status)
+        .oapi_route(get_user)
+        .oapi_route(get_post)
+        .oapi_route(create_post_comment)
+        .with_state(TestState)
+}
+
+async fn send(request: Request<Body>) -> Response {
+    router().oneshot(request).await.unwrap()
+}
+
+/// A `GET` request, with the `Authorization` header if there is one.
+fn get(uri: &str, authorization: Option<&str>) -> Request<Body> {
+    let mut request = Request::get(uri);
+    if let Some(authorization) = authorization {
+        request = request.header(AUTHORIZATION, authorization);
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+fn basic(credentials: &str) -> String {
+    format!("Basic {}", STANDARD.encode(credentials))
+}
+
+async fn body(response: Response) -> Vec<String> {
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
 #[test]
 fn type_alias_schemas() {
     let _: String = schemas::StringAlias::from("hello");
@@ -31,6 +180,7 @@ fn object_schema() {
         req_id: 20,
         name_ref: Some("hello".to_string()),
         inline_object: Some(NestedInlineObject { id: Some(10) }),
+        anonymous_object: Some(schemas::ObjectSchemaAnonymousObject { id: None }),
     };
 }
 
@@ -46,3 +196,221 @@ fn one_of_schema() {
     }
 }
 
+#[tokio::test]
+async fn api_key_security_scheme() {
+    let user = |key| {
+        let request = Request::get("/users/1").header("X-API-Key", key);
+        request.body(Body::empty()).unwrap()
+    };
+    let response = send(user("wrong")).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.into_body().collect().await.unwrap().to_bytes(),
+        "Invalid credentials for security scheme `ApiKeyAuth`"
+    );
+
+    let response = send(user("secret")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["1", "secret"]);
+}
+
+#[tokio::test]
+async fn basic_security_scheme() {
+    // Missing or invalid credentials are challenged
+    let invalid = [
+        None,
+        Some("Basic not-base64!".to_owned()),
+        Some(basic("no colon")),
+        Some(basic("user:wrong")),
+        Some(format!("Bearer {}", STANDARD.encode("user:pass:word"))),
+    ];
+    for authorization in invalid {
+        let response = send(get("/account", authorization.as_deref())).await;
+        assert_eq!(
+            response.status(),
+            StatusCode::UNAUTHORIZED,
+            "{authorization:?}"
+        );
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            "Basic realm=\"BasicAuth\""
+        );
+    }
+
+    // The password is everything after the first colon
+    let response = send(get("/account", Some(&basic("user:pass:word")))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["user", "pass:word"]);
+
+    // The auth-scheme is case-insensitive
+    let authorization = basic("user:pass:word").replace("Basic", "basic");
+    let response = send(get("/account", Some(&authorization))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn bearer_security_scheme() {
+    // Without `x-claims`, the claims are any json object
+    let response = send(get("/claims", Some("Bearer token"))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["user"]);
+
+    for authorization in [None, Some("Bearer wrong"), Some("Basic token")] {
+        let response = send(get("/claims", authorization)).await;
+        assert_eq!(
+            response.status(),
+            StatusCode::UNAUTHORIZED,
+            "{authorization:?}"
+        );
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            "Bearer realm=\"BearerAuth\""
+        );
+    }
+}
+
+#[tokio::test]
+async fn oauth2_security_scheme() {
+    let comment = |scopes: &str| {
+        let request = Request::post("/comments")
+            .header("Content-Type", "application/json")
+            .header("X-Scopes", scopes);
+        request.body(Body::from("{}")).unwrap()
+    };
+
+    let response = send(comment("read")).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        response.into_body().collect().await.unwrap().to_bytes(),
+        "Missing scopes for security scheme `OAuth2`: write"
+    );
+
+    let response = send(comment("write")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["OAuth2"]);
+}
+
+#[tokio::test]
+async fn open_id_connect_security_scheme() {
+    assert_eq!(security::Oidc::ISSUER, "https://example.com");
+
+    let response = send(get("/profile", Some("Bearer id-token"))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["user"]);
+
+    for authorization in [None, Some("Bearer wrong")] {
+        let response = send(get("/profile", authorization)).await;
+        assert_eq!(
+            response.status(),
+            StatusCode::UNAUTHORIZED,
+            "{authorization:?}"
+        );
+        assert_eq!(response.headers()[WWW_AUTHENTICATE], "Bearer");
+    }
+}
+
+#[tokio::test]
+async fn mutual_tls_security_scheme() {
+    let response = send(get("/certificate", None)).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // The TLS acceptor inserts the verified certificate chain
+    let mut request = get("/certificate", None);
+    let chain = vec![b"client".to_vec(), b"ca".to_vec()];
+    request.extensions_mut().insert(ClientCertInfo { chain });
+    let response = send(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["2"]);
+}
+
+#[test]
+fn expansion_snapshot() {
+    let expanded = axum_open_api_codegen::expand_validate_routes!(
+        path = "axum-open-api/tests/test-api.yaml";
+
+        pub mod users {
+            GET /users/{user_id} as pub GetUser;
+        }
+    );
+    insta::assert_snapshot!(expanded);
+}
+
+#[tokio::test]
+async fn global_security_requirement() {
+    let request = Request::get("/users/1").body(Body::empty()).unwrap();
+    assert_eq!(send(request).await.status(), StatusCode::UNAUTHORIZED);
+
+    let request = Request::get("/users/1")
+        .header("X-API-Key", "secret")
+        .body(Body::empty())
+        .unwrap();
+    let response = send(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["1", "secret"]);
+}
+
+#[tokio::test]
+async fn public_operation() {
+    // `security: []` overrides the global requirement
+    let response = send(get("/status", None)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["ok"]);
+}
+
+#[tokio::test]
+async fn alternative_security_requirements() {
+    let comment = || Request::post("/comments").header("Content-Type", "application/json");
+    let request = comment().body(Body::from("{}")).unwrap();
+    assert_eq!(send(request).await.status(), StatusCode::UNAUTHORIZED);
+
+    // The first alternative
+    let request = comment()
+        .header("X-Scopes", "read write")
+        .body(Body::from("{}"))
+        .unwrap();
+    let response = send(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["OAuth2"]);
+
+    // The second alternative, when the first one is not satisfied
+    let request = comment()
+        .header("X-Scopes", "read")
+        .header(AUTHORIZATION, basic("user:pass:word"))
+        .body(Body::from("{}"))
+        .unwrap();
+    let response = send(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["user"]);
+
+    // Without either, the request gets the rejection of the first alternative
+    let request = comment()
+        .header("X-Scopes", "read")
+        .header(AUTHORIZATION, basic("user:wrong"))
+        .body(Body::from("{}"))
+        .unwrap();
+    assert_eq!(send(request).await.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn array_path_params() {
+    // The items of an array are separated by commas
+    let post = |uri| {
+        let request = Request::get(uri).header("X-API-Key", "secret");
+        request.body(Body::empty()).unwrap()
+    };
+    let response = send(post("/users/1/posts/2,3?amount=3")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["1", "2", "3"]);
+
+    let response = send(post("/users/1/posts/2?amount=3")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body(response).await, ["1", "2"]);
+
+    let response = send(post("/users/1/posts/2,x?amount=3")).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // The generated uris of the operation round-trip
+    let uri = GetPost::uri(&"1".to_owned(), &vec![2, 3].into(), &None, &3);
+    let response = send(post(&uri)).await;
+    assert_eq!(body(response).await, ["1", "2", "3"]);
+}
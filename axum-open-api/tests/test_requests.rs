@@ -0,0 +1,43 @@
+#![cfg(feature = "test-helpers")]
+
+use axum::{body::Body, http::Request, Json, Router};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use schemas::User;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users              as pub ListUsers;
+    POST    /users              as pub CreateUser;
+    GET     /users/{user_id}    as pub GetUser;
+);
+
+async fn send(request: Request<Body>) -> String {
+    let router = Router::new()
+        .oapi_route(|req: ListUsers| async move { format!("page {:?}", req.page) })
+        .oapi_route(|req: CreateUser| async move { Json(req.body) })
+        .oapi_route(|req: GetUser| async move { format!("user {}", req.user_id) });
+    let response = router.oneshot(request).await.unwrap();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn request_builders() {
+    assert_eq!(send(GetUser::request().user_id(3).build()).await, "user 3");
+    assert_eq!(
+        send(ListUsers::request().query_page(2).build()).await,
+        "page Some(2)"
+    );
+    assert_eq!(send(ListUsers::request().build()).await, "page None");
+
+    let bob = User {
+        id: 2,
+        name: "Bob".to_owned(),
+    };
+    let request = CreateUser::request().json_body(&bob).build();
+    assert_eq!(send(request).await, r#"{"id":2,"name":"Bob"}"#);
+}
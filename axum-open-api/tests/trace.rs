@@ -0,0 +1,168 @@
+#![cfg(feature = "tracing")]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::{
+    trace::{set_redaction_hook, OtelLayer},
+    OapiRouter,
+};
+use axum_open_api_codegen::validate_routes;
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+use tower::ServiceExt;
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+use tracing_core::span::Current;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+
+    GET     /users              as pub ListUsers;
+    DELETE  /users/{user_id}    as pub DeleteUser;
+);
+
+/// The metadata and fields of a recorded span.
+type RecordedSpan = (&'static Metadata<'static>, Vec<String>);
+
+/// A subscriber that records the name and fields of every span, and which span is entered.
+#[derive(Clone, Default)]
+struct Spans {
+    spans: Arc<Mutex<Vec<RecordedSpan>>>,
+    entered: Arc<Mutex<Vec<Id>>>,
+}
+
+impl Spans {
+    /// The fields of the span with the name, like `otel.name=listUsers`.
+    fn fields(&self, name: &str) -> Vec<String> {
+        let spans = self.spans.lock().unwrap();
+        let span = spans.iter().find(|span| span.0.name() == name);
+        span.expect("the span should be recorded").1.clone()
+    }
+}
+
+struct Fields<'a>(&'a mut Vec<String>);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push(format!("{}={value:?}", field.name()));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push(format!("{}={value}", field.name()));
+    }
+}
+
+impl Subscriber for Spans {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut spans = self.spans.lock().unwrap();
+        let mut fields = Vec::new();
+        span.record(&mut Fields(&mut fields));
+        spans.push((span.metadata(), fields));
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        let fields = &mut spans[span.into_u64() as usize - 1].1;
+        values.record(&mut Fields(fields));
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, span: &Id) {
+        self.entered.lock().unwrap().push(span.clone());
+    }
+
+    fn exit(&self, _span: &Id) {
+        self.entered.lock().unwrap().pop();
+    }
+
+    fn current_span(&self) -> Current {
+        let Some(span) = self.entered.lock().unwrap().last().cloned() else {
+            return Current::none();
+        };
+        let metadata = self.spans.lock().unwrap()[span.into_u64() as usize - 1].0;
+        Current::new(span, metadata)
+    }
+}
+
+async fn list_users(_req: ListUsers) {}
+
+async fn delete_user(_req: DeleteUser) {}
+
+#[tokio::test]
+async fn extraction_spans() {
+    let spans = Spans::default();
+    let _guard = tracing::subscriber::set_default(spans.clone());
+    set_redaction_hook(|name| name == "page");
+    let router = Router::new().oapi_route(list_users).oapi_route(delete_user);
+
+    let request = Request::get("/users?page=2").body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let fields = spans.fields("oapi_extract");
+    assert!(
+        fields.contains(&"otel.name=listUsers".to_owned()),
+        "{fields:?}"
+    );
+    assert!(
+        fields.contains(&"http.route=/users".to_owned()),
+        "{fields:?}"
+    );
+    assert!(
+        fields.contains(&"params=page=[redacted]".to_owned()),
+        "{fields:?}"
+    );
+
+    // Operations without an operationId are named after their method and path
+    spans.spans.lock().unwrap().clear();
+    let request = Request::delete("/users/3").body(Body::empty()).unwrap();
+    router.oneshot(request).await.unwrap();
+    let fields = spans.fields("oapi_extract");
+    assert!(
+        fields.contains(&"otel.name=DELETE /users/{user_id}".to_owned()),
+        "{fields:?}"
+    );
+    assert!(
+        fields.contains(&"params=user_id=3".to_owned()),
+        "{fields:?}"
+    );
+}
+
+#[tokio::test]
+async fn otel_layer() {
+    let spans = Spans::default();
+    let _guard = tracing::subscriber::set_default(spans.clone());
+    let router = Router::new().oapi_route(delete_user).layer(OtelLayer);
+
+    let request = Request::delete("/users/3").body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The route is recorded with the path template of the spec
+    let fields = spans.fields("http_request");
+    for field in [
+        "otel.name=DELETE /users/{user_id}",
+        "otel.kind=server",
+        "http.request.method=DELETE",
+        "http.route=/users/{user_id}",
+        "url.path=/users/3",
+        "url.template=/users/{user_id}",
+    ] {
+        assert!(fields.contains(&field.to_owned()), "{field}: {fields:?}");
+    }
+}
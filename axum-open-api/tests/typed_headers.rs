@@ -0,0 +1,71 @@
+#![cfg(feature = "typed-header")]
+
+use axum::{
+    body::Body,
+    http::{
+        header::{IF_MODIFIED_SINCE, USER_AGENT},
+        Request, StatusCode,
+    },
+    Router,
+};
+use axum_open_api::{
+    typed_header::{
+        headers::{IfModifiedSince, UserAgent},
+        TypedHeader,
+    },
+    OapiRouter,
+};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use std::time::SystemTime;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/params.yaml";
+    typed_headers = true;
+
+    GET /downloads as pub ListDownloads;
+);
+
+async fn list_downloads(req: ListDownloads) -> String {
+    let TypedHeader(user_agent): TypedHeader<UserAgent> = req.user_agent;
+    let modified_since: Option<TypedHeader<IfModifiedSince>> = req.if_modified_since;
+    let modified_since = modified_since.map(|TypedHeader(since)| SystemTime::from(since));
+    format!("{} {}", user_agent.as_str(), modified_since.is_some())
+}
+
+async fn send(headers: &[(&str, &str)]) -> (StatusCode, String) {
+    let mut request = Request::get("/downloads");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = Router::new()
+        .oapi_route(list_downloads)
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn typed_headers() {
+    let user_agent = (USER_AGENT.as_str(), "curl/8.0");
+    let date = "Wed, 21 Oct 2015 07:28:00 GMT";
+    let (status, body) = send(&[user_agent, (IF_MODIFIED_SINCE.as_str(), date)]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "curl/8.0 true");
+    assert_eq!(send(&[user_agent]).await.1, "curl/8.0 false");
+}
+
+#[tokio::test]
+async fn invalid_headers() {
+    // The required header is missing, or the optional one is invalid
+    assert_eq!(send(&[]).await.0, StatusCode::BAD_REQUEST);
+    let headers = [
+        (USER_AGENT.as_str(), "curl/8.0"),
+        (IF_MODIFIED_SINCE.as_str(), "yesterday"),
+    ];
+    assert_eq!(send(&headers).await.0, StatusCode::BAD_REQUEST);
+}
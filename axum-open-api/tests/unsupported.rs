@@ -0,0 +1,16 @@
+use axum_open_api_codegen::validate_routes;
+
+// `anyOf` fails the compilation by default
+validate_routes!(
+    path = "axum-open-api/tests/components.yaml";
+    unsupported = { composition: allow, formats: deny };
+
+    GET /pets as pub ListPets;
+);
+
+#[test]
+fn allowed_composition() {
+    // The `anyOf` of a string and an integer falls back to any json
+    let values: [schemas::Unreferenced; 2] = [serde_json::json!("a"), serde_json::json!(1)];
+    assert_eq!(serde_json::to_string(&values).unwrap(), r#"["a",1]"#);
+}
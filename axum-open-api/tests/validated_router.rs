@@ -0,0 +1,34 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Router,
+};
+use axum_open_api::OapiRouter;
+use axum_open_api_codegen::validate_routes;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/operations.yaml";
+    validated_router = validated_router;
+
+    GET /users/{user_id} as pub GetUser;
+);
+
+#[tokio::test]
+async fn routes_outside_the_spec() {
+    // `/users/me` would fail the compilation, as it shadows `/users/{user_id}`
+    let router: Router = validated_router!(
+        Router::new().oapi_route(|_req: GetUser| async { StatusCode::NO_CONTENT }),
+        "/metrics" => get(|| async { "up 1" }),
+    );
+
+    for (uri, status) in [
+        ("/metrics", StatusCode::OK),
+        ("/users/1", StatusCode::NO_CONTENT),
+    ] {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), status);
+    }
+}
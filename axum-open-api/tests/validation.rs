@@ -0,0 +1,67 @@
+#![cfg(feature = "validation")]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use axum_open_api::{
+    spec::SpecDocument,
+    validation::{SpecValidationLayer, ValidationMode},
+    NoContent, OapiRouter,
+};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use std::sync::Mutex;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/validation.yaml";
+
+    POST /users as pub CreateUser;
+);
+
+async fn create_user(_req: CreateUser) -> NoContent {
+    NoContent
+}
+
+fn router(mode: ValidationMode) -> Router {
+    let spec = SpecDocument::parse(include_str!("validation.yaml")).unwrap();
+    let layer = SpecValidationLayer::new(spec).mode(mode);
+    Router::new().oapi_route(create_user).layer(layer)
+}
+
+async fn send(router: Router, body: &'static str) -> (StatusCode, String) {
+    let request = Request::post("/users")
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn rejected_requests() {
+    let router = router(ValidationMode::Reject);
+    let (status, _) = send(router.clone(), r#"{"name":"Ann","age":3}"#).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    // The generated code does not check `minLength` and `minimum`, but the layer does
+    let (status, body) = send(router, r#"{"name":"An","age":-1}"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body.lines().count(), 2, "{body}");
+}
+
+static REPORTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[tokio::test]
+async fn reported_requests() {
+    let router = router(ValidationMode::Report(|violations| {
+        REPORTED.lock().unwrap().extend_from_slice(violations)
+    }));
+    let (status, _) = send(router, r#"{"name":"An"}"#).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(REPORTED.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,49 @@
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Extension, Router,
+};
+use axum_open_api::{MatchedOperation, OapiRouter, OperationMeta};
+use axum_open_api_codegen::validate_routes;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+validate_routes!(
+    path = "axum-open-api/tests/events.yaml";
+
+    POST webhook newEvent as pub NewEvent;
+);
+
+async fn new_event(operation: Extension<MatchedOperation>, req: NewEvent) -> String {
+    format!("{} {}", operation.path_template, req.body.kind)
+}
+
+fn router() -> Router {
+    Router::new().oapi_webhook("/hooks/new-event", new_event)
+}
+
+#[test]
+fn webhook_meta() {
+    assert_eq!(NewEvent::method(), Method::POST);
+    assert_eq!(NewEvent::path_template(), "newEvent");
+    assert_eq!(NewEvent::operation_id(), Some("newEvent"));
+}
+
+#[tokio::test]
+async fn webhook_route() {
+    let request = Request::post("/hooks/new-event")
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"kind":"created"}"#))
+        .unwrap();
+    let response = router().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(bytes, "newEvent created");
+
+    // The webhook is only routed at its method
+    let request = Request::get("/hooks/new-event")
+        .body(Body::empty())
+        .unwrap();
+    let response = router().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
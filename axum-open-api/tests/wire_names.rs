@@ -0,0 +1,23 @@
+use axum_open_api_codegen::validate_routes;
+use schemas::{Profile, Status};
+
+validate_routes!(
+    path = "axum-open-api/tests/schemas.yaml";
+);
+
+#[test]
+fn wire_names() {
+    // The fields are snake case, and renamed to the properties of the spec
+    let profile = Profile {
+        created_at: "2024-01-01".to_owned(),
+        user_id: "1".to_owned(),
+        r#type: "admin".to_owned(),
+    };
+    let json = serde_json::json!({ "createdAt": "2024-01-01", "userID": "1", "type": "admin" });
+    assert_eq!(serde_json::to_value(profile).unwrap(), json);
+    // The variants are renamed to the values of the spec
+    assert_eq!(
+        serde_json::to_value(Status::InProgress).unwrap(),
+        "in-progress"
+    );
+}